@@ -0,0 +1,127 @@
+//! A bounded log of the actions performed and their outcomes.
+
+use crate::theme::Theme;
+use ratatui::{
+    prelude::{BlockExt, Buffer, Rect},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Widget},
+};
+use std::{
+    collections::VecDeque,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The number of entries kept before the oldest are dropped.
+const CAPACITY: usize = 100;
+
+#[derive(Debug, Default)]
+pub struct ActionLog {
+    entries: VecDeque<LogEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    time: SystemTime,
+    pub text: String,
+    pub error: bool,
+}
+
+impl LogEntry {
+    pub fn info(text: impl Into<String>) -> Self {
+        Self {
+            time: SystemTime::now(),
+            text: text.into(),
+            error: false,
+        }
+    }
+
+    pub fn error(text: impl Into<String>) -> Self {
+        Self {
+            time: SystemTime::now(),
+            text: text.into(),
+            error: true,
+        }
+    }
+
+    /// The time of this entry formatted as `HH:MM:SS` in UTC.
+    fn timestamp(&self) -> String {
+        let secs = self
+            .time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        format!(
+            "{:02}:{:02}:{:02}",
+            secs / 3600 % 24,
+            secs / 60 % 60,
+            secs % 60
+        )
+    }
+}
+
+impl ActionLog {
+    pub fn push(&mut self, entry: LogEntry) {
+        if self.entries.len() == CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+}
+
+/// Widget displaying the most recent entries of an [`ActionLog`] that fit in its area.
+pub struct LogPane<'a> {
+    log: &'a ActionLog,
+    block: Option<Block<'a>>,
+    theme: Theme,
+}
+
+impl<'a> LogPane<'a> {
+    pub fn new(log: &'a ActionLog) -> Self {
+        Self {
+            log,
+            block: None,
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+}
+
+impl Widget for LogPane<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if let Some(block) = &self.block {
+            block.render(area, buf);
+        }
+        let area = self.block.inner_if_some(area);
+
+        if self.log.entries.is_empty() {
+            Line::styled("Nothing logged yet", self.theme.dim()).render(area, buf);
+            return;
+        }
+
+        let visible = self.log.entries.len().min(area.height as usize);
+        let entries = self.log.entries.range(self.log.entries.len() - visible..);
+        for (y, entry) in entries.enumerate() {
+            let text = Span::raw(entry.text.as_str());
+            let line = Line::from(vec![
+                Span::styled(entry.timestamp(), self.theme.dim()),
+                Span::raw(" "),
+                if entry.error {
+                    text.fg(self.theme.error)
+                } else {
+                    text
+                },
+            ]);
+            buf.set_line(area.x, area.y + y as u16, &line, area.width);
+        }
+    }
+}
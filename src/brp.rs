@@ -1,41 +1,153 @@
-use crate::{Message, ThreadQuitToken};
-use anyhow::anyhow;
+use crate::{
+    action_log::LogEntry,
+    components::ComponentErrors,
+    debug_log,
+    diagnosis::Diagnosis,
+    entity_filter::EntityFilter,
+    popup::ErrorPopup,
+    stats,
+    theme::Theme,
+    transport::{Socket, Transport},
+    Message, ThreadQuitToken, WakeToken,
+};
 use bevy_ecs::entity::Entity;
 use bevy_remote::{
     builtin_methods::{
-        BrpDestroyParams, BrpGetParams, BrpGetResponse, BrpListParams, BrpListResponse, BrpQuery,
-        BrpQueryFilter, BrpQueryParams, BrpQueryResponse, BrpRemoveParams,
+        BrpDestroyParams, BrpGetParams, BrpGetResponse, BrpInsertParams, BrpListParams,
+        BrpListResponse, BrpQuery, BrpQueryFilter, BrpQueryParams, BrpQueryResponse,
+        BrpRemoveParams, BrpReparentParams, BrpSpawnParams, BrpSpawnResponse,
     },
-    BrpPayload, BrpRequest,
+    error_codes, BrpError, BrpPayload, BrpRequest,
 };
+use disqualified::ShortName;
 use ratatui::{
     style::Stylize,
     text::{Line, Span},
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
 use std::{
+    collections::{HashMap, HashSet},
+    fmt,
     net::{IpAddr, Ipv4Addr, SocketAddr},
-    sync::mpsc,
+    str::FromStr,
+    sync::{mpsc, Arc, Mutex, PoisonError},
     time::{Duration, Instant},
 };
 
-pub const DEFAULT_SOCKET: SocketAddr =
-    SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 15702);
+pub const DEFAULT_SOCKET: Socket = Socket::Tcp(SocketAddr::new(
+    IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+    15702,
+));
 pub const QUERY_COOLDOWN: Duration = Duration::from_millis(100);
+/// The longest time to wait between attempts to reach an unresponsive app.
+pub const MAX_RETRY_DELAY: Duration = Duration::from_secs(5);
+/// How long the selected entity has to stay selected before its components are fetched, so
+/// scrolling quickly through entities doesn't query each one passed over.
+const SELECTION_DEBOUNCE: Duration = Duration::from_millis(150);
+/// The default of [`set_min_request_delay`].
+pub const DEFAULT_MIN_REQUEST_DELAY: Duration = Duration::from_millis(20);
+
+/// The shortest time the querying threads wait between requests, see [`request_delay`].
+static MIN_REQUEST_DELAY: Mutex<Duration> = Mutex::new(DEFAULT_MIN_REQUEST_DELAY);
+
+/// Set the shortest time the querying threads wait between requests.
+pub fn set_min_request_delay(delay: Duration) {
+    *MIN_REQUEST_DELAY
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = delay;
+}
+
+/// How long to wait for the rest of the `cooldown` since `last_time`. Always at least the minimum
+/// request delay, so an app slower to respond than the cooldown isn't asked again the moment it
+/// does.
+pub fn request_delay(cooldown: Duration, last_time: Instant) -> Duration {
+    let min_delay = *MIN_REQUEST_DELAY
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    cooldown.saturating_sub(last_time.elapsed()).max(min_delay)
+}
+
+/// Lists the JSON schemas of the registered types. It was added after Bevy 0.15 so isn't in
+/// [`bevy_remote::builtin_methods`], older apps respond with a method not found error.
+pub const BRP_REGISTRY_SCHEMA_METHOD: &str = "bevy/registry/schema";
+/// Sets a single field of a component. Like [`BRP_REGISTRY_SCHEMA_METHOD`] it was added after
+/// Bevy 0.15.
+pub const BRP_MUTATE_COMPONENT_METHOD: &str = "bevy/mutate_component";
+
+/// Where `Name` has lived across Bevy versions, tried in order unless a path is configured.
+pub const NAME_COMPONENTS: [&str; 2] = ["bevy_core::name::Name", "bevy_ecs::name::Name"];
+const PARENT_COMPONENT: &str = "bevy_hierarchy::components::parent::Parent";
+const CHILDREN_COMPONENT: &str = "bevy_hierarchy::components::children::Children";
+/// The components that make up the hierarchy, which can't be copied onto another entity without
+/// leaving it inconsistent.
+pub const HIERARCHY_COMPONENTS: [&str; 2] = [PARENT_COMPONENT, CHILDREN_COMPONENT];
+
+/// How entities are labelled in the entities list.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EntityLabel {
+    /// The name followed by the id.
+    #[default]
+    Both,
+    /// Just the name, or the id for entities without one so they can still be told apart.
+    Name,
+    Id,
+}
 
-#[derive(Debug)]
+impl EntityLabel {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Both => Self::Name,
+            Self::Name => Self::Id,
+            Self::Id => Self::Both,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct EntityMeta {
     pub id: Entity,
+    /// `None` for entities without a `Name` or whose `Name` isn't in a shape brptui understands.
     pub name: Option<String>,
+    pub parent: Option<Entity>,
+    /// The values of the extra components requested for every entity.
+    pub extra: HashMap<String, Value>,
 }
 
 impl EntityMeta {
-    pub fn title(&self) -> Line {
-        Line::from(vec![
-            Span::raw(self.name()).bold(),
-            Span::raw(" "),
-            Span::raw(self.id.to_string()).dim(),
-        ])
+    pub fn title(&self, label: EntityLabel, theme: &Theme) -> Line<'_> {
+        match (label, &self.name) {
+            (EntityLabel::Both, _) => Line::from(vec![
+                Span::raw(self.name()).bold(),
+                Span::raw(" "),
+                Span::styled(self.id.to_string(), theme.dim()),
+            ]),
+            (EntityLabel::Name, Some(name)) => Line::from(Span::raw(name.as_str()).bold()),
+            (EntityLabel::Name, None) | (EntityLabel::Id, _) => {
+                Line::from(Span::raw(self.id.to_string()).bold())
+            }
+        }
+    }
+
+    /// The text of the [`EntityMeta::title`] without styling.
+    pub fn label(&self, label: EntityLabel) -> String {
+        self.title(label, &Theme::default()).to_string()
+    }
+
+    /// Extra details about this entity, such as its parent and the values of the extra
+    /// components requested for every entity. Returns [`None`] when there is nothing to show.
+    pub fn subtitle(&self) -> Option<Line<'_>> {
+        let mut details = Vec::new();
+        if let Some(parent) = self.parent {
+            details.push(format!("child of {parent}"));
+        }
+        let mut extra: Vec<_> = self.extra.iter().collect();
+        extra.sort_by_key(|(name, _)| *name);
+        for (name, value) in extra {
+            details.push(format!("{}={value}", ShortName(name)));
+        }
+        (!details.is_empty()).then(|| Line::raw(details.join("  ")))
     }
 
     pub fn name(&self) -> String {
@@ -43,90 +155,479 @@ impl EntityMeta {
     }
 }
 
+/// If `entity` is `ancestor` or one of its descendants, according to the parents of the given
+/// entities.
+pub fn is_descendant(entities: &[EntityMeta], entity: Entity, ancestor: Entity) -> bool {
+    let mut current = Some(entity);
+    // Bound the walk by the number of entities in case the hierarchy data contains a cycle.
+    for _ in 0..=entities.len() {
+        match current {
+            Some(e) if e == ancestor => return true,
+            Some(e) => {
+                current = entities
+                    .iter()
+                    .find(|meta| meta.id == e)
+                    .and_then(|m| m.parent)
+            }
+            None => return false,
+        }
+    }
+    false
+}
+
+/// An entity id as typed, either `12v3` as entities are shown, `12` for whichever generation has
+/// that index, or the bits of the entity as used in exports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntityId {
+    index: u32,
+    generation: Option<u32>,
+}
+
+impl EntityId {
+    pub fn matches(&self, entity: Entity) -> bool {
+        entity.index() == self.index && self.generation.is_none_or(|g| entity.generation() == g)
+    }
+}
+
+impl From<Entity> for EntityId {
+    fn from(entity: Entity) -> Self {
+        Self {
+            index: entity.index(),
+            generation: Some(entity.generation()),
+        }
+    }
+}
+
+impl FromStr for EntityId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((index, generation)) = s.split_once('v') {
+            return Ok(Self {
+                index: index.parse()?,
+                generation: Some(generation.parse()?),
+            });
+        }
+        let number: u64 = s.parse()?;
+        Ok(match u32::try_from(number) {
+            Ok(index) => Self {
+                index,
+                generation: None,
+            },
+            Err(_) => Self::from(Entity::try_from_bits(number)?),
+        })
+    }
+}
+
+impl fmt::Display for EntityId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.generation {
+            Some(generation) => write!(f, "{}v{generation}", self.index),
+            None => write!(f, "{}", self.index),
+        }
+    }
+}
+
+/// What the entities are queried with.
+#[derive(Debug, Default, Clone)]
+pub struct EntityQueryOptions {
+    /// Components fetched for every entity alongside its name and parent.
+    pub extra_components: Vec<String>,
+    /// The component entities are named by, tried before the [`NAME_COMPONENTS`].
+    pub name_component: Option<String>,
+    /// Query just the ids until they are listed once, so the entities of a large world show up
+    /// before their names and parents are filled in.
+    pub warm_start: bool,
+}
+
 /// Query the connected BRP-enabled Bevy app every [`QUERY_COOLDOWN`] seconds.
 ///
+/// Only entities matching the shared `filter` are listed, it is cleared if the app rejects it. While the
+/// app can't be reached the delay between attempts backs off up to [`MAX_RETRY_DELAY`], the
+/// `waker` can be used to skip the wait and try again immediately.
+///
+/// The whole list is queried each time since BRP has no way to watch a query, `bevy/list+watch`
+/// only watches the components of a single entity.
+///
 /// Resulting [`Message`]s will be sent using the given [`mpsc::Sender`] to the
-/// main thread to be handled.
-pub fn handle_entity_querying(tx: mpsc::Sender<Message>, socket: &SocketAddr) {
+/// main thread to be handled. Returns once the main thread stops receiving or quits with the
+/// `quit` token.
+pub fn handle_entity_querying(
+    tx: mpsc::Sender<Message>,
+    socket: &Socket,
+    options: EntityQueryOptions,
+    filter: Arc<Mutex<EntityFilter>>,
+    waker: WakeToken,
+    quit: ThreadQuitToken,
+) {
+    let EntityQueryOptions {
+        extra_components,
+        name_component,
+        warm_start,
+    } = options;
+    // Found again after failures since the app may have been restarted with another Bevy version.
+    let mut found = None;
+    let mut failures = 0;
+    let mut ids_only = warm_start;
     let mut last_time = Instant::now();
     loop {
+        if quit.should_quit() {
+            return;
+        }
+        if found.is_none() && !ids_only {
+            found = find_meta_components(socket, name_component.as_deref(), &extra_components).ok();
+            let unregistered = found.as_ref().map_or(&[][..], |found| &found.unregistered);
+            if !unregistered.is_empty() {
+                let text = format!(
+                    "Leaving out extra components the app doesn't register: {}",
+                    unregistered.join(", ")
+                );
+                if tx.send(Message::Log(LogEntry::error(text))).is_err() {
+                    return;
+                }
+            }
+        }
+        let MetaComponents {
+            name,
+            parent,
+            extra,
+            ..
+        } = found.clone().unwrap_or_default();
+        let mut option = Vec::new();
+        if !ids_only {
+            option.extend(parent.clone());
+            option.extend(name.clone());
+            option.extend(extra.iter().cloned());
+        }
+
+        let EntityFilter { with, without } = filter
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        let filtered = !with.is_empty() || !without.is_empty();
         let params = BrpQueryParams {
             data: BrpQuery {
-                option: vec!["bevy_core::name::Name".to_string()],
+                option,
                 ..Default::default()
             },
-            filter: BrpQueryFilter::default(),
+            filter: BrpQueryFilter { with, without },
         };
 
-        if let Ok(response) = query_request(socket, params) {
-            let mut entities: Vec<_> = response
-                .iter()
-                .map(|row| EntityMeta {
-                    id: row.entity,
-                    name: row
-                        .components
-                        .get("bevy_core::name::Name")
-                        .map(|name| name.get("name").unwrap().as_str().unwrap().to_string()),
-                })
-                .collect();
+        let result = query_request(socket, params);
+        if let (Err(err), true) = (&result, filtered) {
+            // The app is still there, it just doesn't know a component in the filter.
+            if err.downcast_ref::<RemoteError>().is_some() {
+                *filter.lock().unwrap_or_else(PoisonError::into_inner) = EntityFilter::default();
+                let title = "Failed to filter entities";
+                let _ = tx.send(Message::Log(LogEntry::error(format!("{title}: {err:#}"))));
+                if tx
+                    .send(Message::ShowError(ErrorPopup::new(title, err)))
+                    .is_err()
+                {
+                    return;
+                }
+                continue;
+            }
+        }
+        match result {
+            Ok(response) => {
+                let mut entities: Vec<_> = response
+                    .iter()
+                    .map(|row| EntityMeta {
+                        id: row.entity,
+                        name: name
+                            .as_ref()
+                            .and_then(|name| row.components.get(name))
+                            .and_then(parse_name),
+                        parent: parent
+                            .as_ref()
+                            .and_then(|parent| row.components.get(parent))
+                            .and_then(|parent| serde_json::from_value(parent.clone()).ok()),
+                        extra: extra
+                            .iter()
+                            .filter_map(|name| {
+                                Some((name.clone(), row.components.get(name)?.clone()))
+                            })
+                            .collect(),
+                    })
+                    .collect();
 
-            entities.sort_by_key(|e| e.id);
-            tx.send(Message::UpdateEntities(entities)).unwrap();
-        } else {
-            tx.send(Message::CommunicationFailed).unwrap();
-        };
+                entities.sort_by_key(|e| e.id);
+                failures = 0;
+                // The main thread has stopped receiving once the app is shutting down.
+                if tx.send(Message::UpdateEntities(entities)).is_err() {
+                    return;
+                }
+                // The details are filled in straight away rather than after the cooldown.
+                if ids_only {
+                    ids_only = false;
+                    last_time = Instant::now();
+                    continue;
+                }
+            }
+            Err(err) => {
+                failures += 1;
+                found = None;
+                // The reason is worked out once for each run of failures since connecting to check
+                // can take a while.
+                if failures == 1 {
+                    let diagnosis = Diagnosis::new(socket, &err);
+                    if tx.send(Message::Diagnosed(diagnosis)).is_err() {
+                        return;
+                    }
+                }
+                if tx.send(Message::CommunicationFailed).is_err() {
+                    return;
+                }
+            }
+        }
 
         // Sleep for the remaining time until the next query.
-        std::thread::sleep(QUERY_COOLDOWN.saturating_sub(last_time.elapsed()));
+        let cooldown = if failures > 0 {
+            QUERY_COOLDOWN
+                .saturating_mul(2u32.saturating_pow(failures))
+                .min(MAX_RETRY_DELAY)
+        } else {
+            QUERY_COOLDOWN
+        };
+        waker.sleep(request_delay(cooldown, last_time));
         last_time = Instant::now();
     }
 }
 
+/// The paths of the components entities are named and parented by, each `None` if the app
+/// doesn't register it, such as apps without the hierarchy plugin. Querying an unregistered
+/// component fails the whole query, so they are left out instead.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MetaComponents {
+    pub name: Option<String>,
+    pub parent: Option<String>,
+    /// The extra components asked for with `--extra` that the app registers.
+    pub extra: Vec<String>,
+    /// The extra components asked for that the app doesn't register.
+    pub unregistered: Vec<String>,
+}
+
+impl MetaComponents {
+    /// Pick out of the `registered` component paths, trying the `configured` name before the
+    /// [`NAME_COMPONENTS`] and splitting the `extra` components by whether they are registered.
+    fn from_registered(registered: &[String], configured: Option<&str>, extra: &[String]) -> Self {
+        let is_registered = |path: &&str| registered.iter().any(|r| r == path);
+        let (extra, unregistered) = extra
+            .iter()
+            .cloned()
+            .partition(|path| is_registered(&path.as_str()));
+        Self {
+            extra,
+            unregistered,
+            name: configured
+                .into_iter()
+                .chain(NAME_COMPONENTS)
+                .find(is_registered)
+                .map(String::from),
+            parent: Some(PARENT_COMPONENT)
+                .filter(is_registered)
+                .map(String::from),
+        }
+    }
+}
+
+/// The [`MetaComponents`] registered by the app, trying the `configured` name before the
+/// [`NAME_COMPONENTS`].
+pub fn find_meta_components(
+    socket: &Socket,
+    configured: Option<&str>,
+    extra: &[String],
+) -> anyhow::Result<MetaComponents> {
+    // Listing without an entity gives every registered component.
+    let registered = request::<Option<BrpListParams>, BrpListResponse>(
+        socket,
+        bevy_remote::builtin_methods::BRP_LIST_METHOD,
+        None,
+    )?;
+    Ok(MetaComponents::from_registered(
+        &registered,
+        configured,
+        extra,
+    ))
+}
+
+/// The text of a serialized `Name`, either an object with a `name` field or a plain string
+/// depending on the Bevy version. `None` for anything else rather than failing the query.
+fn parse_name(value: &Value) -> Option<String> {
+    value
+        .as_str()
+        .or_else(|| value.get("name")?.as_str())
+        .map(String::from)
+}
+
+/// The thread is quit and replaced whenever the selection changes, so it waits a moment before
+/// the first query to skip entities that are only scrolled past.
+///
+/// Components that can't be fetched are listed as failed, unless `strict` in which case the
+/// first failure is shown as an error and the thread stops.
+///
+/// Components in `frozen_components` are fetched once and then sent with the value they had, so
+/// they don't change while the rest do.
+///
+/// [`Message::ComponentsLoading`] is sent before the first fetch, and again with `None` if the
+/// thread stops before any components are fetched.
 pub fn handle_components_querying(
     tx: mpsc::Sender<Message>,
-    socket: &SocketAddr,
+    socket: &Socket,
     entity: Entity,
+    fixed_components: Vec<String>,
+    frozen_components: Arc<Mutex<HashSet<String>>>,
+    strict: bool,
     quit: ThreadQuitToken,
 ) {
-    let Ok(components) = list_request(socket, BrpListParams { entity }) else {
-        tx.send(Message::CommunicationFailed).unwrap();
+    std::thread::sleep(SELECTION_DEBOUNCE);
+    if quit.should_quit() {
         return;
+    }
+    // Listing and fetching every component of an entity with many of them can take a moment.
+    if tx.send(Message::ComponentsLoading(Some(entity))).is_err() {
+        return;
+    }
+
+    // A fixed set of components is fetched as is, without listing the entity's components.
+    let components = if fixed_components.is_empty() {
+        let Ok(components) = list_request(socket, BrpListParams { entity }) else {
+            let _ = tx.send(Message::CommunicationFailed);
+            return;
+        };
+        components
+    } else {
+        fixed_components
     };
 
     let params = BrpGetParams {
         entity,
         components,
-        strict: false,
+        strict,
     };
 
     let mut last_time = Instant::now();
+    let mut warned_absent = false;
+    let mut last_values: HashMap<String, Value> = HashMap::new();
     loop {
         if quit.should_quit() {
             return;
         }
 
-        if let Ok(BrpGetResponse::Lenient {
-            components,
-            errors: _,
-        }) = get_request(socket, params.clone())
-        {
+        let frozen: HashMap<String, Value> = {
+            let frozen = frozen_components
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            last_values
+                .iter()
+                .filter(|(type_path, _)| frozen.contains(*type_path))
+                .map(|(type_path, value)| (type_path.clone(), value.clone()))
+                .collect()
+        };
+        let mut params = params.clone();
+        params
+            .components
+            .retain(|type_path| !frozen.contains_key(type_path));
+
+        let response = get_request(socket, params);
+        if let Err(err) = &response {
+            // Any error other than the entity being gone is a component that couldn't be fetched.
+            let failed = err
+                .downcast_ref::<RemoteError>()
+                .is_some_and(|RemoteError(err)| err.code != error_codes::ENTITY_NOT_FOUND);
+            if strict && failed && !quit.should_quit() {
+                let title = "Failed to fetch components";
+                let _ = tx.send(Message::Log(LogEntry::error(format!("{title}: {err:#}"))));
+                let _ = tx.send(Message::ShowError(ErrorPopup::new(title, err)));
+                let _ = tx.send(Message::ComponentsLoading(None));
+                return;
+            }
+        }
+        if let Ok(BrpGetResponse::Strict(mut components)) = response {
+            components.extend(frozen);
+            last_values = components.clone().into_iter().collect();
             let mut components: Vec<_> = components.into_iter().collect();
             components.sort_by(|a, b| a.0.cmp(&b.0));
-            tx.send(Message::UpdateComponents(components)).unwrap();
+            if tx
+                .send(Message::UpdateComponents(
+                    components,
+                    ComponentErrors::new(),
+                ))
+                .is_err()
+            {
+                return;
+            }
+        } else if let Ok(BrpGetResponse::Lenient {
+            mut components,
+            errors,
+        }) = response
+        {
+            components.extend(frozen);
+            last_values = components.clone().into_iter().collect();
+            // Components the entity doesn't have, only asked for when they are fixed, aren't
+            // listed. Which they are is logged once rather than every time they are fetched.
+            let (absent, errors): (HashMap<_, _>, HashMap<_, _>) =
+                errors.into_iter().partition(|(_, err)| {
+                    err.get("code").and_then(Value::as_i64)
+                        == Some(error_codes::COMPONENT_NOT_PRESENT.into())
+                });
+            if !absent.is_empty() && !warned_absent {
+                warned_absent = true;
+                let mut names: Vec<_> = absent
+                    .keys()
+                    .map(|type_path| ShortName(type_path).to_string())
+                    .collect();
+                names.sort();
+                let _ = tx.send(Message::Log(LogEntry::info(format!(
+                    "{entity} doesn't have {}",
+                    names.join(", ")
+                ))));
+            }
+            let errors: ComponentErrors = errors
+                .into_iter()
+                .map(|(type_path, err)| {
+                    let message = err
+                        .get("message")
+                        .and_then(Value::as_str)
+                        .map_or_else(|| err.to_string(), String::from);
+                    (type_path, message)
+                })
+                .collect();
+            // Components that failed are still listed so they can be told apart from missing
+            // ones.
+            let mut components: Vec<_> = components
+                .into_iter()
+                .chain(
+                    errors
+                        .keys()
+                        .map(|type_path| (type_path.clone(), Value::Null)),
+                )
+                .collect();
+            components.sort_by(|a, b| a.0.cmp(&b.0));
+            if tx
+                .send(Message::UpdateComponents(components, errors))
+                .is_err()
+            {
+                return;
+            }
         } else {
             // We don't send a CommunicationFailed message here as it will trigger when the entity
             // is deleted.
+            if !quit.should_quit() {
+                let _ = tx.send(Message::ComponentsLoading(None));
+            }
             return;
         }
 
         // Sleep for the remaining time until the next query.
-        std::thread::sleep(QUERY_COOLDOWN.saturating_sub(last_time.elapsed()));
+        std::thread::sleep(request_delay(QUERY_COOLDOWN, last_time));
         last_time = Instant::now();
     }
 }
 
 /// Post a `bevy/get` request.
-pub fn get_request(socket: &SocketAddr, params: BrpGetParams) -> anyhow::Result<BrpGetResponse> {
+pub fn get_request(socket: &Socket, params: BrpGetParams) -> anyhow::Result<BrpGetResponse> {
     request::<BrpGetParams, BrpGetResponse>(
         socket,
         bevy_remote::builtin_methods::BRP_GET_METHOD,
@@ -135,10 +636,7 @@ pub fn get_request(socket: &SocketAddr, params: BrpGetParams) -> anyhow::Result<
 }
 
 /// Post a `bevy/query` request.
-pub fn query_request(
-    socket: &SocketAddr,
-    params: BrpQueryParams,
-) -> anyhow::Result<BrpQueryResponse> {
+pub fn query_request(socket: &Socket, params: BrpQueryParams) -> anyhow::Result<BrpQueryResponse> {
     request::<BrpQueryParams, BrpQueryResponse>(
         socket,
         bevy_remote::builtin_methods::BRP_QUERY_METHOD,
@@ -147,7 +645,7 @@ pub fn query_request(
 }
 
 /// Post a `bevy/destroy` request.
-pub fn destroy_request(socket: &SocketAddr, params: BrpDestroyParams) -> anyhow::Result<()> {
+pub fn destroy_request(socket: &Socket, params: BrpDestroyParams) -> anyhow::Result<()> {
     request::<BrpDestroyParams, ()>(
         socket,
         bevy_remote::builtin_methods::BRP_DESTROY_METHOD,
@@ -155,8 +653,53 @@ pub fn destroy_request(socket: &SocketAddr, params: BrpDestroyParams) -> anyhow:
     )
 }
 
+/// Post a `bevy/insert` request.
+pub fn insert_request(socket: &Socket, params: BrpInsertParams) -> anyhow::Result<()> {
+    request::<BrpInsertParams, ()>(
+        socket,
+        bevy_remote::builtin_methods::BRP_INSERT_METHOD,
+        params,
+    )
+}
+
+/// Post a `bevy/spawn` request.
+pub fn spawn_request(socket: &Socket, params: BrpSpawnParams) -> anyhow::Result<BrpSpawnResponse> {
+    request::<BrpSpawnParams, BrpSpawnResponse>(
+        socket,
+        bevy_remote::builtin_methods::BRP_SPAWN_METHOD,
+        params,
+    )
+}
+
+/// Post a `bevy/mutate_component` request.
+pub fn mutate_component_request(
+    socket: &Socket,
+    params: BrpMutateComponentParams,
+) -> anyhow::Result<()> {
+    request::<BrpMutateComponentParams, Value>(socket, BRP_MUTATE_COMPONENT_METHOD, params)?;
+    Ok(())
+}
+
+/// A copy of the parameters of [`BRP_MUTATE_COMPONENT_METHOD`] from later versions of
+/// `bevy_remote`.
+#[derive(Debug, Clone, Serialize)]
+pub struct BrpMutateComponentParams {
+    pub entity: Entity,
+    pub component: String,
+    /// The path to the field in Bevy's reflection path syntax, such as `.translation.x`.
+    pub path: String,
+    pub value: Value,
+}
+
+/// If the error is the app not having the requested method, such as apps using older versions
+/// of Bevy.
+pub fn is_method_not_found(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<RemoteError>()
+        .is_some_and(|RemoteError(err)| err.code == error_codes::METHOD_NOT_FOUND)
+}
+
 /// Post a `bevy/remove` request.
-pub fn remove_request(socket: &SocketAddr, params: BrpRemoveParams) -> anyhow::Result<()> {
+pub fn remove_request(socket: &Socket, params: BrpRemoveParams) -> anyhow::Result<()> {
     request::<BrpRemoveParams, ()>(
         socket,
         bevy_remote::builtin_methods::BRP_REMOVE_METHOD,
@@ -164,8 +707,17 @@ pub fn remove_request(socket: &SocketAddr, params: BrpRemoveParams) -> anyhow::R
     )
 }
 
+/// Post a `bevy/reparent` request.
+pub fn reparent_request(socket: &Socket, params: BrpReparentParams) -> anyhow::Result<()> {
+    request::<BrpReparentParams, ()>(
+        socket,
+        bevy_remote::builtin_methods::BRP_REPARENT_METHOD,
+        params,
+    )
+}
+
 /// Post a `bevy/list` request.
-pub fn list_request(socket: &SocketAddr, params: BrpListParams) -> anyhow::Result<BrpListResponse> {
+pub fn list_request(socket: &Socket, params: BrpListParams) -> anyhow::Result<BrpListResponse> {
     request::<BrpListParams, BrpListResponse>(
         socket,
         bevy_remote::builtin_methods::BRP_LIST_METHOD,
@@ -173,30 +725,88 @@ pub fn list_request(socket: &SocketAddr, params: BrpListParams) -> anyhow::Resul
     )
 }
 
-fn request<Params: Serialize, Response: DeserializeOwned>(
-    socket: &SocketAddr,
+/// Post a `bevy/registry/schema` request, returning the schemas keyed by type path.
+pub fn registry_schema_request(socket: &Socket) -> anyhow::Result<HashMap<String, Value>> {
+    request::<Value, HashMap<String, Value>>(
+        socket,
+        BRP_REGISTRY_SCHEMA_METHOD,
+        Value::Object(Default::default()),
+    )
+}
+
+/// Post a request for any method, such as one added by the app itself, returning its result as
+/// is.
+pub fn custom_request(
+    socket: &Socket,
     method: &str,
-    params: Params,
-) -> anyhow::Result<Response> {
+    params: Option<Value>,
+) -> anyhow::Result<Value> {
+    request::<Option<Value>, Value>(socket, method, params)
+}
+
+/// A shell command that posts the same `bevy/get` request the components panel makes, for running
+/// outside of brptui.
+pub fn get_command(socket: &Socket, params: BrpGetParams) -> anyhow::Result<String> {
+    let body = request_body(bevy_remote::builtin_methods::BRP_GET_METHOD, params)?;
+    Ok(socket.curl_command(&String::from_utf8(body)?))
+}
+
+/// The JSON-RPC request for the method with the given params.
+fn request_body<Params: Serialize>(method: &str, params: Params) -> anyhow::Result<Vec<u8>> {
     let request = BrpRequest {
         jsonrpc: String::from("2.0"),
         method: String::from(method),
         id: None,
         params: Some(serde_json::to_value(params)?),
     };
+    Ok(serde_json::to_vec(&request)?)
+}
 
-    let response = ureq::post(&format!("http://{socket}"))
-        .send_json(request)?
-        .into_json::<BrpResponse>()?;
+fn request<Params: Serialize, Response: DeserializeOwned>(
+    socket: &Socket,
+    method: &str,
+    params: Params,
+) -> anyhow::Result<Response> {
+    let body = request_body(method, params)?;
+    let start = Instant::now();
+    if debug_log::is_enabled() {
+        debug_log::log(format_args!("sent {}", String::from_utf8_lossy(&body)));
+    }
+    let result = socket.post(&body);
+    stats::record(method, body.len(), result.as_ref().map_or(0, Vec::len));
+    if debug_log::is_enabled() {
+        let elapsed = start.elapsed().as_millis();
+        match &result {
+            Ok(received) => debug_log::log(format_args!(
+                "received {method} in {elapsed}ms {}",
+                String::from_utf8_lossy(received)
+            )),
+            Err(err) => debug_log::log(format_args!("failed {method} after {elapsed}ms: {err:#}")),
+        }
+    }
+    let received = result?;
+    let response: BrpResponse = serde_json::from_slice(&received)?;
 
     let body = match response.payload {
         BrpPayload::Result(value) => serde_json::from_value(value)?,
-        BrpPayload::Error(err) => return Err(anyhow!("BrpPayload was an error: {err:?}")),
+        BrpPayload::Error(err) => return Err(RemoteError(err).into()),
     };
 
     Ok(body)
 }
 
+/// An error payload returned by the remote app, kept intact so it can be shown in full.
+#[derive(Debug, Clone)]
+pub struct RemoteError(pub BrpError);
+
+impl fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (code {})", self.0.message, self.0.code)
+    }
+}
+
+impl std::error::Error for RemoteError {}
+
 /// A copy of [`bevy_remote::BrpResponse`] since it can't be deserialized due to `&'static str`.
 #[derive(Debug, Deserialize, Clone)]
 pub struct BrpResponse {
@@ -204,3 +814,74 @@ pub struct BrpResponse {
     #[serde(flatten)]
     pub payload: BrpPayload,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_names_of_either_shape() {
+        assert_eq!(parse_name(&json!("Player")), Some("Player".to_string()));
+        assert_eq!(
+            parse_name(&json!({ "hash": 1, "name": "Player" })),
+            Some("Player".to_string())
+        );
+        assert_eq!(parse_name(&json!({ "hash": 1 })), None);
+        assert_eq!(parse_name(&json!(3)), None);
+    }
+
+    #[test]
+    fn malformed_names_are_left_out() {
+        for malformed in [
+            json!(null),
+            json!([]),
+            json!(["Player"]),
+            json!({}),
+            json!({ "name": null }),
+            json!({ "name": 7 }),
+            json!({ "name": { "name": "Player" } }),
+        ] {
+            assert_eq!(parse_name(&malformed), None, "{malformed}");
+        }
+    }
+
+    #[test]
+    fn finds_name_where_the_app_registers_it() {
+        for path in NAME_COMPONENTS {
+            let found = MetaComponents::from_registered(&[path.to_string()], None, &[]);
+            assert_eq!(found.name.as_deref(), Some(path));
+        }
+    }
+
+    #[test]
+    fn prefers_the_configured_name() {
+        let registered = ["my_game::Label".to_string(), NAME_COMPONENTS[0].to_string()];
+        let found = MetaComponents::from_registered(&registered, Some("my_game::Label"), &[]);
+        assert_eq!(found.name.as_deref(), Some("my_game::Label"));
+    }
+
+    #[test]
+    fn leaves_out_unregistered_components() {
+        let found = MetaComponents::from_registered(&["my_game::Player".to_string()], None, &[]);
+        assert_eq!(found, MetaComponents::default());
+
+        let registered = [PARENT_COMPONENT.to_string()];
+        let found = MetaComponents::from_registered(&registered, Some("my_game::Label"), &[]);
+        assert_eq!(found.name, None);
+        assert_eq!(found.parent.as_deref(), Some(PARENT_COMPONENT));
+    }
+
+    #[test]
+    fn leaves_out_unregistered_extra_components() {
+        let registered = ["my_game::Health".to_string(), "my_game::Team".to_string()];
+        let extra = [
+            "my_game::Team".to_string(),
+            "my_game::Helth".to_string(),
+            "my_game::Health".to_string(),
+        ];
+        let found = MetaComponents::from_registered(&registered, None, &extra);
+        assert_eq!(found.extra, ["my_game::Team", "my_game::Health"]);
+        assert_eq!(found.unregistered, ["my_game::Helth"]);
+    }
+}
@@ -0,0 +1,114 @@
+//! Command line arguments.
+
+use crate::{brp, keymap::KeymapPreset, transport::Socket};
+use clap::{error::ErrorKind, CommandFactory, Parser};
+use std::{env, fmt, path::PathBuf};
+
+/// The environment variable the socket is read from when `--socket` isn't given.
+pub const SOCKET_ENV: &str = "BRPTUI_SOCKET";
+
+/// A Bevy Remote Protocol client for the terminal.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Args {
+    /// The address of the app to connect to, or `unix:` followed by the path of a Unix domain
+    /// socket. Defaults to $BRPTUI_SOCKET if set, then the last address used, or 127.0.0.1:15702
+    /// the first time.
+    #[arg(long, value_name = "ADDRESS")]
+    pub socket: Option<Socket>,
+
+    /// Extra components to fetch for every entity and show in the entity details, given as full
+    /// type paths. Ones the app doesn't register are left out and logged.
+    #[arg(long = "extra", value_name = "TYPE_PATH", value_delimiter = ',')]
+    pub extra_components: Vec<String>,
+
+    /// Fetch and show only these components of each entity, given as full type paths, instead of
+    /// listing every component the entity has.
+    #[arg(long, value_name = "TYPE_PATH", value_delimiter = ',')]
+    pub components: Vec<String>,
+
+    /// Draw with just ASCII, for terminals and fonts without box drawing characters and arrows.
+    #[arg(long)]
+    pub ascii: bool,
+
+    /// Write the requests made to the app and everything brptui does to this file, for debugging
+    /// brptui itself. Large requests and responses are cut short.
+    #[arg(long, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// List just the ids of the entities first and fill in their names and parents right after,
+    /// so the entities of very large worlds show up sooner.
+    #[arg(long)]
+    pub refresh_once_then_watch: bool,
+
+    /// Disable every action that changes the connected app, such as despawning or editing.
+    #[arg(long)]
+    pub read_only: bool,
+
+    /// Print every entity as JSON and exit instead of opening the TUI. Exits with 2 if the app
+    /// can't be reached and 3 if it responds with an error.
+    #[arg(long)]
+    pub once: bool,
+
+    /// Print the entities that have all the given components as JSON, including the values of
+    /// those components, and exit. Implies --once.
+    #[arg(long, value_name = "TYPE_PATH", value_delimiter = ',')]
+    pub query: Vec<String>,
+
+    /// The key bindings to use.
+    #[arg(long, value_enum, default_value_t)]
+    pub keymap: KeymapPreset,
+}
+
+impl Args {
+    /// The socket to connect to and where it was given, preferring `--socket`, then
+    /// [`SOCKET_ENV`], then the socket of the last session and then the default. Exits with an
+    /// error like clap's own if the environment variable isn't a valid socket.
+    pub fn socket(&self, session: Option<Socket>) -> (Socket, SocketSource) {
+        if let Some(socket) = &self.socket {
+            return (socket.clone(), SocketSource::Flag);
+        }
+        match env::var(SOCKET_ENV) {
+            Ok(value) if !value.is_empty() => match value.parse() {
+                Ok(socket) => return (socket, SocketSource::Env),
+                Err(err) => Self::command()
+                    .error(
+                        ErrorKind::InvalidValue,
+                        format!("invalid value '{value}' for ${SOCKET_ENV}: {err}"),
+                    )
+                    .exit(),
+            },
+            Err(env::VarError::NotUnicode(_)) => Self::command()
+                .error(
+                    ErrorKind::InvalidUtf8,
+                    format!("${SOCKET_ENV} isn't valid unicode"),
+                )
+                .exit(),
+            _ => {}
+        }
+        match session {
+            Some(socket) => (socket, SocketSource::Session),
+            None => (brp::DEFAULT_SOCKET, SocketSource::Default),
+        }
+    }
+}
+
+/// Where the socket being connected to was given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocketSource {
+    Flag,
+    Env,
+    Session,
+    Default,
+}
+
+impl fmt::Display for SocketSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SocketSource::Flag => write!(f, "--socket"),
+            SocketSource::Env => write!(f, "${SOCKET_ENV}"),
+            SocketSource::Session => write!(f, "last session"),
+            SocketSource::Default => write!(f, "default"),
+        }
+    }
+}
@@ -0,0 +1,29 @@
+//! Access to the system clipboard.
+
+/// The system clipboard, opened when first used.
+///
+/// On some platforms copied text is served by the process that copied it, so the clipboard is
+/// kept open rather than opened for each copy.
+#[derive(Default)]
+pub struct Clipboard {
+    inner: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    pub fn copy(&mut self, text: String) -> anyhow::Result<()> {
+        self.open()?.set_text(text)?;
+        Ok(())
+    }
+
+    pub fn paste(&mut self) -> anyhow::Result<String> {
+        Ok(self.open()?.get_text()?)
+    }
+
+    fn open(&mut self) -> anyhow::Result<&mut arboard::Clipboard> {
+        let clipboard = match self.inner.take() {
+            Some(clipboard) => clipboard,
+            None => arboard::Clipboard::new()?,
+        };
+        Ok(self.inner.insert(clipboard))
+    }
+}
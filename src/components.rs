@@ -0,0 +1,336 @@
+//! Logic for laying out the rows of the components panel.
+
+use crate::theme::Theme;
+use disqualified::ShortName;
+use ratatui::{
+    style::Stylize,
+    text::{Line, Span},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
+
+/// Why components of the selected entity couldn't be fetched, by type path. They are still listed,
+/// with `null` in place of their value.
+pub type ComponentErrors = HashMap<String, String>;
+
+/// A single row in the components panel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComponentRow {
+    /// A non-selectable header naming the module a group of components belongs to.
+    Group(String),
+    /// The header of a collapsed group in place of its `len` components, selectable so it can be
+    /// expanded again.
+    Collapsed { group: String, len: usize },
+    /// An index into the components of the selected entity.
+    Component(usize),
+}
+
+impl ComponentRow {
+    /// The index of the component this row represents, if any.
+    pub fn component(&self) -> Option<usize> {
+        match self {
+            ComponentRow::Group(_) | ComponentRow::Collapsed { .. } => None,
+            ComponentRow::Component(index) => Some(*index),
+        }
+    }
+}
+
+/// Options controlling which components are shown and how.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ComponentListOptions {
+    /// Group components by the first segment of their type path.
+    pub grouped: bool,
+    /// Hide components without any inspectable data, see [`is_marker`].
+    pub hide_markers: bool,
+    /// Order components by when they last changed, most recent first, instead of by type path.
+    pub recent_first: bool,
+    /// List components by their full type paths instead of their short names.
+    pub full_paths: bool,
+}
+
+/// When each component of the selected entity last changed value.
+#[derive(Debug, Default)]
+pub struct ComponentChanges {
+    last_changed: HashMap<String, Instant>,
+    /// If the next components are of a newly selected entity, so their differences from the
+    /// previous ones aren't changes.
+    reset: bool,
+}
+
+impl ComponentChanges {
+    /// Forget the changes, such as when another entity is selected.
+    pub fn reset(&mut self) {
+        self.last_changed.clear();
+        self.reset = true;
+    }
+
+    /// Record the components with a different value than before, including those just added.
+    pub fn update(&mut self, old: &[(String, Value)], new: &[(String, Value)]) {
+        if std::mem::take(&mut self.reset) {
+            return;
+        }
+        let now = Instant::now();
+        for (type_path, value) in new {
+            let old_value = old.iter().find(|(old_path, _)| old_path == type_path);
+            if old_value.is_none_or(|(_, old_value)| old_value != value) {
+                self.last_changed.insert(type_path.clone(), now);
+            }
+        }
+    }
+}
+
+/// Order the components by type path, or by when they last changed if
+/// [`ComponentListOptions::recent_first`], keeping each group together when grouping.
+pub fn sort(
+    components: &mut [(String, Value)],
+    changes: &ComponentChanges,
+    options: ComponentListOptions,
+) {
+    components.sort_by(|(a, _), (b, _)| {
+        let group = match options.grouped {
+            true => module_prefix(a).cmp(module_prefix(b)),
+            false => Ordering::Equal,
+        };
+        let recency = match options.recent_first {
+            true => changes
+                .last_changed
+                .get(b)
+                .cmp(&changes.last_changed.get(a)),
+            false => Ordering::Equal,
+        };
+        group.then(recency).then_with(|| a.cmp(b))
+    });
+}
+
+/// Build the rows to display for the given components.
+///
+/// When grouping, a header row is placed above each group, and the groups in `collapsed` are
+/// just their header. The components are expected to be sorted by their type path so each group
+/// is contiguous.
+pub fn component_rows(
+    components: &[(String, Value)],
+    options: ComponentListOptions,
+    collapsed: &HashSet<String>,
+) -> Vec<ComponentRow> {
+    let mut rows = Vec::with_capacity(components.len());
+    let mut current_group = None;
+    for (n, (name, value)) in components.iter().enumerate() {
+        if options.hide_markers && is_marker(value) {
+            continue;
+        }
+        if options.grouped {
+            let group = module_prefix(name);
+            if current_group != Some(group) {
+                rows.push(match collapsed.contains(group) {
+                    true => ComponentRow::Collapsed {
+                        group: group.to_string(),
+                        len: 0,
+                    },
+                    false => ComponentRow::Group(group.to_string()),
+                });
+                current_group = Some(group);
+            }
+            if let Some(ComponentRow::Collapsed { len, .. }) = rows.last_mut() {
+                *len += 1;
+                continue;
+            }
+        }
+        rows.push(ComponentRow::Component(n));
+    }
+    rows
+}
+
+/// Find the row displaying the component at the given index.
+pub fn row_of_component(rows: &[ComponentRow], component: usize) -> Option<usize> {
+    rows.iter()
+        .position(|row| row.component() == Some(component))
+}
+
+/// The number of components hidden by the given options.
+pub fn hidden_count(components: &[(String, Value)], options: ComponentListOptions) -> usize {
+    if options.hide_markers {
+        components.iter().filter(|(_, v)| is_marker(v)).count()
+    } else {
+        0
+    }
+}
+
+/// If the value of a component has nothing to inspect, such as marker components which serialize
+/// to `{}` or `null`.
+pub fn is_marker(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Object(map) => map.is_empty(),
+        _ => false,
+    }
+}
+
+/// Render the given rows as [`Line`]s for a [`PaginatedList`](crate::paginated_list::PaginatedList).
+pub fn row_lines<'a>(
+    rows: &'a [ComponentRow],
+    components: &[(String, Value)],
+    errors: &ComponentErrors,
+    frozen: &HashSet<String>,
+    options: ComponentListOptions,
+    theme: &Theme,
+) -> impl Iterator<Item = Line<'a>> + 'a {
+    let dim = theme.dim();
+    let error = theme.error;
+    let grouped = options.grouped;
+    let names: Vec<_> = display_names(components, options.full_paths)
+        .into_iter()
+        .zip(components)
+        .map(|(display_name, (name, _))| {
+            (
+                display_name,
+                errors.contains_key(name),
+                frozen.contains(name),
+            )
+        })
+        .collect();
+    rows.iter().map(move |row| match row {
+        ComponentRow::Group(group) => Line::from(Span::styled(group.as_str(), dim).italic()),
+        ComponentRow::Collapsed { group, len } => Line::from(vec![
+            Span::raw(group.as_str()).italic(),
+            Span::styled(format!(" {len} collapsed"), dim),
+        ]),
+        ComponentRow::Component(n) => {
+            let (name, failed, frozen) = &names[*n];
+            let mut line = Line::from(Span::raw(name.clone()).bold());
+            if grouped {
+                line.spans.insert(0, Span::raw("  "));
+            }
+            if *failed {
+                line.push_span(Span::raw(" failed").fg(error));
+            } else if *frozen {
+                line.push_span(Span::styled(" frozen", dim));
+            }
+            line
+        }
+    })
+}
+
+/// The names components are listed by, their short names with just enough of their module paths
+/// to tell apart those sharing one, or their full type paths with `full_paths`.
+pub fn display_names(components: &[(String, Value)], full_paths: bool) -> Vec<String> {
+    if full_paths {
+        return components
+            .iter()
+            .map(|(type_path, _)| type_path.clone())
+            .collect();
+    }
+    let mut names: Vec<_> = components
+        .iter()
+        .map(|(type_path, _)| ShortName(type_path).to_string())
+        .collect();
+    // Each pass adds another module to the names that are still shared.
+    for depth in 2.. {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for name in &names {
+            *counts.entry(name.as_str()).or_default() += 1;
+        }
+        let shared: Vec<_> = (0..names.len())
+            .filter(|n| counts[names[*n].as_str()] > 1)
+            .collect();
+        let mut lengthened = false;
+        for n in shared {
+            let name = qualified_name(&components[n].0, depth);
+            lengthened |= name != names[n];
+            names[n] = name;
+        }
+        if !lengthened {
+            break;
+        }
+    }
+    names
+}
+
+/// The short name of `type_path` with the last `depth` segments of its path rather than just the
+/// last, keeping its generic arguments short.
+fn qualified_name(type_path: &str, depth: usize) -> String {
+    let base = type_path.split('<').next().unwrap_or(type_path);
+    let segments: Vec<_> = base.split("::").collect();
+    let short = ShortName(type_path).to_string();
+    let last = segments.last().copied().unwrap_or_default();
+    let generics = short.strip_prefix(last).unwrap_or_default();
+    let start = segments.len().saturating_sub(depth);
+    format!("{}{generics}", segments[start..].join("::"))
+}
+
+/// The part of a type path before the first `::`.
+pub fn module_prefix(type_path: &str) -> &str {
+    type_path
+        .split_once("::")
+        .map(|(prefix, _)| prefix)
+        .unwrap_or(type_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn components() -> Vec<(String, Value)> {
+        [
+            "bevy_render::view::Visibility",
+            "bevy_transform::components::GlobalTransform",
+            "bevy_transform::components::Transform",
+        ]
+        .into_iter()
+        .map(|type_path| (type_path.to_string(), json!({ "x": 1 })))
+        .collect()
+    }
+
+    #[test]
+    fn groups_have_headers() {
+        let options = ComponentListOptions {
+            grouped: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            component_rows(&components(), options, &HashSet::new()),
+            [
+                ComponentRow::Group("bevy_render".to_string()),
+                ComponentRow::Component(0),
+                ComponentRow::Group("bevy_transform".to_string()),
+                ComponentRow::Component(1),
+                ComponentRow::Component(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn collapsed_groups_are_just_their_header() {
+        let options = ComponentListOptions {
+            grouped: true,
+            ..Default::default()
+        };
+        let collapsed = HashSet::from(["bevy_transform".to_string()]);
+        let rows = component_rows(&components(), options, &collapsed);
+        assert_eq!(
+            rows,
+            [
+                ComponentRow::Group("bevy_render".to_string()),
+                ComponentRow::Component(0),
+                ComponentRow::Collapsed {
+                    group: "bevy_transform".to_string(),
+                    len: 2,
+                },
+            ]
+        );
+        assert_eq!(rows[2].component(), None);
+    }
+
+    #[test]
+    fn collapsing_only_applies_while_grouped() {
+        let collapsed = HashSet::from(["bevy_transform".to_string()]);
+        let rows = component_rows(&components(), ComponentListOptions::default(), &collapsed);
+        assert_eq!(rows.len(), 3);
+    }
+}
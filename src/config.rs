@@ -0,0 +1,91 @@
+//! Files brptui keeps in the user's config directory.
+
+use crate::{
+    brp::{self, EntityLabel},
+    inspector::BoolStyle,
+    theme::Theme,
+};
+use serde::Deserialize;
+use std::{env, fs, io, path::PathBuf};
+
+/// Options read from `config.json` in the [`config_dir`], command line arguments take precedence.
+#[derive(Debug, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    /// Disable every action that changes the connected app.
+    pub read_only: bool,
+    /// The relative widths of the entities, components and inspector panels.
+    pub panel_ratios: [u16; 3],
+    /// How entities are labelled in the entities list.
+    pub entity_label: EntityLabel,
+    /// The colors to draw with.
+    pub theme: Theme,
+    /// The path of the component entities are named by, for apps where `Name` isn't at any of the
+    /// [`NAME_COMPONENTS`](crate::brp::NAME_COMPONENTS).
+    pub name_component: Option<String>,
+    /// Whether moving past either end of a list moves to the other end.
+    pub wrap_selection: bool,
+    /// Whether moving past either end of the inspector moves to the other end, off by default as
+    /// the inspector has always stopped at its ends.
+    pub inspector_wrap: bool,
+    /// Whether the selection flashes for a frame when it moves, to draw the eye to it.
+    pub flash_selection: bool,
+    /// How booleans are shown in the inspector.
+    pub bool_style: BoolStyle,
+    /// List the fields of objects in the inspector alphabetically rather than in the order the app
+    /// sent them.
+    pub sort_fields: bool,
+    /// Draw with just ASCII, for terminals and fonts without box drawing characters and arrows.
+    pub ascii: bool,
+    /// The shortest time in milliseconds to wait between requests made on a timer, even when the
+    /// app is slow to respond.
+    pub min_request_delay: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            read_only: false,
+            panel_ratios: [1, 1, 2],
+            entity_label: EntityLabel::default(),
+            theme: Theme::default(),
+            name_component: None,
+            wrap_selection: true,
+            inspector_wrap: false,
+            flash_selection: true,
+            bool_style: BoolStyle::default(),
+            sort_fields: false,
+            ascii: false,
+            min_request_delay: brp::DEFAULT_MIN_REQUEST_DELAY.as_millis() as u64,
+        }
+    }
+}
+
+impl Config {
+    /// Load the config file, using the defaults if there isn't one.
+    pub fn load() -> anyhow::Result<Self> {
+        let Some(path) = config_dir().map(|dir| dir.join("config.json")) else {
+            return Ok(Self::default());
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err.into()),
+        };
+        serde_json::from_str(&text)
+            .map_err(|err| anyhow::anyhow!("invalid config file {}: {err}", path.display()))
+    }
+}
+
+/// The directory brptui stores its files in, `$XDG_CONFIG_HOME/brptui` or `~/.config/brptui` on
+/// Unix and `%APPDATA%\brptui` on Windows.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        PathBuf::from(env::var_os("APPDATA")?)
+    } else if let Some(config_home) = env::var_os("XDG_CONFIG_HOME").filter(|v| !v.is_empty()) {
+        PathBuf::from(config_home)
+    } else {
+        PathBuf::from(env::var_os("HOME")?).join(".config")
+    };
+    Some(base.join("brptui"))
+}
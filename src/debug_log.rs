@@ -0,0 +1,92 @@
+//! An optional log of the BRP requests brptui makes and the messages it handles, written to a file
+//! for debugging brptui itself.
+
+use std::{
+    fmt::{self, Write as _},
+    fs::File,
+    io::{self, BufWriter, Write as _},
+    path::Path,
+    sync::{mpsc, Mutex, PoisonError},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The longest an entry can be before the rest is cut off, so large payloads such as every
+/// entity in a world don't flood the file.
+const MAX_ENTRY_LEN: usize = 2000;
+
+/// Sends entries to the thread writing them, `None` while not logging.
+static SENDER: Mutex<Option<mpsc::Sender<String>>> = Mutex::new(None);
+
+/// Start logging to the file at `path`, replacing what it held. The file is written on another
+/// thread so logging never waits on it.
+pub fn start(path: &Path) -> io::Result<()> {
+    let file = File::create(path)?;
+    let (tx, rx) = mpsc::channel::<String>();
+    thread::spawn(move || {
+        let mut writer = BufWriter::new(file);
+        // Flushed whenever nothing else is waiting, so little is lost if brptui crashes.
+        while let Ok(entry) = rx.recv() {
+            for entry in std::iter::once(entry).chain(rx.try_iter()) {
+                let _ = writeln!(writer, "{entry}");
+            }
+            let _ = writer.flush();
+        }
+    });
+    *SENDER.lock().unwrap_or_else(PoisonError::into_inner) = Some(tx);
+    Ok(())
+}
+
+/// Whether entries are being logged, for skipping work only needed to log them.
+pub fn is_enabled() -> bool {
+    SENDER
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .is_some()
+}
+
+/// Log an entry with the time it happened, cut off after [`MAX_ENTRY_LEN`]. Does nothing unless
+/// logging was started.
+pub fn log(args: fmt::Arguments) {
+    let Some(tx) = SENDER
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+    else {
+        return;
+    };
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let mut entry = Entry::default();
+    let _ = write!(entry, "{}.{:03} ", time.as_secs(), time.subsec_millis());
+    // Formatting stops as soon as the entry is full rather than formatting all of it first.
+    if entry.write_fmt(args).is_err() && entry.truncated {
+        entry.text.push_str(" [truncated]");
+    }
+    let _ = tx.send(entry.text);
+}
+
+/// The text of an entry, refusing anything past [`MAX_ENTRY_LEN`].
+#[derive(Default)]
+struct Entry {
+    text: String,
+    truncated: bool,
+}
+
+impl fmt::Write for Entry {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let room = MAX_ENTRY_LEN.saturating_sub(self.text.len());
+        if s.len() <= room {
+            self.text.push_str(s);
+            return Ok(());
+        }
+        let mut end = room;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.text.push_str(&s[..end]);
+        self.truncated = true;
+        Err(fmt::Error)
+    }
+}
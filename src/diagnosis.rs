@@ -0,0 +1,48 @@
+//! Working out why the app can't be reached, to suggest what to check.
+
+use crate::{brp::RemoteError, transport::Socket};
+
+/// Why requests to the app failed.
+#[derive(Debug, Clone)]
+pub enum Diagnosis {
+    /// Nothing accepted a connection, the app isn't running or is on another socket.
+    NotListening,
+    /// Something accepted the connection but didn't answer like BRP, with the error it gave.
+    NotRemote(String),
+    /// The app answered but with an error.
+    RemoteError(String),
+}
+
+impl Diagnosis {
+    /// Diagnose the error from a failed request by trying to connect to the socket.
+    pub fn new(socket: &Socket, err: &anyhow::Error) -> Self {
+        if let Some(err) = err.downcast_ref::<RemoteError>() {
+            return Self::RemoteError(err.to_string());
+        }
+        match socket.is_listening() {
+            true => Self::NotRemote(format!("{err:#}")),
+            false => Self::NotListening,
+        }
+    }
+
+    /// What went wrong, for the given socket.
+    pub fn summary(&self, socket: &Socket) -> String {
+        match self {
+            Self::NotListening => format!("Nothing is listening on {socket}"),
+            Self::NotRemote(err) => format!("{socket} didn't answer like a Bevy app: {err}"),
+            Self::RemoteError(err) => format!("The app answered with an error: {err}"),
+        }
+    }
+
+    /// What to check to fix it.
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Self::NotListening => {
+                "Is the app running with bevy_remote's RemotePlugin and RemoteHttpPlugin added, \
+                 and is the port correct?"
+            }
+            Self::NotRemote(_) => "Is the port correct? It may belong to another program.",
+            Self::RemoteError(_) => "The app may use a version of Bevy brptui doesn't support.",
+        }
+    }
+}
@@ -0,0 +1,402 @@
+//! The differences between two snapshots of the components of entities, for seeing what an action
+//! in the app changed.
+
+use crate::{components, popup, theme::Theme};
+use bevy_ecs::entity::Entity;
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Style, Stylize},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Padding, Paragraph, StatefulWidget, Widget},
+};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The components of an entity at the time they were captured.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub entity: Entity,
+    pub components: Vec<(String, Value)>,
+}
+
+/// How a value differs between two snapshots.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added(Value),
+    Removed(Value),
+    Changed { old: Value, new: Value },
+}
+
+/// A value that differs, at the name its component is listed by followed by its inspector path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffLine {
+    pub path: String,
+    pub change: Change,
+}
+
+/// Every field that differs between `before` and `after`, going into objects and arrays so only
+/// the innermost values that differ are listed. Components in just one of them are listed whole.
+pub fn diff(before: &[(String, Value)], after: &[(String, Value)]) -> Vec<DiffLine> {
+    // Named among the components of both snapshots so ones sharing a short name stay apart.
+    let type_paths: Vec<_> = before
+        .iter()
+        .chain(
+            after
+                .iter()
+                .filter(|(type_path, _)| !before.iter().any(|(other, _)| other == type_path)),
+        )
+        .map(|(type_path, _)| (type_path.clone(), Value::Null))
+        .collect();
+    let names: HashMap<&str, String> = type_paths
+        .iter()
+        .map(|(type_path, _)| type_path.as_str())
+        .zip(components::display_names(&type_paths, false))
+        .collect();
+    let name = |type_path: &str| names[type_path].clone();
+    let mut lines = Vec::new();
+    for (type_path, old) in before {
+        let path = name(type_path);
+        match after.iter().find(|(other, _)| other == type_path) {
+            Some((_, new)) => diff_values(path, old, new, &mut lines),
+            None => lines.push(DiffLine {
+                path,
+                change: Change::Removed(old.clone()),
+            }),
+        }
+    }
+    for (type_path, new) in after {
+        if !before.iter().any(|(other, _)| other == type_path) {
+            lines.push(DiffLine {
+                path: name(type_path),
+                change: Change::Added(new.clone()),
+            });
+        }
+    }
+    lines
+}
+
+fn diff_values(path: String, old: &Value, new: &Value, lines: &mut Vec<DiffLine>) {
+    match (old, new) {
+        _ if old == new => {}
+        (Value::Object(old_fields), Value::Object(new_fields)) => {
+            for (key, old_value) in old_fields {
+                let path = format!("{path}.{key}");
+                match new_fields.get(key) {
+                    Some(new_value) => diff_values(path, old_value, new_value, lines),
+                    None => lines.push(DiffLine {
+                        path,
+                        change: Change::Removed(old_value.clone()),
+                    }),
+                }
+            }
+            for (key, new_value) in new_fields {
+                if !old_fields.contains_key(key) {
+                    lines.push(DiffLine {
+                        path: format!("{path}.{key}"),
+                        change: Change::Added(new_value.clone()),
+                    });
+                }
+            }
+        }
+        (Value::Array(old_items), Value::Array(new_items)) => {
+            for n in 0..old_items.len().max(new_items.len()) {
+                let path = format!("{path}[{n}]");
+                match (old_items.get(n), new_items.get(n)) {
+                    (Some(old_item), Some(new_item)) => {
+                        diff_values(path, old_item, new_item, lines)
+                    }
+                    (Some(old_item), None) => lines.push(DiffLine {
+                        path,
+                        change: Change::Removed(old_item.clone()),
+                    }),
+                    (None, Some(new_item)) => lines.push(DiffLine {
+                        path,
+                        change: Change::Added(new_item.clone()),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ => lines.push(DiffLine {
+            path,
+            change: Change::Changed {
+                old: old.clone(),
+                new: new.clone(),
+            },
+        }),
+    }
+}
+
+/// The differences between two snapshots, shown over the UI until dismissed.
+#[derive(Debug)]
+pub struct DiffPopup {
+    title: String,
+    lines: Vec<DiffLine>,
+    scroll: usize,
+    /// The number of lines shown when last rendered, for scrolling by half of them.
+    height: usize,
+}
+
+impl DiffPopup {
+    pub fn new(before: &Snapshot, after: &Snapshot, lines: Vec<DiffLine>) -> Self {
+        let title = match before.entity == after.entity {
+            true => format!(" diff of entity {} ", before.entity),
+            false => format!(" diff of entity {} to {} ", before.entity, after.entity),
+        };
+        Self {
+            title,
+            lines,
+            scroll: 0,
+            height: 0,
+        }
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.scroll += 1;
+    }
+
+    pub fn scroll_up_half_page(&mut self) {
+        self.scroll = self.scroll.saturating_sub((self.height / 2).max(1));
+    }
+
+    pub fn scroll_down_half_page(&mut self) {
+        self.scroll += (self.height / 2).max(1);
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.scroll = 0;
+    }
+
+    pub fn scroll_to_bottom(&mut self) {
+        self.scroll = usize::MAX;
+    }
+}
+
+/// Renders a [`DiffPopup`], with added values in the accent color, removed ones in the error color
+/// and changed ones in the changed color.
+pub struct DiffWidget {
+    theme: Theme,
+}
+
+impl DiffWidget {
+    pub fn new(theme: Theme) -> Self {
+        Self { theme }
+    }
+
+    fn line<'a>(&self, line: &'a DiffLine) -> Line<'a> {
+        let (symbol, color, value) = match &line.change {
+            Change::Added(value) => ("+ ", self.theme.accent, value.to_string()),
+            Change::Removed(value) => ("- ", self.theme.error, value.to_string()),
+            Change::Changed { old, new } => {
+                let arrow = self.theme.symbol("→", "->");
+                ("~ ", self.theme.changed, format!("{old} {arrow} {new}"))
+            }
+        };
+        Line::from(vec![
+            Span::raw(symbol).fg(color).bold(),
+            Span::raw(line.path.as_str()).fg(color),
+            Span::styled(": ", self.theme.dim()),
+            Span::raw(value),
+        ])
+    }
+}
+
+impl StatefulWidget for DiffWidget {
+    type State = DiffPopup;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let width = area.width.saturating_sub(4);
+        let height = (state.lines.len() as u16)
+            .saturating_add(2)
+            .min(area.height.saturating_sub(2));
+        let popup_area = popup::centered(area, width, height);
+
+        let block = Block::default()
+            .title(Line::from(state.title.as_str()).bold())
+            .title(Line::from(format!(" {} changes ", state.lines.len())).right_aligned())
+            .borders(Borders::ALL)
+            .border_set(self.theme.border_set(border::THICK))
+            .border_style(Style::default().fg(self.theme.accent))
+            .padding(Padding::horizontal(1));
+        let inner = block.inner(popup_area);
+        state.height = inner.height as usize;
+        state.scroll = state
+            .scroll
+            .min(state.lines.len().saturating_sub(state.height));
+
+        let lines: Vec<_> = state
+            .lines
+            .iter()
+            .skip(state.scroll)
+            .take(state.height)
+            .map(|line| self.line(line))
+            .collect();
+        Clear.render(popup_area, buf);
+        Paragraph::new(lines).block(block).render(popup_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn components(values: &[(&str, Value)]) -> Vec<(String, Value)> {
+        values
+            .iter()
+            .map(|(type_path, value)| (type_path.to_string(), value.clone()))
+            .collect()
+    }
+
+    fn changed(path: &str, old: Value, new: Value) -> DiffLine {
+        DiffLine {
+            path: path.to_string(),
+            change: Change::Changed { old, new },
+        }
+    }
+
+    #[test]
+    fn equal_snapshots_have_no_differences() {
+        let snapshot = components(&[("game::Health", json!({ "current": 10 }))]);
+        assert_eq!(diff(&snapshot, &snapshot), []);
+    }
+
+    #[test]
+    fn lists_added_and_removed_components_whole() {
+        let before = components(&[
+            ("game::Health", json!({ "current": 10 })),
+            ("game::Burning", json!({ "damage": 2 })),
+        ]);
+        let after = components(&[
+            ("game::Health", json!({ "current": 10 })),
+            ("game::Frozen", json!(null)),
+        ]);
+        assert_eq!(
+            diff(&before, &after),
+            [
+                DiffLine {
+                    path: "Burning".to_string(),
+                    change: Change::Removed(json!({ "damage": 2 })),
+                },
+                DiffLine {
+                    path: "Frozen".to_string(),
+                    change: Change::Added(json!(null)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_just_the_innermost_changed_fields() {
+        let before = components(&[(
+            "bevy_transform::components::transform::Transform",
+            json!({ "translation": [0.0, 1.0, 2.0], "scale": [1.0, 1.0, 1.0] }),
+        )]);
+        let after = components(&[(
+            "bevy_transform::components::transform::Transform",
+            json!({ "translation": [0.0, 1.5, 2.0], "scale": [1.0, 1.0, 1.0] }),
+        )]);
+        assert_eq!(
+            diff(&before, &after),
+            [changed("Transform.translation[1]", json!(1.0), json!(1.5))]
+        );
+    }
+
+    #[test]
+    fn goes_into_nested_objects() {
+        let before = components(&[(
+            "game::Stats",
+            json!({ "attack": { "base": 5, "bonus": 1 }, "name": "Ogre" }),
+        )]);
+        let after = components(&[(
+            "game::Stats",
+            json!({ "attack": { "base": 5, "critical": 2 }, "name": "Troll" }),
+        )]);
+        assert_eq!(
+            diff(&before, &after),
+            [
+                DiffLine {
+                    path: "Stats.attack.bonus".to_string(),
+                    change: Change::Removed(json!(1)),
+                },
+                DiffLine {
+                    path: "Stats.attack.critical".to_string(),
+                    change: Change::Added(json!(2)),
+                },
+                changed("Stats.name", json!("Ogre"), json!("Troll")),
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_items_past_the_end_of_the_shorter_array() {
+        let before = components(&[("game::Inventory", json!({ "items": ["sword", "shield"] }))]);
+        let grown = components(&[(
+            "game::Inventory",
+            json!({ "items": ["sword", "shield", "potion"] }),
+        )]);
+        let shrunk = components(&[("game::Inventory", json!({ "items": ["axe"] }))]);
+
+        assert_eq!(
+            diff(&before, &grown),
+            [DiffLine {
+                path: "Inventory.items[2]".to_string(),
+                change: Change::Added(json!("potion")),
+            }]
+        );
+        assert_eq!(
+            diff(&before, &shrunk),
+            [
+                changed("Inventory.items[0]", json!("sword"), json!("axe")),
+                DiffLine {
+                    path: "Inventory.items[1]".to_string(),
+                    change: Change::Removed(json!("shield")),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn values_of_another_kind_are_changed_whole() {
+        let before = components(&[("game::Target", json!({ "Entity": 4294967297u64 }))]);
+        let after = components(&[("game::Target", json!("None"))]);
+        assert_eq!(
+            diff(&before, &after),
+            [changed(
+                "Target",
+                json!({ "Entity": 4294967297u64 }),
+                json!("None")
+            )]
+        );
+    }
+
+    #[test]
+    fn components_sharing_a_short_name_are_told_apart() {
+        let before = components(&[
+            (
+                "bevy_asset::handle::Handle<game::Mesh>",
+                json!({ "Strong": 1 }),
+            ),
+            ("game::ui::Handle<game::Mesh>", json!({ "Strong": 2 })),
+        ]);
+        let after = components(&[
+            (
+                "bevy_asset::handle::Handle<game::Mesh>",
+                json!({ "Strong": 3 }),
+            ),
+            ("game::ui::Handle<game::Mesh>", json!({ "Strong": 4 })),
+        ]);
+        assert_eq!(
+            diff(&before, &after),
+            [
+                changed("handle::Handle<Mesh>.Strong", json!(1), json!(3)),
+                changed("ui::Handle<Mesh>.Strong", json!(2), json!(4)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,44 @@
+//! Opening the documentation of component types on docs.rs.
+
+use anyhow::Context;
+use std::{
+    process::{Command, Stdio},
+    thread,
+};
+
+/// A best-effort link to the docs.rs page of the type at `type_path`, which only resolves when
+/// every module in the path is public. `is_enum` picks between the pages of enums and structs.
+pub fn url(type_path: &str, is_enum: bool) -> String {
+    // Generic arguments aren't part of the page.
+    let path = type_path.split('<').next().unwrap_or(type_path);
+    let mut segments: Vec<_> = path.split("::").collect();
+    let name = segments.pop().unwrap_or_default();
+    let krate = segments.first().copied().unwrap_or(name);
+    let kind = if is_enum { "enum" } else { "struct" };
+    let modules: String = segments.iter().map(|module| format!("{module}/")).collect();
+    format!("https://docs.rs/{krate}/latest/{modules}{kind}.{name}.html")
+}
+
+/// Open `url` in the default browser without waiting for it to close.
+pub fn open(url: &str) -> anyhow::Result<()> {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        // The empty argument is the title of the window, without it the url would be taken as it.
+        command.args(["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    // The browser mustn't draw over or read from the terminal brptui is using.
+    let mut child = command
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to open the browser")?;
+    thread::spawn(move || child.wait());
+    Ok(())
+}
@@ -0,0 +1,31 @@
+//! Editing values in the user's own editor, for changes too big for the inspector.
+
+use anyhow::{bail, Context};
+use std::{env, fs, process::Command};
+
+/// Open `text` in the editor set by `$VISUAL` or `$EDITOR` and return it once the editor exits.
+///
+/// The terminal must be handed over to the editor before calling this, it blocks until the editor
+/// is closed.
+pub fn edit(text: &str) -> anyhow::Result<String> {
+    let command = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|command| !command.trim().is_empty())
+        .context("Set $EDITOR to the editor to open values in")?;
+    // Editors are often given with arguments, like `code --wait`.
+    let mut words = command.split_whitespace();
+    let program = words.next().unwrap_or_default();
+
+    let path = env::temp_dir().join(format!("brptui-{}.json", std::process::id()));
+    fs::write(&path, text).context("Failed to write the value to edit")?;
+    let status = Command::new(program).args(words).arg(&path).status();
+    let edited = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+
+    let status = status.with_context(|| format!("Failed to run {program}"))?;
+    if !status.success() {
+        bail!("{program} exited with {status}");
+    }
+    edited.context("Failed to read the edited value")
+}
@@ -0,0 +1,86 @@
+//! Listing only the entities that have, or don't have, certain components.
+
+use crate::{registry::Registry, theme::Theme};
+use disqualified::ShortName;
+use ratatui::text::{Line, Span};
+
+/// The components entities must have and must not have to be listed, by type path.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EntityFilter {
+    pub with: Vec<String>,
+    pub without: Vec<String>,
+}
+
+impl EntityFilter {
+    /// Parse a filter typed like `Transform -GlobalTransform`, where components starting with `-`
+    /// are excluded and the rest, optionally starting with `+`, are required.
+    ///
+    /// Components can be given by their short name if the registry is available to look up the
+    /// type path, otherwise they need their full type path.
+    pub fn parse(text: &str, registry: Option<&Registry>) -> Result<Self, String> {
+        let mut filter = Self::default();
+        for word in text.split_whitespace() {
+            let (name, list) = match word.strip_prefix('-') {
+                Some(name) => (name, &mut filter.without),
+                None => (word.strip_prefix('+').unwrap_or(word), &mut filter.with),
+            };
+            let type_path = resolve(name, registry)?;
+            if !list.contains(&type_path) {
+                list.push(type_path);
+            }
+        }
+        Ok(filter)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.with.is_empty() && self.without.is_empty()
+    }
+
+    /// The filter as it would be typed, to start editing it from.
+    pub fn text(&self) -> String {
+        let with = self.with.iter().cloned();
+        let without = self.without.iter().map(|path| format!("-{path}"));
+        with.chain(without).collect::<Vec<_>>().join(" ")
+    }
+
+    /// A summary for the title of the entities panel, like `with Transform without Parent`.
+    pub fn title(&self, theme: &Theme) -> Line<'static> {
+        let mut spans = Vec::new();
+        for (label, paths) in [("with ", &self.with), ("without ", &self.without)] {
+            if paths.is_empty() {
+                continue;
+            }
+            if !spans.is_empty() {
+                spans.push(Span::raw(" "));
+            }
+            spans.push(Span::styled(label, theme.dim()));
+            let names: Vec<_> = paths.iter().map(|p| ShortName(p).to_string()).collect();
+            spans.push(Span::raw(names.join(", ")));
+        }
+        Line::from(spans)
+    }
+}
+
+/// The type path of the component with the given name.
+fn resolve(name: &str, registry: Option<&Registry>) -> Result<String, String> {
+    let Some(registry) = registry else {
+        // Without the registry the app checks the path when queried.
+        return match name.contains("::") {
+            true => Ok(name.to_string()),
+            false => Err(format!(
+                "{name} needs its full type path since the registry isn't available"
+            )),
+        };
+    };
+    if let Some(component) = registry.components.iter().find(|c| c.type_path == name) {
+        return Ok(component.type_path.clone());
+    }
+    let mut matches = registry.components.iter().filter(|c| c.short_path == name);
+    match (matches.next(), matches.next()) {
+        (Some(component), None) => Ok(component.type_path.clone()),
+        (Some(_), Some(_)) => Err(format!(
+            "More than one component is named {name}, use its full type path"
+        )),
+        (None, _) => Err(format!("{name} isn't a registered component")),
+    }
+}
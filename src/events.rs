@@ -1,37 +1,88 @@
 //! Logic for handling [`event::Event`]s.
 
-use crate::Message;
-use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
-use std::sync::mpsc;
+use crate::{action_log::LogEntry, Message, ThreadQuitToken};
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex, MutexGuard, PoisonError,
+    },
+    thread,
+    time::Duration,
+};
+
+/// How long to wait before reading again after failing to read an event.
+const READ_ERROR_DELAY: Duration = Duration::from_millis(100);
+/// How long to wait for an event before checking whether reading has been paused.
+const POLL_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Lets the main thread stop [`handle_events`] reading the terminal, so a program run in it such
+/// as an editor gets all of the input.
+#[derive(Debug, Default, Clone)]
+pub struct EventGate {
+    paused: Arc<AtomicBool>,
+    /// Held by the event handler while it is reading.
+    reading: Arc<Mutex<()>>,
+}
+
+impl EventGate {
+    /// Stop reading events until the returned guard is dropped, waiting for any read in progress
+    /// to finish.
+    pub fn pause(&self) -> PausedEvents<'_> {
+        self.paused.store(true, Ordering::Relaxed);
+        PausedEvents {
+            gate: self,
+            _reading: self.reading.lock().unwrap_or_else(PoisonError::into_inner),
+        }
+    }
+}
+
+/// Events aren't read while this is alive.
+pub struct PausedEvents<'a> {
+    gate: &'a EventGate,
+    _reading: MutexGuard<'a, ()>,
+}
+
+impl Drop for PausedEvents<'_> {
+    fn drop(&mut self) {
+        self.gate.paused.store(false, Ordering::Relaxed);
+    }
+}
 
 /// Resulting [`Message`]s will be sent using the given [`mpsc::Sender`] to the
-/// main thread to be handled.
-pub fn handle_events(tx: mpsc::Sender<Message>) {
+/// main thread to be handled. Returns once the main thread stops receiving or quits with the
+/// `quit` token.
+pub fn handle_events(tx: mpsc::Sender<Message>, gate: EventGate, quit: ThreadQuitToken) {
     loop {
-        let message = match event::read().unwrap() {
-            Event::Key(key) if key.kind == KeyEventKind::Press => handle_key(key),
-            _ => None,
+        if quit.should_quit() {
+            return;
+        }
+        if gate.paused.load(Ordering::Relaxed) {
+            thread::sleep(POLL_TIMEOUT);
+            continue;
+        }
+        let reading = gate.reading.lock().unwrap_or_else(PoisonError::into_inner);
+        let message = match event::poll(POLL_TIMEOUT).and_then(|ready| match ready {
+            true => event::read().map(Some),
+            false => Ok(None),
+        }) {
+            Ok(Some(Event::Key(key))) if key.kind == KeyEventKind::Press => Some(Message::Key(key)),
+            Ok(Some(Event::Resize(..))) => Some(Message::Resize),
+            Ok(_) => None,
+            // Failing to read is usually transient so keep going, but don't spin on it.
+            Err(err) => {
+                thread::sleep(READ_ERROR_DELAY);
+                Some(Message::Log(LogEntry::error(format!(
+                    "Failed to read terminal event: {err}"
+                ))))
+            }
         };
+        drop(reading);
 
         if let Some(msg) = message {
-            tx.send(msg).unwrap();
+            if tx.send(msg).is_err() {
+                return;
+            }
         }
     }
 }
-
-fn handle_key(key: event::KeyEvent) -> Option<Message> {
-    match key.code {
-        KeyCode::Left | KeyCode::Char('h') => Some(Message::MoveLeft),
-        KeyCode::Right | KeyCode::Char('l') => Some(Message::MoveRight),
-        KeyCode::Up | KeyCode::Char('k') => Some(Message::MoveUp),
-        KeyCode::Down | KeyCode::Char('j') => Some(Message::MoveDown),
-        KeyCode::PageUp | KeyCode::Char('[') => Some(Message::PageUp),
-        KeyCode::PageDown | KeyCode::Char(']') => Some(Message::PageDown),
-        KeyCode::Home => Some(Message::Home),
-        KeyCode::End => Some(Message::End),
-        KeyCode::Delete | KeyCode::Char('x') => Some(Message::Delete),
-        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(Message::Quit),
-        KeyCode::Char('q') => Some(Message::Quit),
-        _ => None,
-    }
-}
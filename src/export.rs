@@ -0,0 +1,130 @@
+//! Dumping every entity and its components to a JSON file.
+
+use crate::{brp, theme::Theme, transport::Socket, Message, ThreadQuitToken};
+use bevy_ecs::entity::Entity;
+use bevy_remote::builtin_methods::{BrpGetParams, BrpGetResponse, BrpListParams};
+use ratatui::{
+    style::Stylize,
+    text::{Line, Span},
+};
+use serde_json::{Map, Value};
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::PathBuf,
+    sync::mpsc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The width of the progress bar in the footer, in cells.
+const PROGRESS_BAR_WIDTH: usize = 20;
+
+/// A finished export.
+#[derive(Debug, Clone)]
+pub struct Exported {
+    pub path: PathBuf,
+    /// The entities that couldn't be fetched, such as those despawned during the export, which
+    /// are listed under `skipped` in the file instead.
+    pub skipped: usize,
+}
+
+/// An export running on a worker thread.
+#[derive(Debug)]
+pub struct Export {
+    pub quitter: ThreadQuitToken,
+    /// The number of entities fetched so far.
+    pub done: usize,
+    pub total: usize,
+}
+
+impl Export {
+    /// A progress bar for the footer.
+    pub fn line(&self, theme: &Theme) -> Line<'_> {
+        let filled = (self.done * PROGRESS_BAR_WIDTH)
+            .checked_div(self.total)
+            .unwrap_or(PROGRESS_BAR_WIDTH);
+        Line::from(vec![
+            Span::raw(format!("exporting {}/{} ", self.done, self.total)).fg(theme.accent),
+            Span::raw(theme.symbol("█", "#").repeat(filled)).fg(theme.accent),
+            Span::styled(
+                theme.symbol("░", "-").repeat(PROGRESS_BAR_WIDTH - filled),
+                theme.dim(),
+            ),
+            Span::styled("  esc", theme.dim().bold()),
+            Span::styled(" cancel", theme.dim()),
+        ])
+    }
+}
+
+/// Fetch the components of every given entity and write them to a JSON file in the working
+/// directory, mapping entity ids to their components. Entities that can't be fetched are mapped
+/// to why under `skipped` rather than failing the export, as entities come and go in a running
+/// app.
+///
+/// An [`Message::ExportProgress`] is sent after each entity and [`Message::ExportFinished`] once
+/// done, with the path of the file unless the export failed or was cancelled with the `quit`
+/// token.
+pub fn export_world(
+    tx: mpsc::Sender<Message>,
+    socket: &Socket,
+    entities: Vec<Entity>,
+    quit: ThreadQuitToken,
+) {
+    let result = fetch_and_write(&tx, socket, &entities, &quit);
+    let _ = tx.send(Message::ExportFinished(
+        result.map_err(|err| format!("{err:#}")),
+    ));
+}
+
+fn fetch_and_write(
+    tx: &mpsc::Sender<Message>,
+    socket: &Socket,
+    entities: &[Entity],
+    quit: &ThreadQuitToken,
+) -> anyhow::Result<Option<Exported>> {
+    let mut world = Map::new();
+    let mut skipped = Map::new();
+    for (n, &entity) in entities.iter().enumerate() {
+        if quit.should_quit() {
+            return Ok(None);
+        }
+
+        let id = entity.to_bits().to_string();
+        match fetch_components(socket, entity) {
+            Ok(components) => world.insert(id, Value::Object(components.into_iter().collect())),
+            Err(err) => skipped.insert(id, Value::String(format!("{err:#}"))),
+        };
+        let _ = tx.send(Message::ExportProgress(n + 1));
+    }
+
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let path = PathBuf::from(format!("world-{secs}.json"));
+    let skipped_count = skipped.len();
+    let mut document = Map::new();
+    document.insert(String::from("entities"), Value::Object(world));
+    if !skipped.is_empty() {
+        document.insert(String::from("skipped"), Value::Object(skipped));
+    }
+    serde_json::to_writer_pretty(BufWriter::new(File::create(&path)?), &document)?;
+    Ok(Some(Exported {
+        path,
+        skipped: skipped_count,
+    }))
+}
+
+fn fetch_components(socket: &Socket, entity: Entity) -> anyhow::Result<Map<String, Value>> {
+    let components = brp::list_request(socket, BrpListParams { entity })?;
+    let params = BrpGetParams {
+        entity,
+        components,
+        strict: false,
+    };
+    Ok(match brp::get_request(socket, params)? {
+        BrpGetResponse::Lenient { components, .. } | BrpGetResponse::Strict(components) => {
+            components.into_iter().collect()
+        }
+    })
+}
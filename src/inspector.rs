@@ -1,18 +1,77 @@
-use crate::PRIMARY_COLOR;
+use crate::{
+    brp::{EntityLabel, EntityMeta},
+    registry::Registry,
+    text_input::TextInput,
+    theme::{self, Theme},
+};
+use bevy_ecs::entity::Entity;
 use ratatui::{
     prelude::{BlockExt, Buffer, Rect},
     style::{Color, Style, Stylize},
     text::{Line, Span},
     widgets::{Block, StatefulWidget, Widget},
 };
-use serde_json::{Number, Value};
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
+use std::{collections::HashSet, ops::Range, time::Duration};
 
 const INDENT_AMOUNT: u16 = 3;
+/// The most lines kept visible above and below the selection when scrolling, shorter inspectors
+/// keep a quarter of their height.
+const MAX_SCROLL_MARGIN: usize = 6;
+/// The shortest array of small integers taken to be bytes, shorter ones are likely vectors or
+/// colors.
+const MIN_BYTES_LEN: usize = 16;
+/// The number of bytes shown in hex on the line of a byte array.
+const BYTES_PREVIEW_LEN: usize = 16;
 
 pub struct Inspector<'a> {
     value: &'a Value,
     block: Option<Block<'a>>,
     focused: bool,
+    swatches: bool,
+    /// The registry and type path of the value, used to annotate fields with their types.
+    types: Option<(&'a Registry, &'a str)>,
+    /// The paths of the objects and arrays to show collapsed, or expanded for byte arrays which
+    /// are collapsed by default.
+    collapsed: Option<&'a HashSet<String>>,
+    /// The entities to label references to other entities with.
+    entities: &'a [EntityMeta],
+    entity_label: EntityLabel,
+    /// Whether moving past the first or last line moves to the other end.
+    wrap: bool,
+    /// Whether the selected line flashes when the selection moves to it.
+    flash: bool,
+    bool_style: BoolStyle,
+    /// Whether the fields of objects are listed alphabetically.
+    sort_fields: bool,
+    theme: Theme,
+}
+
+/// How booleans are shown in the inspector. The selected one is always shown as text.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BoolStyle {
+    /// `true` and `false`.
+    #[default]
+    Text,
+    /// `☑` and `☐`.
+    Icons,
+    /// `[x]` and `[ ]`, for terminals without the icons.
+    Ascii,
+}
+
+impl BoolStyle {
+    /// The symbol shown in place of the text of `value`, if any.
+    fn symbol(self, value: bool) -> Option<&'static str> {
+        match (self, value) {
+            (BoolStyle::Text, _) => None,
+            (BoolStyle::Icons, true) => Some("☑"),
+            (BoolStyle::Icons, false) => Some("☐"),
+            (BoolStyle::Ascii, true) => Some("[x]"),
+            (BoolStyle::Ascii, false) => Some("[ ]"),
+        }
+    }
 }
 
 impl<'a> Inspector<'a> {
@@ -21,6 +80,16 @@ impl<'a> Inspector<'a> {
             value,
             block: None,
             focused,
+            swatches: true,
+            types: None,
+            collapsed: None,
+            entities: &[],
+            entity_label: EntityLabel::default(),
+            wrap: false,
+            flash: false,
+            bool_style: BoolStyle::default(),
+            sort_fields: false,
+            theme: Theme::default(),
         }
     }
 
@@ -29,6 +98,61 @@ impl<'a> Inspector<'a> {
         self
     }
 
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Whether moving up from the first line selects the last and moving down from the last
+    /// selects the first, otherwise the selection stops at the ends.
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Flash the selected line for a frame when the selection moves to it.
+    pub fn flash(mut self, flash: bool) -> Self {
+        self.flash = flash;
+        self
+    }
+
+    pub fn bool_style(mut self, bool_style: BoolStyle) -> Self {
+        self.bool_style = bool_style;
+        self
+    }
+
+    /// List the fields of objects alphabetically rather than in the order the app sent them.
+    pub fn sort_fields(mut self, sort_fields: bool) -> Self {
+        self.sort_fields = sort_fields;
+        self
+    }
+
+    /// Show a swatch next to objects that look like colors.
+    pub fn swatches(mut self, swatches: bool) -> Self {
+        self.swatches = swatches;
+        self
+    }
+
+    /// Annotate fields with their type, looked up in the registry as fields of `type_path`.
+    pub fn types(mut self, registry: &'a Registry, type_path: &'a str) -> Self {
+        self.types = Some((registry, type_path));
+        self
+    }
+
+    /// Collapse the objects and arrays at the given paths down to a single line.
+    pub fn collapsed(mut self, collapsed: &'a HashSet<String>) -> Self {
+        self.collapsed = Some(collapsed);
+        self
+    }
+
+    /// Label fields referencing other entities with the entity, if it is among `entities`. Which
+    /// fields hold entities is looked up in the registry given to [`Inspector::types`].
+    pub fn entities(mut self, entities: &'a [EntityMeta], label: EntityLabel) -> Self {
+        self.entities = entities;
+        self.entity_label = label;
+        self
+    }
+
     fn fields(&self) -> usize {
         match self.value {
             Value::Object(obj) => obj.len(),
@@ -42,7 +166,89 @@ pub struct InspectorState {
     selected: usize,
     paths: Vec<String>,
     value_types: Vec<ValueType>,
+    /// The line each selectable line is on, counting every line.
+    selectable_lines: Vec<usize>,
     scroll: usize,
+    /// The height the inspector was last rendered at, used to move by half of it.
+    height: usize,
+    /// Whether the inspector was last rendered wrapping the selection around its ends.
+    wrap: bool,
+    /// Whether the selection moved to another line since the last render.
+    moved: bool,
+    /// Only fields with a name or path containing this are shown, along with the fields around
+    /// them.
+    pub filter: TextInput,
+    /// The text shown in place of the selected value while it is being edited.
+    pub edit: Option<TextInput>,
+    /// Why the text being edited can't be set, shown next to it until it is fixed.
+    pub edit_error: Option<String>,
+    /// The lines last rendered, kept until the value or anything else they depend on changes so
+    /// that unchanged frames don't flatten the whole value again.
+    flattened: Option<Flattened>,
+}
+
+/// The lines of a value along with everything they were flattened from.
+#[derive(Debug)]
+struct Flattened {
+    value: Value,
+    collapsed: HashSet<String>,
+    query: String,
+    sort_fields: bool,
+    ascii: bool,
+    /// The type the lines were annotated as, if the registry was available.
+    type_path: Option<String>,
+    lines: Vec<InspectorLine>,
+}
+
+impl Flattened {
+    fn new(inspector: &Inspector, query: &str) -> Self {
+        // The contents of collapsed values are skipped while flattening, unless filtering as the
+        // matches within them still need to be found.
+        let lines = match query.is_empty() {
+            true => flatten_value(
+                inspector.value,
+                inspector.types,
+                inspector.collapsed,
+                inspector.sort_fields,
+                &inspector.theme,
+            ),
+            false => collapse_lines(
+                filter_lines(
+                    flatten_value(
+                        inspector.value,
+                        inspector.types,
+                        None,
+                        inspector.sort_fields,
+                        &inspector.theme,
+                    ),
+                    query,
+                ),
+                inspector.collapsed,
+            ),
+        };
+        Self {
+            value: inspector.value.clone(),
+            collapsed: inspector.collapsed.cloned().unwrap_or_default(),
+            query: query.to_string(),
+            sort_fields: inspector.sort_fields,
+            ascii: inspector.theme.ascii,
+            type_path: inspector.types.map(|(_, type_path)| type_path.to_string()),
+            lines,
+        }
+    }
+
+    /// Whether these are the lines the inspector would flatten with the given filter query.
+    fn is_for(&self, inspector: &Inspector, query: &str) -> bool {
+        self.query == query
+            && self.sort_fields == inspector.sort_fields
+            && self.ascii == inspector.theme.ascii
+            && self.type_path.as_deref() == inspector.types.map(|(_, type_path)| type_path)
+            && match inspector.collapsed {
+                Some(collapsed) => self.collapsed == *collapsed,
+                None => self.collapsed.is_empty(),
+            }
+            && self.value == *inspector.value
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -53,6 +259,12 @@ pub enum ValueType {
     String,
     Array,
     Object,
+    /// A Bevy enum, either a unit variant string or an object with the variant as its only key.
+    Enum,
+    /// A tuple or tuple struct with more than one field.
+    Tuple,
+    /// A number the registry says is an entity, such as the one in `Parent`.
+    Entity,
 }
 
 impl StatefulWidget for Inspector<'_> {
@@ -63,19 +275,44 @@ impl StatefulWidget for Inspector<'_> {
             block.render(area, buf);
         }
         let area = self.block.inner_if_some(area);
+        // Hidden panels are given no space.
+        if area.is_empty() {
+            return;
+        }
 
         if self.fields() == 0 {
+            // The lines of the value shown before are no longer there to select.
+            state.paths.clear();
+            state.value_types.clear();
+            state.selectable_lines.clear();
+            state.selected = 0;
+            state.flattened = None;
             Line::raw("Nothing to show").bold().render(area, buf);
             return;
         }
 
-        let flat_map = flatten_value(self.value);
+        let query = state.filter.value();
+        let (flattened, changed) = match state.flattened.take() {
+            Some(flattened) if flattened.is_for(&self, query) => (flattened, false),
+            _ => (Flattened::new(&self, query), true),
+        };
+        let flat_map = &flattened.lines;
 
-        state.update_paths(&flat_map);
-        state.update_value_types(&flat_map);
-        state.update_selected(&flat_map);
-        state.update_scroll(&flat_map, area.height);
+        if changed {
+            state.update_selectable_lines(flat_map);
+            state.paths = flat_map
+                .iter()
+                .filter(|line| line.selectable())
+                .map(|line| line.path.clone())
+                .collect();
+        }
+        state.update_selected();
+        state.height = area.height as usize;
+        state.wrap = self.wrap;
+        state.update_scroll(flat_map.len(), area.height);
         let upper_limit = (state.scroll + area.height as usize).min(flat_map.len());
+        state.update_value_types(flat_map, self.types, state.scroll..upper_limit, changed);
+        let selected_y = state.selectable_lines.get(state.selected).copied();
 
         for (y, line) in flat_map[state.scroll..upper_limit].iter().enumerate() {
             let mut rect = Rect {
@@ -85,18 +322,23 @@ impl StatefulWidget for Inspector<'_> {
                 y: area.y + y as u16,
             };
 
-            let selected = self.focused && line.path == state.selected_path();
+            let selected = self.focused && selected_y == Some(state.scroll + y);
+            let collapsed = line.collapsed_by_default()
+                != self.collapsed.is_some_and(|c| c.contains(&line.path));
 
             // Since the indent is just blank space there is no point rendering anything and the
             // space can just be subtracted from the lines rect.
             let _indent_rect = split_rect(&mut rect, line.indent_level * INDENT_AMOUNT);
 
-            if let Some(name) = line.name {
-                let name_rect = split_rect(&mut rect, name.len() as u16 + 2);
-                Line::from(vec![Span::raw(name), Span::raw(": ")])
-                    .bold()
+            if let Some(name) = &line.name {
+                let name = Line::from(match name {
+                    LineName::Field(name) => vec![Span::raw(name.as_str()), Span::raw(": ")],
+                    LineName::Key(key) => vec![Span::raw(key.as_str()), Span::raw(" => ")],
+                });
+                let name_rect = split_rect(&mut rect, name.width() as u16);
+                name.bold()
                     .fg(if selected {
-                        PRIMARY_COLOR
+                        self.theme.selection
                     } else {
                         Color::Reset
                     })
@@ -104,136 +346,449 @@ impl StatefulWidget for Inspector<'_> {
             }
 
             match &line.kind {
-                InspectorLineKind::ObjectStart => render_char(rect, buf, '{', selected),
-                InspectorLineKind::ObjectEnd => render_char(rect, buf, '}', selected),
+                InspectorLineKind::ObjectStart {
+                    swatch,
+                    time,
+                    variant,
+                    len,
+                } => {
+                    // The one field of an enum variant is its name, which isn't worth counting.
+                    let count = match len {
+                        _ if *variant || time.is_some() => None,
+                        1 => Some(String::from("1 field")),
+                        len => Some(format!("{len} fields")),
+                    };
+                    let mut rest =
+                        render_start(rect, buf, '{', selected, collapsed, count, &self.theme);
+                    if let Some(time) = time {
+                        let span = Span::raw(format!(" {time}"));
+                        let time_rect = split_rect(&mut rest, span.width() as u16);
+                        span.render(time_rect, buf);
+                    }
+                    if let Some(color) = swatch.filter(|_| self.swatches) {
+                        let _gap_rect = split_rect(&mut rest, 1);
+                        Span::raw("    ").bg(color).render(rest, buf);
+                    }
+                }
+                InspectorLineKind::ObjectEnd => render_char(rect, buf, '}', selected, &self.theme),
 
-                InspectorLineKind::ArrayStart => render_char(rect, buf, '[', selected),
-                InspectorLineKind::ArrayEnd => render_char(rect, buf, ']', selected),
+                InspectorLineKind::ArrayStart { len, bytes } => {
+                    let count = match (len, bytes) {
+                        (len, Some(_)) => format!("{len} bytes"),
+                        (1, None) => String::from("1 item"),
+                        (len, None) => format!("{len} items"),
+                    };
+                    let mut rest = render_start(
+                        rect,
+                        buf,
+                        '[',
+                        selected,
+                        collapsed,
+                        Some(count),
+                        &self.theme,
+                    );
+                    if let Some(bytes) = bytes.as_ref().filter(|_| collapsed) {
+                        let span = Span::raw(format!(" {bytes}"));
+                        let bytes_rect = split_rect(&mut rest, span.width() as u16);
+                        truncate(span, bytes_rect.width, &self.theme).render(bytes_rect, buf);
+                    }
+                }
+                InspectorLineKind::ArrayEnd => render_char(rect, buf, ']', selected, &self.theme),
 
                 InspectorLineKind::Item { value } => {
-                    let span = match value {
-                        PrimitiveValue::Null => Span::raw("None"),
-                        PrimitiveValue::Bool(b) => Span::raw(b.to_string()),
-                        PrimitiveValue::Number(n) => Span::raw(n.to_string()),
-                        PrimitiveValue::String(s) => Span::raw(*s),
+                    let field_type = self.types.and_then(|(registry, type_path)| {
+                        registry.field_type(type_path, &line.path)
+                    });
+                    let is_float = matches!(field_type, Some("f32" | "f64"));
+                    let editing = state.edit.as_ref().filter(|_| selected);
+                    let span = match (editing, value) {
+                        (Some(edit), _) => {
+                            Span::raw(format!("{}{}", edit.value(), self.theme.cursor()))
+                        }
+                        (None, PrimitiveValue::Null) if is_float => {
+                            Span::raw(null_float(&self.theme)).italic()
+                        }
+                        (None, PrimitiveValue::Null) => Span::raw("None"),
+                        (None, PrimitiveValue::Bool(b)) => {
+                            let bool_style = match self.bool_style {
+                                BoolStyle::Icons if self.theme.ascii => BoolStyle::Ascii,
+                                bool_style => bool_style,
+                            };
+                            match bool_style.symbol(*b).filter(|_| !selected) {
+                                Some(symbol) => Span::raw(symbol),
+                                None => Span::raw(b.to_string()),
+                            }
+                        }
+                        (None, PrimitiveValue::Number(n)) => {
+                            Span::raw(number_text(n, field_type, &self.theme))
+                        }
+                        (None, PrimitiveValue::String(s)) => match special_float(s, &self.theme) {
+                            Some(marker) => Span::raw(marker).italic(),
+                            None => Span::raw(s.as_str()),
+                        },
                     };
-                    if selected {
-                        span.fg(PRIMARY_COLOR).bold().render(rect, buf);
+                    // Leave room for at least the start of the type annotation.
+                    let span = truncate(span, rect.width.saturating_sub(2), &self.theme);
+                    let value_rect = split_rect(&mut rect, span.width() as u16 + 2);
+                    let edit_error = editing.and(state.edit_error.as_deref());
+                    if editing.is_some() {
+                        let color = match edit_error {
+                            Some(_) => self.theme.error,
+                            None => self.theme.accent,
+                        };
+                        span.fg(color).bold().render(value_rect, buf);
+                    } else if selected {
+                        span.fg(self.theme.selection).bold().render(value_rect, buf);
                     } else {
-                        span.render(rect, buf);
+                        span.render(value_rect, buf);
                     };
+                    // The error takes the place of everything after the value until it is fixed.
+                    if let Some(error) = edit_error {
+                        Span::styled(error, Style::default().fg(self.theme.error))
+                            .render(rect, buf);
+                        continue;
+                    }
+                    if let (PrimitiveValue::Number(n), Some((registry, type_path))) =
+                        (value, self.types)
+                    {
+                        if registry.is_entity(type_path, &line.path) {
+                            let target =
+                                n.as_u64().and_then(|bits| Entity::try_from_bits(bits).ok());
+                            let span =
+                                match self.entities.iter().find(|meta| Some(meta.id) == target) {
+                                    Some(meta) => Span::styled(
+                                        format!(
+                                            "{} {}",
+                                            self.theme.symbol("→", "->"),
+                                            meta.label(self.entity_label)
+                                        ),
+                                        Style::default().fg(self.theme.accent),
+                                    ),
+                                    None => Span::styled(
+                                        self.theme.symbol("→ gone", "-> gone"),
+                                        self.theme.dim(),
+                                    ),
+                                };
+                            let span = truncate(span, rect.width.saturating_sub(2), &self.theme);
+                            let link_rect = split_rect(&mut rect, span.width() as u16 + 2);
+                            span.render(link_rect, buf);
+                        }
+                    }
+                    if let Some(field_type) = field_type {
+                        Span::styled(field_type, self.theme.dim()).render(rect, buf);
+                    }
                 }
             }
         }
+
+        let flash = self.flash && self.focused && std::mem::take(&mut state.moved);
+        if let Some(y) = selected_y.filter(|y| flash && (state.scroll..upper_limit).contains(y)) {
+            let indent = flat_map[y].indent_level * INDENT_AMOUNT;
+            let row = Rect {
+                x: area.x + indent.min(area.width),
+                y: area.y + (y - state.scroll) as u16,
+                width: area.width.saturating_sub(indent),
+                height: 1,
+            };
+            theme::flash(buf, row);
+        }
+
+        state.flattened = Some(flattened);
     }
 }
 
 impl InspectorState {
     pub fn select_previous(&mut self) {
-        self.selected = self.selected.saturating_sub(1);
+        // Values such as empty objects have nothing to select.
+        if self.value_types.is_empty() {
+            return;
+        }
+        let last = self.value_types.len().saturating_sub(1);
+        self.move_to(match self.selected.checked_sub(1) {
+            Some(n) => n,
+            None if self.wrap => last,
+            None => 0,
+        });
     }
 
     pub fn select_next(&mut self) {
-        self.selected = (self.selected + 1).min(self.value_types.len() - 1);
+        if self.value_types.is_empty() {
+            return;
+        }
+        let last = self.value_types.len().saturating_sub(1);
+        self.move_to(match self.selected + 1 {
+            n if n <= last => n,
+            _ if self.wrap => 0,
+            _ => last,
+        });
+    }
+
+    /// Move down by half the visible lines, scrolling the view along with the selection.
+    pub fn select_next_half_page(&mut self) {
+        let Some(&from) = self.selectable_lines.get(self.selected) else {
+            return;
+        };
+        let half = (self.height / 2).max(1);
+        // The last selectable line within half a page, or the next one if there is none.
+        self.move_to(
+            self.selectable_lines
+                .iter()
+                .rposition(|&y| y <= from + half)
+                .unwrap_or(self.selected)
+                .max(self.selected + 1)
+                .min(self.selectable_lines.len() - 1),
+        );
+        self.scroll += half;
+    }
+
+    /// Move up by half the visible lines, scrolling the view along with the selection.
+    pub fn select_previous_half_page(&mut self) {
+        let Some(&from) = self.selectable_lines.get(self.selected) else {
+            return;
+        };
+        let half = (self.height / 2).max(1);
+        // The first selectable line within half a page, or the previous one if there is none.
+        self.move_to(
+            self.selectable_lines
+                .iter()
+                .position(|&y| y + half >= from)
+                .unwrap_or(self.selected)
+                .min(self.selected.saturating_sub(1)),
+        );
+        self.scroll = self.scroll.saturating_sub(half);
     }
 
     pub fn select_first(&mut self) {
-        self.selected = 0;
+        self.move_to(0);
     }
 
     pub fn select_last(&mut self) {
-        self.selected = self.value_types.len() - 1;
+        self.move_to(self.value_types.len().saturating_sub(1));
     }
 
-    pub fn selected_path(&self) -> &str {
-        &self.paths[self.selected]
+    /// Select the given line, marking the selection as moved if it is another line.
+    fn move_to(&mut self, selected: usize) {
+        self.moved |= selected != self.selected;
+        self.selected = selected;
     }
 
-    pub fn selected_value_type(&self) -> ValueType {
-        self.value_types[self.selected]
+    /// The path of the selected line, `None` when there is nothing to select.
+    pub fn selected_path(&self) -> Option<&str> {
+        self.paths.get(self.selected).map(String::as_str)
     }
 
-    fn update_paths(&mut self, flat_map: &[InspectorLine]) {
-        self.paths = flat_map
-            .iter()
-            .filter(|line| line.selectable())
-            .map(|line| line.path.clone())
-            .collect()
+    /// The type of the selected line, `None` when there is nothing to select.
+    pub fn selected_value_type(&self) -> Option<ValueType> {
+        self.value_types.get(self.selected).copied()
     }
 
-    fn update_value_types(&mut self, flat_map: &[InspectorLine]) {
-        self.value_types = flat_map
+    fn update_selectable_lines(&mut self, flat_map: &[InspectorLine]) {
+        self.selectable_lines = flat_map
             .iter()
-            .filter_map(InspectorLine::value_type)
+            .enumerate()
+            .filter(|(_, line)| line.selectable())
+            .map(|(y, _)| y)
             .collect();
+
+        // Keep the same field selected if it still exists, such as when the value changes shape
+        // or another component with the same field is shown. The scroll is left alone so the
+        // view doesn't jump around.
+        let previous_path = self.paths.get(self.selected);
+        if let Some(index) = previous_path.and_then(|previous| {
+            self.selectable_lines
+                .iter()
+                .position(|&y| flat_map[y].path == *previous)
+        }) {
+            self.selected = index;
+        }
     }
 
-    fn update_scroll(&mut self, flat_map: &[InspectorLine], height: u16) {
-        let selected_line_y = flat_map
-            .iter()
-            .enumerate()
-            .filter(|(_, l)| l.selectable())
-            .nth(self.selected)
-            .map(|(y, _)| y)
+    /// Work out the type of the selectable lines, only asking the registry about those in the
+    /// visible lines as it is slow to walk for values with thousands of lines. The selection is
+    /// always visible so its type is always exact.
+    ///
+    /// The types of every line are worked out again when the lines `changed`, otherwise just the
+    /// visible ones are.
+    fn update_value_types(
+        &mut self,
+        flat_map: &[InspectorLine],
+        types: Option<(&Registry, &str)>,
+        visible: Range<usize>,
+        changed: bool,
+    ) {
+        if changed {
+            self.value_types = self
+                .selectable_lines
+                .iter()
+                .map(|&y| flat_map[y].value_type().unwrap_or(ValueType::Null))
+                .collect();
+        }
+        let first = self
+            .selectable_lines
+            .partition_point(|&y| y < visible.start);
+        for (n, &y) in self.selectable_lines.iter().enumerate().skip(first) {
+            if y >= visible.end {
+                break;
+            }
+            self.value_types[n] = flat_map[y].exact_value_type(types);
+        }
+    }
+
+    fn update_scroll(&mut self, lines: usize, height: u16) {
+        let selected_line_y = self
+            .selectable_lines
+            .get(self.selected)
+            .copied()
             .unwrap_or_default();
 
-        let top_margin = 6;
-        let bottom_margin = height.saturating_sub(6) as usize;
+        let height = height as usize;
+        let margin = (height / 4).min(MAX_SCROLL_MARGIN);
+        // The furthest down the view the selection can be before scrolling.
+        let bottom = height.saturating_sub(margin + 1);
 
-        if selected_line_y < self.scroll + top_margin {
-            self.scroll = selected_line_y.saturating_sub(top_margin);
-        } else if selected_line_y > self.scroll + bottom_margin {
-            self.scroll = selected_line_y.saturating_sub(bottom_margin);
+        if selected_line_y < self.scroll + margin {
+            self.scroll = selected_line_y.saturating_sub(margin);
+        } else if selected_line_y > self.scroll + bottom {
+            self.scroll = selected_line_y - bottom;
         }
 
-        self.scroll = self
-            .scroll
-            .min(flat_map.len().saturating_sub(height as usize));
+        self.scroll = self.scroll.min(lines.saturating_sub(height));
     }
 
-    fn update_selected(&mut self, flat_map: &[InspectorLine]) {
-        self.selected = self.selected.min(flat_map.len().saturating_sub(1));
+    fn update_selected(&mut self) {
+        self.selected = self
+            .selected
+            .min(self.selectable_lines.len().saturating_sub(1));
     }
 }
 
 #[derive(Debug)]
-struct InspectorLine<'a> {
-    name: Option<&'a str>,
+struct InspectorLine {
+    name: Option<LineName>,
     path: String,
     indent_level: u16,
-    kind: InspectorLineKind<'a>,
+    kind: InspectorLineKind,
 }
 
 #[derive(Debug)]
-enum InspectorLineKind<'a> {
-    ObjectStart,
-    ArrayStart,
-    Item { value: PrimitiveValue<'a> },
+enum LineName {
+    /// The name of a field of an object.
+    Field(String),
+    /// The key of an entry of a map serialized as an array of `[key, value]` pairs.
+    Key(String),
+}
+
+#[derive(Debug)]
+enum InspectorLineKind {
+    ObjectStart {
+        swatch: Option<Color>,
+        /// The time the object holds if it is a `Duration` or `Timer`, shown in place of its
+        /// field count.
+        time: Option<String>,
+        /// If the object looks like an enum variant holding data.
+        variant: bool,
+        /// The number of fields in the object.
+        len: usize,
+    },
+    ArrayStart {
+        /// The number of items in the array.
+        len: usize,
+        /// The first bytes in hex if the array looks like bytes, which is collapsed by default.
+        bytes: Option<String>,
+    },
+    Item {
+        value: PrimitiveValue,
+    },
     ArrayEnd,
     ObjectEnd,
 }
 
 /// A copy of [`Value`] with just the types that are primitive in Rust.
 #[derive(Debug)]
-enum PrimitiveValue<'a> {
+enum PrimitiveValue {
     Null,
     Bool(bool),
     Number(Number),
-    String(&'a str),
+    String(String),
 }
 
-fn flatten_value(value: &Value) -> Vec<InspectorLine> {
+/// Flatten the value into lines, leaving out everything within the collapsed objects and arrays
+/// but the line they start on.
+///
+/// Every line is flattened, not just the visible ones, since the selection, filter and paths are
+/// worked out over all of them. Only the slow registry lookups are limited to the visible lines,
+/// and the lines are kept in [`InspectorState`] so they are only flattened again once the value
+/// changes.
+fn flatten_value(
+    value: &Value,
+    types: Option<(&Registry, &str)>,
+    collapsed: Option<&HashSet<String>>,
+    sort_fields: bool,
+    theme: &Theme,
+) -> Vec<InspectorLine> {
     let mut flat_map = Vec::new();
-    flatten_value_inner(None, value, &mut flat_map, String::new(), 0);
+    let context = FlattenContext {
+        types,
+        collapsed,
+        sort_fields,
+        theme,
+    };
+    flatten_value_inner(None, value, &mut flat_map, String::new(), 0, &context);
     flat_map
 }
 
-fn flatten_value_inner<'a>(
-    name: Option<&'a str>,
-    value: &'a Value,
-    out: &mut Vec<InspectorLine<'a>>,
+/// What stays the same while flattening a value.
+struct FlattenContext<'r> {
+    types: Option<(&'r Registry, &'r str)>,
+    collapsed: Option<&'r HashSet<String>>,
+    /// Whether the fields of objects are sorted by name.
+    sort_fields: bool,
+    /// For writing times and byte previews with just ASCII when it is.
+    theme: &'r Theme,
+}
+
+impl FlattenContext<'_> {
+    /// Whether the object or array at `path` is collapsed, where being in the collapsed set
+    /// flips whether it is `collapsed_by_default`.
+    fn is_collapsed(&self, path: &str, collapsed_by_default: bool) -> bool {
+        collapsed_by_default != self.collapsed.is_some_and(|c| c.contains(path))
+    }
+
+    /// The first bytes of the array at `path` in hex if it looks like bytes, a long array of
+    /// integers that fit in a `u8` which the registry, if there is one, agrees are `u8`s.
+    fn bytes_preview(&self, array: &[Value], path: &str) -> Option<String> {
+        let bytes: Vec<_> = array
+            .iter()
+            .map(|item| item.as_u64().filter(|n| *n <= u8::MAX as u64))
+            .collect::<Option<_>>()?;
+        if bytes.len() < MIN_BYTES_LEN {
+            return None;
+        }
+        if let Some((registry, type_path)) = self.types {
+            let item_type = registry.field_type(type_path, &format!("{path}[0]"));
+            if item_type.is_some_and(|item_type| item_type != "u8") {
+                return None;
+            }
+        }
+        let mut preview: String = bytes
+            .iter()
+            .take(BYTES_PREVIEW_LEN)
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        if bytes.len() > BYTES_PREVIEW_LEN {
+            preview.push_str(self.theme.ellipsis());
+        }
+        Some(preview)
+    }
+}
+
+fn flatten_value_inner(
+    name: Option<LineName>,
+    value: &Value,
+    out: &mut Vec<InspectorLine>,
     base_path: String,
     indent_level: u16,
+    context: &FlattenContext,
 ) {
     match value {
         Value::Null => out.push(InspectorLine {
@@ -265,25 +820,53 @@ fn flatten_value_inner<'a>(
             path: base_path,
             indent_level,
             kind: InspectorLineKind::Item {
-                value: PrimitiveValue::String(s),
+                value: PrimitiveValue::String(s.clone()),
             },
         }),
 
         Value::Array(array) => {
+            let bytes = context.bytes_preview(array, &base_path);
+            let collapsed = context.is_collapsed(&base_path, bytes.is_some());
             out.push(InspectorLine {
                 name,
                 path: base_path.to_owned(),
                 indent_level,
-                kind: InspectorLineKind::ArrayStart,
+                kind: InspectorLineKind::ArrayStart {
+                    len: array.len(),
+                    bytes,
+                },
             });
+            if collapsed {
+                return;
+            }
+            // Maps with keys that can't be object keys are serialized as `[key, value]` pairs,
+            // which are shown as entries with the path leading to the value.
+            let is_map = match context
+                .types
+                .and_then(|(registry, type_path)| registry.field_kind(type_path, &base_path))
+            {
+                Some(kind) => kind == "Map",
+                None => looks_like_map(array),
+            };
             for (n, value) in array.iter().enumerate() {
-                flatten_value_inner(
-                    None,
-                    value,
-                    out,
-                    format!("{base_path}[{n}]"),
-                    indent_level + 1,
-                );
+                match value.as_array().map(Vec::as_slice) {
+                    Some([key, value]) if is_map => flatten_value_inner(
+                        Some(LineName::Key(map_key(key))),
+                        value,
+                        out,
+                        format!("{base_path}[{n}][1]"),
+                        indent_level + 1,
+                        context,
+                    ),
+                    _ => flatten_value_inner(
+                        None,
+                        value,
+                        out,
+                        format!("{base_path}[{n}]"),
+                        indent_level + 1,
+                        context,
+                    ),
+                }
             }
             out.push(InspectorLine {
                 name: None,
@@ -298,15 +881,29 @@ fn flatten_value_inner<'a>(
                 name,
                 path: base_path.to_owned(),
                 indent_level,
-                kind: InspectorLineKind::ObjectStart,
+                kind: InspectorLineKind::ObjectStart {
+                    swatch: color_swatch(map),
+                    time: time_summary(map, context.theme),
+                    variant: looks_like_variant(map),
+                    len: map.len(),
+                },
             });
-            for (name, value) in map {
+            if context.is_collapsed(&base_path, false) {
+                return;
+            }
+            // The paths are by name, so they lead to the same fields either way.
+            let mut fields: Vec<_> = map.iter().collect();
+            if context.sort_fields {
+                fields.sort_by_key(|(name, _)| *name);
+            }
+            for (name, value) in fields {
                 flatten_value_inner(
-                    Some(name),
+                    Some(LineName::Field(name.clone())),
                     value,
                     out,
                     format!("{base_path}.{name}"),
                     indent_level + 1,
+                    context,
                 );
             }
             out.push(InspectorLine {
@@ -319,13 +916,103 @@ fn flatten_value_inner<'a>(
     }
 }
 
-impl InspectorLine<'_> {
+/// Keep the lines with a name or path containing the query, ignoring case, along with the
+/// objects and arrays they are in and everything within them. The root is always kept so there
+/// is something to select.
+fn filter_lines(lines: Vec<InspectorLine>, query: &str) -> Vec<InspectorLine> {
+    if query.is_empty() {
+        return lines;
+    }
+    let query = query.to_lowercase();
+    let matched: HashSet<&str> = lines
+        .iter()
+        .filter(|line| line.matches(&query))
+        .map(|line| line.path.as_str())
+        .collect();
+    // Everything the matches are in, including the root.
+    let around_matches: HashSet<&str> = matched.iter().flat_map(|path| ancestors(path)).collect();
+    let keep: Vec<bool> = lines
+        .iter()
+        .map(|line| {
+            around_matches.contains(line.path.as_str())
+                || ancestors(&line.path).any(|path| matched.contains(path))
+        })
+        .collect();
+    lines
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(line, keep)| keep.then_some(line))
+        .collect()
+}
+
+/// Hide everything within the collapsed objects and arrays, leaving just the line they start on.
+fn collapse_lines(
+    lines: Vec<InspectorLine>,
+    collapsed: Option<&HashSet<String>>,
+) -> Vec<InspectorLine> {
+    // Being in the set flips whether byte arrays are collapsed.
+    let by_default: HashSet<String> = lines
+        .iter()
+        .filter(|line| line.collapsed_by_default())
+        .map(|line| line.path.clone())
+        .collect();
+    let collapsed: HashSet<String> = match collapsed {
+        Some(collapsed) => collapsed
+            .symmetric_difference(&by_default)
+            .cloned()
+            .collect(),
+        None => by_default,
+    };
+    if collapsed.is_empty() {
+        return lines;
+    }
+    lines
+        .into_iter()
+        .filter(|line| {
+            let is_end = matches!(
+                line.kind,
+                InspectorLineKind::ObjectEnd | InspectorLineKind::ArrayEnd
+            );
+            let hidden_end = is_end && collapsed.contains(&line.path);
+            let hidden_within =
+                ancestors(&line.path).any(|path| path != line.path && collapsed.contains(path));
+            !hidden_end && !hidden_within
+        })
+        .collect()
+}
+
+/// The paths of the objects and arrays `path` is within, from the root, followed by `path` itself.
+fn ancestors(path: &str) -> impl Iterator<Item = &str> {
+    path.match_indices(['.', '['])
+        .map(|(n, _)| &path[..n])
+        .chain([path])
+}
+
+impl InspectorLine {
+    /// Whether this line starts a byte array, which is collapsed unless in the collapsed set.
+    fn collapsed_by_default(&self) -> bool {
+        matches!(
+            self.kind,
+            InspectorLineKind::ArrayStart { bytes: Some(_), .. }
+        )
+    }
+
+    /// If the name or path of this line contains the given lowercase query.
+    fn matches(&self, query: &str) -> bool {
+        let name_matches = match &self.name {
+            Some(LineName::Field(name)) => name.to_lowercase().contains(query),
+            Some(LineName::Key(key)) => key.to_lowercase().contains(query),
+            None => false,
+        };
+        name_matches || self.path.to_lowercase().contains(query)
+    }
+
     /// The [`ValueType`] of this line to determine which keybinds to show.
     fn value_type(&self) -> Option<ValueType> {
         match &self.kind {
             InspectorLineKind::Item { value } => Some(ValueType::from(value)),
-            InspectorLineKind::ArrayStart => Some(ValueType::Array),
-            InspectorLineKind::ObjectStart => Some(ValueType::Object),
+            InspectorLineKind::ArrayStart { .. } => Some(ValueType::Array),
+            InspectorLineKind::ObjectStart { .. } => Some(ValueType::Object),
             _ => None,
         }
     }
@@ -334,6 +1021,168 @@ impl InspectorLine<'_> {
     fn selectable(&self) -> bool {
         self.value_type().is_some()
     }
+
+    /// The [`ValueType`] of this line, asking the registry about its field if there is one to
+    /// tell apart the types that are serialized the same.
+    fn exact_value_type(&self, types: Option<(&Registry, &str)>) -> ValueType {
+        let value_type = self.value_type().unwrap_or(ValueType::Null);
+        // The registry knows the shape of the type, otherwise enums are guessed from
+        // their serialized form.
+        let kind =
+            types.and_then(|(registry, type_path)| registry.field_kind(type_path, &self.path));
+        match (kind, &self.kind, value_type) {
+            (Some("Enum"), _, _) => ValueType::Enum,
+            (Some("Tuple" | "TupleStruct"), _, ValueType::Array) => ValueType::Tuple,
+            (_, _, ValueType::Number)
+                if types.is_some_and(|(registry, type_path)| {
+                    registry.is_entity(type_path, &self.path)
+                }) =>
+            {
+                ValueType::Entity
+            }
+            (None, InspectorLineKind::ObjectStart { variant: true, .. }, _) => ValueType::Enum,
+            (
+                _,
+                InspectorLineKind::Item {
+                    value: PrimitiveValue::String(s),
+                },
+                _,
+            ) if is_stringified_integer(s)
+                && types.is_some_and(|(registry, type_path)| {
+                    registry
+                        .field_type(type_path, &self.path)
+                        .is_some_and(is_integer_type)
+                }) =>
+            {
+                ValueType::Number
+            }
+            _ => value_type,
+        }
+    }
+}
+
+/// A part of a path to a field as used by the inspector.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PathSegment<'a> {
+    Field(&'a str),
+    Index(usize),
+}
+
+/// Split an inspector path such as `.points[2].x` into its fields and indices.
+pub fn path_segments(path: &str) -> Vec<PathSegment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = path;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').unwrap_or(after.len());
+            if let Ok(n) = after[..end].parse() {
+                segments.push(PathSegment::Index(n));
+            }
+            rest = after.get(end + 1..).unwrap_or_default();
+        } else {
+            let after = rest.strip_prefix('.').unwrap_or(rest);
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            segments.push(PathSegment::Field(&after[..end]));
+            rest = &after[end..];
+        }
+    }
+    segments
+}
+
+/// The value at the given inspector path within `value`.
+pub fn value_at_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path_segments(path)
+        .into_iter()
+        .try_fold(value, |value, segment| match segment {
+            PathSegment::Field(name) => value.get(name),
+            PathSegment::Index(n) => value.get(n),
+        })
+}
+
+/// A mutable reference to the value at the given inspector path within `value`.
+pub fn value_at_path_mut<'a>(value: &'a mut Value, path: &str) -> Option<&'a mut Value> {
+    path_segments(path)
+        .into_iter()
+        .try_fold(value, |value, segment| match segment {
+            PathSegment::Field(name) => value.get_mut(name),
+            PathSegment::Index(n) => value.get_mut(n),
+        })
+}
+
+/// The text of a number, exact for any integer serde_json can hold.
+///
+/// Integers too large for 64 bits, such as big `u128`s, are parsed as floats and have lost
+/// precision, so on integer fields they are written out in full but marked as approximate.
+fn number_text(n: &Number, field_type: Option<&str>, theme: &Theme) -> String {
+    let is_integer = field_type.is_some_and(is_integer_type);
+    match n.as_f64() {
+        Some(f) if is_integer && n.is_f64() => format!("{}{f:.0}", theme.symbol("≈", "~")),
+        _ => n.to_string(),
+    }
+}
+
+/// If `ty` is one of Rust's integer types.
+fn is_integer_type(ty: &str) -> bool {
+    matches!(
+        ty,
+        "u8" | "u16"
+            | "u32"
+            | "u64"
+            | "u128"
+            | "usize"
+            | "i8"
+            | "i16"
+            | "i32"
+            | "i64"
+            | "i128"
+            | "isize"
+    )
+}
+
+/// If the string holds an integer too large for a JSON number, as some serializers write `u128`s
+/// and `i128`s. They are shown and treated as the numbers they are on integer fields.
+fn is_stringified_integer(s: &str) -> bool {
+    s.parse::<u128>().is_ok() || s.parse::<i128>().is_ok()
+}
+
+/// The marker for a float field that is null, as serde_json serializes NaN and both infinities
+/// alike, leaving which of them it was unknown.
+fn null_float(theme: &Theme) -> &'static str {
+    theme.symbol("NaN/±∞", "NaN/inf")
+}
+
+/// The marker for a special float value that was serialized as a string, such as `"inf"`.
+fn special_float(s: &str, theme: &Theme) -> Option<&'static str> {
+    match s.to_ascii_lowercase().as_str() {
+        "nan" | "-nan" => Some("NaN"),
+        "inf" | "+inf" | "infinity" | "+infinity" => Some(theme.symbol("∞", "inf")),
+        "-inf" | "-infinity" => Some(theme.symbol("-∞", "-inf")),
+        _ => None,
+    }
+}
+
+/// Shorten the span to fit in `width` cells, ending it with an ellipsis if anything was cut.
+fn truncate<'a>(span: Span<'a>, width: u16, theme: &Theme) -> Span<'a> {
+    let width = width as usize;
+    if span.width() <= width {
+        return span;
+    }
+    let ellipsis = theme.ellipsis();
+    let ellipsis_width = Span::raw(ellipsis).width();
+    let mut content = String::new();
+    let mut used = 0;
+    for ch in span.content.chars() {
+        let ch_width = Span::raw(ch.to_string()).width();
+        if used + ch_width + ellipsis_width > width {
+            break;
+        }
+        used += ch_width;
+        content.push(ch);
+    }
+    if width >= ellipsis_width {
+        content.push_str(ellipsis);
+    }
+    Span::styled(content, span.style)
 }
 
 /// Take the given `width` off the front of the given `rect` and return a new rect containing that
@@ -349,14 +1198,182 @@ fn split_rect(rect: &mut Rect, width: u16) -> Rect {
     new_rect
 }
 
-fn render_char(rect: Rect, buf: &mut Buffer, ch: char, selected: bool) {
+fn render_char(rect: Rect, buf: &mut Buffer, ch: char, selected: bool, theme: &Theme) {
     buf[rect.as_position()].set_char(ch);
     if selected {
-        buf[rect.as_position()].set_style(Style::default().fg(PRIMARY_COLOR).bold());
+        buf[rect.as_position()].set_style(Style::default().fg(theme.selection).bold());
+    }
+}
+
+/// Render the opening brace of an object or array followed by an ellipsis and the closing brace
+/// if it is collapsed, and then the count of what is in it. Returns the rest of the line.
+fn render_start(
+    mut rect: Rect,
+    buf: &mut Buffer,
+    open: char,
+    selected: bool,
+    collapsed: bool,
+    count: Option<String>,
+    theme: &Theme,
+) -> Rect {
+    render_char(rect, buf, open, selected, theme);
+    let _open_rect = split_rect(&mut rect, 1);
+    if collapsed {
+        let close = if open == '{' { '}' } else { ']' };
+        let span = Span::styled(format!("{}{close}", theme.ellipsis()), theme.dim());
+        let close_rect = split_rect(&mut rect, span.width() as u16);
+        span.render(close_rect, buf);
+    }
+    if let Some(count) = count {
+        let span = Span::styled(format!(" {count}"), theme.dim());
+        let count_rect = split_rect(&mut rect, span.width() as u16);
+        span.render(count_rect, buf);
+    }
+    rect
+}
+
+/// If the object is shaped like a serialized enum variant with data, a single key naming the
+/// variant in `UpperCamelCase`.
+fn looks_like_variant(map: &Map<String, Value>) -> bool {
+    map.len() == 1
+        && map
+            .keys()
+            .next()
+            .and_then(|key| key.chars().next())
+            .is_some_and(char::is_uppercase)
+}
+
+/// If every item of the array is a `[key, value]` pair with a primitive key, as maps with keys
+/// that can't be object keys are serialized. Pairs of numbers are more likely to be vectors such
+/// as `Vec2` so they aren't counted.
+fn looks_like_map(array: &[Value]) -> bool {
+    !array.is_empty()
+        && array
+            .iter()
+            .all(|item| match item.as_array().map(Vec::as_slice) {
+                Some([key, value]) => {
+                    matches!(key, Value::String(_) | Value::Number(_) | Value::Bool(_))
+                        && !(key.is_number() && value.is_number())
+                }
+                _ => false,
+            })
+}
+
+/// The text shown for the key of a map entry.
+fn map_key(key: &Value) -> String {
+    match key {
+        Value::String(s) => s.clone(),
+        key => key.to_string(),
+    }
+}
+
+/// The duration an object holds if it is shaped like a serialized `Duration`, with just whole
+/// seconds and the nanoseconds on top.
+fn duration(map: &Map<String, Value>) -> Option<Duration> {
+    if map.len() != 2 {
+        return None;
+    }
+    let secs = map.get("secs")?.as_u64()?;
+    let nanos = map.get("nanos")?.as_u64().filter(|n| *n < 1_000_000_000)?;
+    Some(Duration::new(secs, nanos as u32))
+}
+
+/// A readable summary of an object shaped like a `Duration`, or like a `Stopwatch` or `Timer`
+/// with the time elapsed out of its duration.
+fn time_summary(map: &Map<String, Value>, theme: &Theme) -> Option<String> {
+    let field = |value: Option<&Value>| duration(value?.as_object()?);
+    let elapsed = || {
+        let stopwatch = map.get("stopwatch").unwrap_or(&Value::Null);
+        field(map.get("elapsed").or_else(|| stopwatch.get("elapsed")))
+    };
+    if let Some(duration) = duration(map) {
+        Some(format_duration(duration, theme))
+    } else if let Some(total) = field(map.get("duration")) {
+        let elapsed = elapsed()?;
+        Some(format!(
+            "{} / {}",
+            format_duration(elapsed, theme),
+            format_duration(total, theme)
+        ))
+    } else {
+        elapsed().map(|elapsed| format_duration(elapsed, theme))
+    }
+}
+
+/// A duration in the largest unit it has a whole one of, like `1.250s` or `16.667ms`.
+fn format_duration(duration: Duration, theme: &Theme) -> String {
+    let secs = duration.as_secs_f64();
+    if duration.as_secs() >= 60 {
+        format!("{}m {:06.3}s", duration.as_secs() / 60, secs % 60.0)
+    } else if secs >= 1.0 {
+        format!("{secs:.3}s")
+    } else if secs >= 1e-3 {
+        format!("{:.3}ms", secs * 1e3)
+    } else if secs >= 1e-6 {
+        format!("{:.3}{}", secs * 1e6, theme.symbol("µs", "us"))
+    } else {
+        format!("{}ns", duration.as_nanos())
     }
 }
 
-impl From<&PrimitiveValue<'_>> for ValueType {
+/// The color an object represents if its fields match the shape of one of Bevy's color types.
+///
+/// RGB objects are assumed to be in sRGB space since linear colors share the same field names.
+fn color_swatch(map: &Map<String, Value>) -> Option<Color> {
+    let field = |name: &str| map.get(name).and_then(Value::as_f64).map(|v| v as f32);
+    let channel = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    let rgb = if let (Some(r), Some(g), Some(b)) = (field("red"), field("green"), field("blue")) {
+        (r, g, b)
+    } else if let (Some(h), Some(s), Some(l)) =
+        (field("hue"), field("saturation"), field("lightness"))
+    {
+        hsl_to_rgb(h, s, l)
+    } else if let (Some(h), Some(s), Some(v)) = (field("hue"), field("saturation"), field("value"))
+    {
+        let (s, l) = hsv_to_hsl(s, v);
+        hsl_to_rgb(h, s, l)
+    } else {
+        return None;
+    };
+
+    // Only colors have exactly these fields, plus an optional alpha.
+    let expected_fields = if map.contains_key("alpha") { 4 } else { 3 };
+    if map.len() != expected_fields {
+        return None;
+    }
+
+    Some(Color::Rgb(channel(rgb.0), channel(rgb.1), channel(rgb.2)))
+}
+
+/// Convert the saturation and value of an HSV color to the saturation and lightness of HSL.
+fn hsv_to_hsl(saturation: f32, value: f32) -> (f32, f32) {
+    let lightness = value * (1.0 - saturation / 2.0);
+    let saturation = if lightness <= 0.0 || lightness >= 1.0 {
+        0.0
+    } else {
+        (value - lightness) / lightness.min(1.0 - lightness)
+    };
+    (saturation, lightness)
+}
+
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> (f32, f32, f32) {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u8 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    (r + m, g + m, b + m)
+}
+
+impl From<&PrimitiveValue> for ValueType {
     fn from(value: &PrimitiveValue) -> Self {
         match value {
             PrimitiveValue::Null => Self::Null,
@@ -366,3 +1383,591 @@ impl From<&PrimitiveValue<'_>> for ValueType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::Instant;
+
+    /// The text of each row of the inspector rendered 40 cells wide.
+    fn rendered(inspector: Inspector) -> Vec<String> {
+        rendered_with(inspector, &mut InspectorState::default())
+    }
+
+    fn rendered_with(inspector: Inspector, state: &mut InspectorState) -> Vec<String> {
+        let area = Rect::new(0, 0, 40, 10);
+        let mut buf = Buffer::empty(area);
+        inspector.render(area, &mut buf, state);
+        (0..area.height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    /// A registry with a `Speed` component holding an `f32`.
+    fn float_registry() -> Registry {
+        let schemas = json!({
+            "game::Speed": {
+                "typePath": "game::Speed",
+                "shortPath": "Speed",
+                "kind": "Struct",
+                "reflectTypes": ["Component"],
+                "properties": { "value": { "type": { "$ref": "#/$defs/f32" } } },
+            },
+            "f32": { "typePath": "f32", "shortPath": "f32", "kind": "Value" },
+        });
+        Registry::new(serde_json::from_value(schemas).unwrap())
+    }
+
+    /// The paths of the lines of the flattened value.
+    fn paths(value: &Value, types: Option<(&Registry, &str)>) -> Vec<String> {
+        flatten_value(value, types, None, false, &Theme::default())
+            .into_iter()
+            .map(|line| line.path)
+            .collect()
+    }
+
+    /// A registry with a `Scores` component holding a map with integer keys and a list of
+    /// string and float pairs, which are serialized the same way.
+    fn scores_registry() -> Registry {
+        let schemas = json!({
+            "game::Scores": {
+                "typePath": "game::Scores",
+                "shortPath": "Scores",
+                "kind": "Struct",
+                "reflectTypes": ["Component"],
+                "properties": {
+                    "by_id": { "type": { "$ref": "#/$defs/HashMap<u32, f32>" } },
+                    "by_name": { "type": { "$ref": "#/$defs/Vec<(String, f32)>" } },
+                },
+            },
+            "HashMap<u32, f32>": {
+                "typePath": "HashMap<u32, f32>",
+                "shortPath": "HashMap<u32, f32>",
+                "kind": "Map",
+                "keyType": { "type": { "$ref": "#/$defs/u32" } },
+                "valueType": { "type": { "$ref": "#/$defs/f32" } },
+            },
+            "Vec<(String, f32)>": {
+                "typePath": "Vec<(String, f32)>",
+                "shortPath": "Vec<(String, f32)>",
+                "kind": "List",
+                "items": { "type": { "$ref": "#/$defs/(String, f32)" } },
+            },
+            "(String, f32)": {
+                "typePath": "(String, f32)",
+                "shortPath": "(String, f32)",
+                "kind": "Tuple",
+                "prefixItems": [
+                    { "type": { "$ref": "#/$defs/String" } },
+                    { "type": { "$ref": "#/$defs/f32" } },
+                ],
+            },
+        });
+        Registry::new(serde_json::from_value(schemas).unwrap())
+    }
+
+    #[test]
+    fn pair_arrays_look_like_maps_without_the_registry() {
+        let value = json!({ "by_name": [["alice", 1.5], ["bob", 2.0]] });
+        assert_eq!(
+            paths(&value, None),
+            [
+                "",
+                ".by_name",
+                ".by_name[0][1]",
+                ".by_name[1][1]",
+                ".by_name",
+                ""
+            ]
+        );
+        // Pairs of numbers are more likely vectors.
+        assert!(!looks_like_map(&[json!([1, 2]), json!([3, 4])]));
+        assert!(!looks_like_map(&[]));
+        assert!(!looks_like_map(&[json!(["a", 1]), json!("b")]));
+    }
+
+    #[test]
+    fn registry_decides_what_is_a_map() {
+        let registry = scores_registry();
+        let value = json!({
+            "by_id": [[1, 1.5], [2, 2.0]],
+            "by_name": [["alice", 1.5], ["bob", 2.0]],
+        });
+        let lines = flatten_value(
+            &value,
+            Some((&registry, "game::Scores")),
+            None,
+            false,
+            &Theme::default(),
+        );
+        let keys: Vec<_> = lines
+            .iter()
+            .filter_map(|line| match &line.name {
+                Some(LineName::Key(key)) => Some(key.as_str()),
+                _ => None,
+            })
+            .collect();
+        // Pairs of numbers are shown as entries when the registry says they are a map, while a
+        // list of pairs is still a list.
+        assert_eq!(keys, ["1", "2"]);
+        assert_eq!(
+            paths(&value, Some((&registry, "game::Scores"))),
+            [
+                "",
+                ".by_id",
+                ".by_id[0][1]",
+                ".by_id[1][1]",
+                ".by_id",
+                ".by_name",
+                ".by_name[0]",
+                ".by_name[0][0]",
+                ".by_name[0][1]",
+                ".by_name[0]",
+                ".by_name[1]",
+                ".by_name[1][0]",
+                ".by_name[1][1]",
+                ".by_name[1]",
+                ".by_name",
+                "",
+            ]
+        );
+    }
+
+    #[test]
+    fn selecting_within_an_empty_object_does_nothing() {
+        let empty = json!({});
+        let mut state = InspectorState::default();
+        rendered_with(Inspector::new(&empty, true), &mut state);
+        state.select_next();
+        state.select_previous();
+        assert_eq!(state.selected_path(), None);
+        assert_eq!(state.selected_value_type(), None);
+
+        // Nothing is left selected from a value shown before.
+        let value = json!({ "a": 1, "b": true });
+        rendered_with(Inspector::new(&value, true), &mut state);
+        state.select_next();
+        rendered_with(Inspector::new(&value, true), &mut state);
+        assert_eq!(state.selected_path(), Some(".a"));
+        rendered_with(Inspector::new(&empty, true), &mut state);
+        state.select_next();
+        state.select_previous();
+        assert_eq!(state.selected_path(), None);
+        assert_eq!(state.selected_value_type(), None);
+    }
+
+    #[test]
+    fn selection_wraps_or_stops_at_the_ends() {
+        let value = json!({ "a": 1, "b": 2 });
+        // The object itself and its two fields can be selected.
+        let mut state = InspectorState::default();
+        rendered_with(Inspector::new(&value, true).wrap(true), &mut state);
+        state.select_previous();
+        assert_eq!(state.selected_path(), Some(".b"));
+        state.select_next();
+        assert_eq!(state.selected_path(), Some(""));
+
+        let mut state = InspectorState::default();
+        rendered_with(Inspector::new(&value, true).wrap(false), &mut state);
+        state.select_previous();
+        assert_eq!(state.selected_path(), Some(""));
+        state.select_next();
+        state.select_next();
+        state.select_next();
+        assert_eq!(state.selected_path(), Some(".b"));
+    }
+
+    #[test]
+    fn selection_stops_at_the_ends_by_default() {
+        let value = json!({ "a": 1, "b": 2 });
+        let mut state = InspectorState::default();
+        rendered_with(Inspector::new(&value, true), &mut state);
+        state.select_previous();
+        assert_eq!(state.selected_path(), Some(""));
+        state.select_last();
+        state.select_next();
+        assert_eq!(state.selected_path(), Some(".b"));
+    }
+
+    #[test]
+    fn ascii_theme_renders_just_ascii() {
+        let registry = float_registry();
+        let value = json!({
+            "value": null,
+            "bytes": (0..40).collect::<Vec<_>>(),
+            "elapsed": { "secs": 0, "nanos": 1500 },
+            "visible": true,
+            "limit": "-inf",
+            "name": "a string far too long to fit in the forty cells the inspector is given",
+        });
+        let theme = Theme {
+            ascii: true,
+            ..Theme::default()
+        };
+        let lines = rendered(
+            Inspector::new(&value, true)
+                .types(&registry, "game::Speed")
+                .theme(theme),
+        );
+        for line in &lines {
+            assert!(line.is_ascii(), "{line:?}");
+        }
+        // The same value uses symbols outside of ASCII otherwise.
+        let lines = rendered(Inspector::new(&value, true).types(&registry, "game::Speed"));
+        assert!(lines.iter().any(|line| !line.is_ascii()), "{lines:?}");
+    }
+
+    #[test]
+    fn stringified_special_floats_are_marked() {
+        let value = json!({ "a": "inf", "b": "-Infinity", "c": "NaN", "d": "infinite" });
+        let lines = rendered(Inspector::new(&value, true));
+        assert!(lines.iter().any(|line| line.ends_with("a: ∞")), "{lines:?}");
+        assert!(
+            lines.iter().any(|line| line.ends_with("b: -∞")),
+            "{lines:?}"
+        );
+        assert!(
+            lines.iter().any(|line| line.ends_with("c: NaN")),
+            "{lines:?}"
+        );
+        assert!(
+            lines.iter().any(|line| line.ends_with("d: infinite")),
+            "{lines:?}"
+        );
+
+        let theme = Theme {
+            ascii: true,
+            ..Theme::default()
+        };
+        let lines = rendered(Inspector::new(&value, true).theme(theme));
+        assert!(
+            lines.iter().any(|line| line.ends_with("a: inf")),
+            "{lines:?}"
+        );
+        assert!(
+            lines.iter().any(|line| line.ends_with("b: -inf")),
+            "{lines:?}"
+        );
+    }
+
+    #[test]
+    fn null_floats_are_marked_ambiguous() {
+        let registry = float_registry();
+        let value = json!({ "value": null });
+        let lines = rendered(Inspector::new(&value, true).types(&registry, "game::Speed"));
+        assert!(
+            lines.iter().any(|line| line.contains("value: NaN/±∞")),
+            "{lines:?}"
+        );
+        // Without knowing the field is a float, null is just an empty value.
+        let lines = rendered(Inspector::new(&value, true));
+        assert!(
+            lines.iter().any(|line| line.ends_with("value: None")),
+            "{lines:?}"
+        );
+    }
+
+    #[test]
+    fn full_width_integers_are_exact() {
+        let theme = Theme::default();
+        let max = Number::from(u64::MAX);
+        assert_eq!(number_text(&max, Some("u64"), &theme), u64::MAX.to_string());
+        assert_eq!(number_text(&max, None, &theme), "18446744073709551615");
+        let min = Number::from(i64::MIN);
+        assert_eq!(
+            number_text(&min, Some("i64"), &theme),
+            "-9223372036854775808"
+        );
+        assert_eq!(number_text(&Number::from(-1), Some("i64"), &theme), "-1");
+    }
+
+    #[test]
+    fn integers_past_64_bits_are_marked_approximate() {
+        let theme = Theme::default();
+        let big: Number = serde_json::from_str("340282366920938463463374607431768211455").unwrap();
+        assert_eq!(
+            number_text(&big, Some("u128"), &theme),
+            "≈340282366920938463463374607431768211456"
+        );
+        // Floats are left as they are.
+        assert_eq!(number_text(&big, Some("f64"), &theme), big.to_string());
+    }
+
+    #[test]
+    fn detects_stringified_integers() {
+        assert!(is_stringified_integer(
+            "340282366920938463463374607431768211455"
+        ));
+        assert!(is_stringified_integer(
+            "-170141183460469231731687303715884105728"
+        ));
+        assert!(is_stringified_integer("0"));
+        assert!(!is_stringified_integer("1.5"));
+        assert!(!is_stringified_integer("inf"));
+        assert!(!is_stringified_integer(""));
+    }
+
+    /// Times flattening and rendering a component with 10k items, run with
+    /// `cargo test --release bench_ -- --ignored --nocapture`.
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_render_10k_items() {
+        const RUNS: u32 = 50;
+        let value = Value::Array((0..10_000).map(|n| json!({ "x": n, "y": n })).collect());
+
+        let start = Instant::now();
+        for _ in 0..RUNS {
+            std::hint::black_box(flatten_value(&value, None, None, false, &Theme::default()));
+        }
+        println!("flatten: {:?}", start.elapsed() / RUNS);
+
+        let area = Rect::new(0, 0, 80, 40);
+        let mut buf = Buffer::empty(area);
+        let mut state = InspectorState::default();
+        let start = Instant::now();
+        for _ in 0..RUNS {
+            Inspector::new(&value, true).render(area, &mut buf, &mut state);
+        }
+        println!("render: {:?}", start.elapsed() / RUNS);
+
+        let collapsed: HashSet<_> = (0..10_000).map(|n| format!("[{n}]")).collect();
+        let start = Instant::now();
+        for _ in 0..RUNS {
+            Inspector::new(&value, true)
+                .collapsed(&collapsed)
+                .render(area, &mut buf, &mut state);
+        }
+        println!(
+            "render with every item collapsed: {:?}",
+            start.elapsed() / RUNS
+        );
+
+        // Alternating between two values misses any cached lines every frame.
+        let mut changed = value.clone();
+        changed[0]["x"] = json!(-1);
+        let mut state = InspectorState::default();
+        let start = Instant::now();
+        for n in 0..RUNS {
+            let value = if n % 2 == 0 { &value } else { &changed };
+            Inspector::new(value, true).render(area, &mut buf, &mut state);
+        }
+        println!("render a changed value: {:?}", start.elapsed() / RUNS);
+
+        let mut state = InspectorState {
+            filter: TextInput::with_value("x"),
+            ..Default::default()
+        };
+        let start = Instant::now();
+        for _ in 0..RUNS {
+            Inspector::new(&value, true).render(area, &mut buf, &mut state);
+        }
+        println!("render filtered: {:?}", start.elapsed() / RUNS);
+    }
+
+    /// Moves the selection down through every one of `lines` lines and back up again, checking
+    /// after each move that the selected line is in view and the view moved by at most a line.
+    fn scroll_through(lines: usize, height: u16) -> InspectorState {
+        let mut state = InspectorState {
+            selectable_lines: (0..lines).collect(),
+            ..Default::default()
+        };
+        let order = (0..lines).chain((0..lines).rev());
+        for selected in order {
+            let scroll = state.scroll;
+            state.selected = selected;
+            state.update_scroll(lines, height);
+            assert!(
+                (state.scroll..state.scroll + height as usize).contains(&selected),
+                "line {selected} is out of view at height {height} scrolled to {}",
+                state.scroll
+            );
+            assert!(
+                state.scroll.abs_diff(scroll) <= 1,
+                "jumped at line {selected}"
+            );
+        }
+        state
+    }
+
+    #[test]
+    fn selection_stays_visible_in_short_inspectors() {
+        for height in 1..=6 {
+            scroll_through(30, height);
+        }
+    }
+
+    #[test]
+    fn selection_stays_visible_in_tall_inspectors() {
+        for height in [20, 40, 100] {
+            scroll_through(300, height);
+        }
+        // Everything fits, so there is nothing to scroll.
+        assert_eq!(scroll_through(30, 40).scroll, 0);
+    }
+
+    #[test]
+    fn scroll_margin_scales_with_the_height() {
+        for (height, margin) in [(3, 0), (8, 2), (16, 4), (60, MAX_SCROLL_MARGIN)] {
+            let mut state = InspectorState {
+                selectable_lines: (0..200).collect(),
+                scroll: 100,
+                ..Default::default()
+            };
+            // Moving up to the margin doesn't scroll, moving past it does.
+            state.selected = 100 + margin;
+            state.update_scroll(200, height);
+            assert_eq!(state.scroll, 100, "height {height}");
+            if margin > 0 {
+                state.selected = 100 + margin - 1;
+                state.update_scroll(200, height);
+                assert_eq!(state.scroll, 99, "height {height}");
+            }
+        }
+    }
+
+    fn summary(value: Value, theme: &Theme) -> Option<String> {
+        time_summary(value.as_object().unwrap(), theme)
+    }
+
+    #[test]
+    fn durations_are_summarized_in_their_largest_unit() {
+        let theme = Theme::default();
+        let cases = [
+            (json!({ "secs": 0, "nanos": 0 }), "0ns"),
+            (json!({ "secs": 0, "nanos": 750 }), "750ns"),
+            (json!({ "secs": 0, "nanos": 12_500 }), "12.500µs"),
+            (json!({ "secs": 0, "nanos": 16_666_667 }), "16.667ms"),
+            (json!({ "secs": 1, "nanos": 250_000_000 }), "1.250s"),
+            (json!({ "secs": 125, "nanos": 500_000_000 }), "2m 05.500s"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(summary(value, &theme).as_deref(), Some(expected));
+        }
+        let ascii = Theme {
+            ascii: true,
+            ..Theme::default()
+        };
+        let value = json!({ "secs": 0, "nanos": 12_500 });
+        assert_eq!(summary(value, &ascii).as_deref(), Some("12.500us"));
+    }
+
+    #[test]
+    fn timers_and_stopwatches_show_the_time_elapsed() {
+        let theme = Theme::default();
+        let stopwatch = json!({
+            "elapsed": { "secs": 0, "nanos": 500_000_000 },
+            "is_paused": false,
+        });
+        assert_eq!(
+            summary(stopwatch.clone(), &theme).as_deref(),
+            Some("500.000ms")
+        );
+
+        let timer = json!({
+            "stopwatch": stopwatch,
+            "duration": { "secs": 2, "nanos": 0 },
+            "mode": "Repeating",
+            "finished": false,
+            "times_finished_this_tick": 0,
+        });
+        assert_eq!(
+            summary(timer, &theme).as_deref(),
+            Some("500.000ms / 2.000s")
+        );
+    }
+
+    #[test]
+    fn unrelated_objects_are_not_summarized() {
+        let theme = Theme::default();
+        for value in [
+            json!({}),
+            json!({ "secs": 1 }),
+            json!({ "secs": 1, "nanos": 0, "label": "cooldown" }),
+            json!({ "secs": -1, "nanos": 0 }),
+            json!({ "secs": 1.5, "nanos": 0 }),
+            json!({ "secs": "1", "nanos": "0" }),
+            json!({ "secs": 1, "nanos": 1_000_000_000 }),
+            json!({ "secs": 1, "millis": 0 }),
+            // A duration without anything elapsed isn't a timer.
+            json!({ "duration": { "secs": 2, "nanos": 0 }, "mode": "Once" }),
+            json!({ "elapsed": 3, "is_paused": false }),
+            json!({ "stopwatch": "paused" }),
+        ] {
+            assert_eq!(summary(value.clone(), &theme), None, "{value}");
+        }
+    }
+
+    #[test]
+    fn durations_are_summarized_next_to_their_fields() {
+        let value = json!({ "cooldown": { "secs": 3, "nanos": 0 } });
+        let lines = rendered(Inspector::new(&value, true));
+        assert_eq!(lines[1], "   cooldown: { 3.000s");
+        // The raw fields are still there to select and edit.
+        assert_eq!(lines[2], "      secs: 3");
+        assert_eq!(lines[3], "      nanos: 0");
+    }
+
+    #[test]
+    fn lines_are_flattened_again_once_anything_they_depend_on_changes() {
+        let mut state = InspectorState::default();
+        let value = json!({ "a": 1, "b": { "c": 2 } });
+        assert_eq!(
+            rendered_with(Inspector::new(&value, true), &mut state)[1],
+            "   a: 1"
+        );
+
+        let changed = json!({ "a": 5, "b": { "c": 2 } });
+        let lines = rendered_with(Inspector::new(&changed, true), &mut state);
+        assert_eq!(lines[1], "   a: 5");
+
+        let collapsed = HashSet::from([".b".to_string()]);
+        let lines = rendered_with(
+            Inspector::new(&changed, true).collapsed(&collapsed),
+            &mut state,
+        );
+        assert_eq!(lines[2], "   b: {…} 1 field");
+        assert_eq!(lines[3], "}");
+        assert_eq!(state.paths, ["", ".a", ".b"]);
+
+        state.filter = TextInput::with_value("c");
+        let lines = rendered_with(Inspector::new(&changed, true), &mut state);
+        assert_eq!(lines[1], "   b: { 1 field");
+        assert_eq!(state.paths, ["", ".b", ".b.c"]);
+
+        // Showing nothing and then the same value again still lists its lines.
+        state.filter.clear();
+        rendered_with(Inspector::new(&json!({}), true), &mut state);
+        rendered_with(Inspector::new(&changed, true), &mut state);
+        assert_eq!(state.paths, ["", ".a", ".b", ".b.c"]);
+    }
+
+    #[test]
+    fn filtering_keeps_what_matches_are_in_and_contain() {
+        let value = json!({
+            "pos": { "x": 1, "y": 2 },
+            "position": { "z": 3 },
+            "list": [{ "speed": 4 }, { "size": 5 }],
+            "other": 6,
+        });
+        let mut state = InspectorState {
+            filter: TextInput::with_value("pos"),
+            ..Default::default()
+        };
+        rendered_with(Inspector::new(&value, true), &mut state);
+        assert_eq!(
+            state.paths,
+            ["", ".pos", ".pos.x", ".pos.y", ".position", ".position.z"]
+        );
+
+        state.filter = TextInput::with_value("speed");
+        rendered_with(Inspector::new(&value, true), &mut state);
+        assert_eq!(state.paths, ["", ".list", ".list[0]", ".list[0].speed"]);
+    }
+}
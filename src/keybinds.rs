@@ -1,7 +1,7 @@
-use crate::{inspector::ValueType, Focus, State};
+use crate::{inspector::ValueType, theme::Theme, Connected, Focus, Model, State};
 use ratatui::{
     prelude::{Buffer, Rect},
-    style::{Style, Stylize},
+    style::Stylize,
     text::{Line, Span},
     widgets::Widget,
 };
@@ -11,14 +11,134 @@ pub struct Keybind {
     pub keys: String,
     pub description: String,
     pub condition: KeybindCondition,
+    // Keybinds with a lower priority are hidden first when the footer is too narrow
+    pub priority: u8,
+}
+
+impl Keybind {
+    // The width of this keybind in the footer
+    fn width(&self) -> usize {
+        Line::raw(self.keys.as_str()).width() + 1 + Line::raw(self.description.as_str()).width()
+    }
 }
 
 // Conditions under which a keybind is active
 pub enum KeybindCondition {
     Always,
     Connected,
+    /// The connection failed, rather than not being made yet.
+    Disconnected,
+    /// Actions that change the app are allowed, they aren't in read-only mode.
+    Writable,
     Focus(Vec<Focus>),
     InspectorValue(Vec<ValueType>),
+    /// There are saved views to switch between.
+    Views,
+    /// Fields are being watched.
+    Watches,
+    /// A popup, the diff or the peek is shown over the panels.
+    Overlay,
+    Not(Box<KeybindCondition>),
+    And(Vec<KeybindCondition>),
+    Or(Vec<KeybindCondition>),
+}
+
+impl KeybindCondition {
+    // Whether this condition is met in the given model
+    pub fn is_met(&self, model: &Model) -> bool {
+        let state = &model.state;
+        match self {
+            KeybindCondition::Always => true,
+            KeybindCondition::Connected => matches!(state, State::Connected(_)),
+            KeybindCondition::Disconnected => matches!(state, State::Disconnected),
+            KeybindCondition::Writable => !model.read_only,
+            KeybindCondition::Focus(required) => {
+                if let Some(Connected { focus, .. }) = state.connected() {
+                    required.contains(focus)
+                } else {
+                    false
+                }
+            }
+            KeybindCondition::InspectorValue(values) => {
+                if let Some(Connected {
+                    focus, inspector, ..
+                }) = state.connected()
+                {
+                    if *focus == Focus::Inspector {
+                        return inspector
+                            .selected_value_type()
+                            .is_some_and(|value_type| values.contains(&value_type));
+                    }
+                }
+                false
+            }
+            KeybindCondition::Views => !model.views.is_empty(),
+            KeybindCondition::Watches => !model.watches.is_empty(),
+            KeybindCondition::Overlay => {
+                model.popup.is_some()
+                    || model.entity_popup.is_some()
+                    || model.diff.is_some()
+                    || model.peeking
+            }
+            KeybindCondition::Not(condition) => !condition.is_met(model),
+            KeybindCondition::And(conditions) => conditions.iter().all(|c| c.is_met(model)),
+            KeybindCondition::Or(conditions) => conditions.iter().any(|c| c.is_met(model)),
+        }
+    }
+}
+
+impl KeybindCondition {
+    // The heading a keybind with this condition is listed under in the cheatsheet
+    fn group(&self) -> String {
+        match self {
+            KeybindCondition::Always
+            | KeybindCondition::Writable
+            | KeybindCondition::Not(_)
+            | KeybindCondition::Or(_) => String::from("General"),
+            KeybindCondition::Connected => String::from("Connected"),
+            KeybindCondition::Disconnected => String::from("Disconnected"),
+            KeybindCondition::Focus(focuses) => focuses
+                .iter()
+                .map(focus_name)
+                .collect::<Vec<_>>()
+                .join(", "),
+            KeybindCondition::InspectorValue(_) => String::from(focus_name(&Focus::Inspector)),
+            KeybindCondition::Views => String::from("Saved views"),
+            KeybindCondition::Watches => String::from("Watches"),
+            KeybindCondition::Overlay => String::from("Popups"),
+            // The first condition is the one the keybind was added with, the rest narrow it down.
+            KeybindCondition::And(conditions) => conditions
+                .first()
+                .map_or_else(|| String::from("General"), KeybindCondition::group),
+        }
+    }
+}
+
+fn focus_name(focus: &Focus) -> &'static str {
+    match focus {
+        Focus::Entities => "Entities",
+        Focus::Components => "Components",
+        Focus::Inspector => "Inspector",
+        Focus::Compared => "Compared",
+        Focus::Filter => "Inspector filter",
+        Focus::EntitiesPage | Focus::ComponentsPage => "Go to page",
+        Focus::EntityId => "Go to entity",
+        Focus::EntityFilter => "Entity filter",
+        Focus::Edit => "Editing",
+        Focus::Search => "Search",
+        Focus::Picker => "Component picker",
+        Focus::Palette => "Method palette",
+        Focus::Response => "Method response",
+        Focus::ViewName => "Saving a view",
+    }
+}
+
+impl std::ops::Not for KeybindCondition {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        KeybindCondition::Not(Box::new(self))
+    }
 }
 
 // Collection of keybinds with helper methods
@@ -42,6 +162,7 @@ impl KeybindSet {
             keys: keys.into(),
             description: description.into(),
             condition,
+            priority: 0,
         });
         self
     }
@@ -80,63 +201,235 @@ impl KeybindSet {
         )
     }
 
-    // Get active keybinds based on current state
-    pub fn active_keybinds(&self, state: &State) -> Vec<(&str, &str)> {
+    pub fn when_not(
+        &mut self,
+        keys: impl Into<String>,
+        description: impl Into<String>,
+        condition: KeybindCondition,
+    ) -> &mut Self {
+        self.add(keys, description, !condition)
+    }
+
+    pub fn when_all(
+        &mut self,
+        keys: impl Into<String>,
+        description: impl Into<String>,
+        conditions: impl Into<Vec<KeybindCondition>>,
+    ) -> &mut Self {
+        self.add(keys, description, KeybindCondition::And(conditions.into()))
+    }
+
+    pub fn when_any(
+        &mut self,
+        keys: impl Into<String>,
+        description: impl Into<String>,
+        conditions: impl Into<Vec<KeybindCondition>>,
+    ) -> &mut Self {
+        self.add(keys, description, KeybindCondition::Or(conditions.into()))
+    }
+
+    // Only show the last added keybind when actions that change the app are allowed
+    pub fn mutating(&mut self) -> &mut Self {
+        if let Some(keybind) = self.keybinds.last_mut() {
+            let condition = std::mem::replace(&mut keybind.condition, KeybindCondition::Always);
+            keybind.condition = KeybindCondition::And(vec![condition, KeybindCondition::Writable]);
+        }
+        self
+    }
+
+    // Set the priority of the last added keybind, see [`Keybind::priority`]
+    pub fn priority(&mut self, priority: u8) -> &mut Self {
+        if let Some(keybind) = self.keybinds.last_mut() {
+            keybind.priority = priority;
+        }
+        self
+    }
+
+    // Get active keybinds based on the current model
+    pub fn active_keybinds(&self, model: &Model) -> Vec<&Keybind> {
         self.keybinds
             .iter()
-            .filter(|kb| match &kb.condition {
-                KeybindCondition::Always => true,
-                KeybindCondition::Connected => matches!(state, State::Connected { .. }),
-                KeybindCondition::Focus(required) => {
-                    if let State::Connected { focus, .. } = state {
-                        required.contains(focus)
-                    } else {
-                        false
-                    }
-                }
-                KeybindCondition::InspectorValue(values) => {
-                    if let State::Connected {
-                        focus, inspector, ..
-                    } = state
-                    {
-                        if *focus == Focus::Inspector {
-                            return values.contains(&inspector.selected_value_type());
-                        }
-                    }
-                    false
-                }
-            })
-            .map(|kb| (kb.keys.as_str(), kb.description.as_str()))
+            .filter(|kb| kb.condition.is_met(model))
             .collect()
     }
+
+    // Every keybind as plain text, grouped under headings by when they are active in the order
+    // the groups are first added
+    pub fn cheatsheet(&self) -> String {
+        let mut groups: Vec<(String, Vec<&Keybind>)> = Vec::new();
+        for keybind in &self.keybinds {
+            let group = keybind.condition.group();
+            match groups.iter_mut().find(|(name, _)| *name == group) {
+                Some((_, keybinds)) => keybinds.push(keybind),
+                None => groups.push((group, vec![keybind])),
+            }
+        }
+
+        let mut text = String::new();
+        for (name, keybinds) in groups {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(&name);
+            text.push('\n');
+            let width = keybinds
+                .iter()
+                .map(|kb| Line::raw(kb.keys.as_str()).width())
+                .max()
+                .unwrap_or_default();
+            for keybind in keybinds {
+                let padding = width - Line::raw(keybind.keys.as_str()).width();
+                text.push_str(&format!(
+                    "  {}{}  {}\n",
+                    keybind.keys,
+                    " ".repeat(padding),
+                    keybind.description
+                ));
+            }
+        }
+        text
+    }
+
+    pub fn len(&self) -> usize {
+        self.keybinds.len()
+    }
+}
+
+fn separator(theme: &Theme) -> &'static str {
+    theme.symbol(" • ", " | ")
+}
+
+// Widget to display active keybinds, hiding the lowest priority ones behind a "+N more" when
+// they don't fit
+pub struct KeybindDisplay<'a>(pub &'a [&'a Keybind], pub Theme);
+
+impl KeybindDisplay<'_> {
+    // Which keybinds fit in the given width, hiding the lowest priority and then the last added
+    // ones first
+    fn visible(&self, width: usize) -> Vec<bool> {
+        let separator = Span::raw(separator(&self.1)).width();
+        let mut visible = vec![true; self.0.len()];
+        loop {
+            let shown: Vec<_> = self.0.iter().zip(&visible).filter(|(_, v)| **v).collect();
+            let hidden = self.0.len() - shown.len();
+            let mut needed = shown.iter().map(|(kb, _)| kb.width()).sum::<usize>()
+                + shown.len().saturating_sub(1) * separator;
+            if hidden > 0 {
+                needed += separator + more_label(hidden).len();
+            }
+            if needed <= width {
+                return visible;
+            }
+
+            let Some(n) = (0..self.0.len())
+                .filter(|n| visible[*n])
+                .min_by_key(|n| (self.0[*n].priority, std::cmp::Reverse(*n)))
+            else {
+                return visible;
+            };
+            // Keep the highest priority keybinds even if they get clipped.
+            if self.0[n].priority == u8::MAX {
+                return visible;
+            }
+            visible[n] = false;
+        }
+    }
 }
 
-// Widget to display active keybinds
-pub struct KeybindDisplay<'a>(pub &'a [(&'a str, &'a str)]);
+fn more_label(hidden: usize) -> String {
+    format!("+{hidden} more")
+}
 
 impl Widget for KeybindDisplay<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let keybinds_len = self.0.len();
-        let text = Line::from(
-            self.0
-                .iter()
-                .enumerate()
-                .flat_map(|(n, (key, description))| {
-                    let dim = Style::default().dim();
-                    [
-                        Span::styled(*key, dim.bold()),
-                        Span::raw(" "),
-                        Span::styled(*description, dim),
-                        if n != keybinds_len - 1 {
-                            Span::styled(" • ", dim)
-                        } else {
-                            Span::default()
-                        },
-                    ]
-                })
-                .collect::<Vec<Span>>(),
+        let visible = self.visible(area.width as usize);
+        let hidden = visible.iter().filter(|v| !**v).count();
+        let dim = self.1.dim();
+
+        let mut spans = Vec::new();
+        for (keybind, _) in self.0.iter().zip(&visible).filter(|(_, v)| **v) {
+            if !spans.is_empty() {
+                spans.push(Span::styled(separator(&self.1), dim));
+            }
+            spans.push(Span::styled(self.1.keys(&keybind.keys), dim.bold()));
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(keybind.description.as_str(), dim));
+        }
+        if hidden > 0 {
+            if !spans.is_empty() {
+                spans.push(Span::styled(separator(&self.1), dim));
+            }
+            spans.push(Span::styled(more_label(hidden), dim));
+        }
+
+        Line::from(spans).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keybind(keys: &str, description: &str, priority: u8) -> Keybind {
+        Keybind {
+            keys: keys.to_string(),
+            description: description.to_string(),
+            condition: KeybindCondition::Always,
+            priority,
+        }
+    }
+
+    fn rendered(keybinds: &[Keybind], width: u16, theme: Theme) -> String {
+        let keybinds: Vec<_> = keybinds.iter().collect();
+        let area = Rect::new(0, 0, width, 1);
+        let mut buf = Buffer::empty(area);
+        KeybindDisplay(&keybinds, theme).render(area, &mut buf);
+        (0..width)
+            .map(|x| buf[(x, 0)].symbol())
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    }
+
+    #[test]
+    fn narrow_footer_hides_low_priority_keybinds_but_keeps_quit() {
+        let keybinds = [
+            keybind("s", "search", 0),
+            keybind("x", "despawn", 0),
+            keybind("L", "log", 0),
+            keybind("q", "quit", u8::MAX),
+        ];
+        assert_eq!(
+            rendered(&keybinds, 80, Theme::default()),
+            "s search • x despawn • L log • q quit"
         );
+        assert_eq!(
+            rendered(&keybinds, 20, Theme::default()),
+            "q quit • +3 more"
+        );
+        let ascii = Theme {
+            ascii: true,
+            ..Theme::default()
+        };
+        assert_eq!(rendered(&keybinds, 20, ascii), "q quit | +3 more");
+    }
+
+    #[test]
+    fn last_added_keybinds_are_hidden_first_at_equal_priority() {
+        let keybinds = [
+            keybind("s", "search", 0),
+            keybind("x", "despawn", 0),
+            keybind("q", "quit", u8::MAX),
+        ];
+        assert_eq!(
+            rendered(&keybinds, 28, Theme::default()),
+            "s search • q quit • +1 more"
+        );
+    }
 
-        text.render(area, buf);
+    #[test]
+    fn highest_priority_keybinds_are_kept_even_when_clipped() {
+        let keybinds = [keybind("q", "quit the app", u8::MAX)];
+        assert_eq!(rendered(&keybinds, 6, Theme::default()), "q quit");
     }
 }
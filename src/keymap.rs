@@ -0,0 +1,255 @@
+//! The mapping from key presses to [`Message`]s.
+
+use crate::{Focus, Message};
+use clap::ValueEnum;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// A set of key bindings to choose from.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum KeymapPreset {
+    /// Vim style movement with hjkl as well as the arrow keys.
+    #[default]
+    Default,
+    /// Movement with the arrow keys only.
+    Arrows,
+    /// Emacs style movement with C-b, C-f, C-p and C-n as well as the arrow keys.
+    Emacs,
+}
+
+/// A single key bound to the [`Message`] it sends.
+struct Binding {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+    message: Message,
+}
+
+/// A lookup table from key presses to [`Message`]s.
+pub struct Keymap {
+    bindings: Vec<Binding>,
+}
+
+impl Keymap {
+    pub fn new(preset: KeymapPreset) -> Self {
+        let mut keymap = Self {
+            bindings: Vec::new(),
+        };
+
+        // Movement, with the keys of the preset first since they are shown first in the footer.
+        match preset {
+            KeymapPreset::Default => {
+                keymap
+                    .bind(KeyCode::Char('h'), Message::MoveLeft)
+                    .bind(KeyCode::Char('l'), Message::MoveRight)
+                    .bind(KeyCode::Char('k'), Message::MoveUp)
+                    .bind(KeyCode::Char('j'), Message::MoveDown)
+                    .bind(KeyCode::Char('['), Message::PageUp)
+                    .bind(KeyCode::Char(']'), Message::PageDown);
+            }
+            KeymapPreset::Arrows => {
+                keymap
+                    .bind(KeyCode::Char('['), Message::PageUp)
+                    .bind(KeyCode::Char(']'), Message::PageDown);
+            }
+            KeymapPreset::Emacs => {
+                keymap
+                    .bind_ctrl('b', Message::MoveLeft)
+                    .bind_ctrl('f', Message::MoveRight)
+                    .bind_ctrl('p', Message::MoveUp)
+                    .bind_ctrl('n', Message::MoveDown)
+                    .bind_alt('v', Message::PageUp)
+                    .bind_ctrl('v', Message::PageDown)
+                    .bind_alt('<', Message::Home)
+                    .bind_alt('>', Message::End)
+                    .bind(KeyCode::Char('['), Message::PageUp)
+                    .bind(KeyCode::Char(']'), Message::PageDown);
+            }
+        }
+        keymap
+            .bind(KeyCode::Left, Message::MoveLeft)
+            .bind(KeyCode::Right, Message::MoveRight)
+            .bind(KeyCode::Up, Message::MoveUp)
+            .bind(KeyCode::Down, Message::MoveDown)
+            .bind(KeyCode::PageUp, Message::PageUp)
+            .bind(KeyCode::PageDown, Message::PageDown)
+            .bind(KeyCode::Home, Message::Home)
+            .bind(KeyCode::End, Message::End)
+            .bind_ctrl('u', Message::HalfPageUp)
+            .bind_ctrl('d', Message::HalfPageDown);
+
+        // Actions
+        keymap
+            .bind(KeyCode::Char('1'), Message::FocusPanel(Focus::Entities))
+            .bind(KeyCode::Char('2'), Message::FocusPanel(Focus::Components))
+            .bind(KeyCode::Char('3'), Message::FocusPanel(Focus::Inspector))
+            .bind(KeyCode::Delete, Message::Delete)
+            .bind(KeyCode::Char('x'), Message::Delete)
+            .bind(KeyCode::Char('s'), Message::Search)
+            .bind(KeyCode::Char('/'), Message::Filter)
+            .bind(KeyCode::Char(':'), Message::TypePage)
+            .bind(KeyCode::Char('#'), Message::TypeEntityId)
+            .bind(KeyCode::Char('F'), Message::FilterEntities)
+            .bind(KeyCode::Char('a'), Message::AddComponent)
+            .bind(KeyCode::Char('D'), Message::Duplicate)
+            .bind(KeyCode::Char('E'), Message::ExportWorld)
+            .bind(
+                KeyCode::Char('v'),
+                Message::CycleVariant { backwards: false },
+            )
+            .bind(
+                KeyCode::Char('V'),
+                Message::CycleVariant { backwards: true },
+            )
+            .bind(KeyCode::Char('t'), Message::Toggle)
+            .bind(KeyCode::Char('e'), Message::Edit)
+            .bind(KeyCode::Char('y'), Message::Copy)
+            .bind(KeyCode::Char('Y'), Message::CopyPath)
+            .bind(KeyCode::Char('f'), Message::FollowEntity)
+            .bind(KeyCode::Enter, Message::ToggleCollapse)
+            .bind(KeyCode::Char('C'), Message::ExpandAll)
+            .bind(KeyCode::Char('0'), Message::ResetView)
+            .bind(KeyCode::Char('P'), Message::Paste)
+            .bind(KeyCode::Char('O'), Message::OpenEditor)
+            .bind(KeyCode::Char('g'), Message::ToggleGrouping)
+            .bind(KeyCode::Char('G'), Message::ToggleCollapseGroup)
+            .bind(KeyCode::Char('m'), Message::ToggleMarkers)
+            .bind(KeyCode::Char('T'), Message::ToggleFullPaths)
+            .bind(KeyCode::Char('o'), Message::ToggleRecentFirst)
+            .bind(KeyCode::Char('p'), Message::Reparent)
+            .bind(KeyCode::Char('w'), Message::ToggleSwatches)
+            .bind(KeyCode::Char('A'), Message::ToggleSortFields)
+            .bind(KeyCode::Char('!'), Message::ToggleStrict)
+            .bind(KeyCode::Char('Z'), Message::ToggleFreeze)
+            .bind(KeyCode::Char('H'), Message::ToggleLock)
+            .bind(KeyCode::Char('c'), Message::ToggleCompare)
+            .bind(KeyCode::Char('z'), Message::ToggleMaximize)
+            .bind(KeyCode::Char('<'), Message::ResizePanel { grow: false })
+            .bind(KeyCode::Char('>'), Message::ResizePanel { grow: true })
+            .bind(KeyCode::Char('L'), Message::ToggleLog)
+            .bind(KeyCode::Char('K'), Message::CopyKeybinds)
+            .bind(KeyCode::Char('B'), Message::OpenDocs)
+            .bind(KeyCode::F(12), Message::ToggleRequestRates)
+            .bind(KeyCode::Char('d'), Message::ToggleEntityDetails)
+            .bind(KeyCode::Char('i'), Message::CycleEntityLabel)
+            .bind(KeyCode::Char('I'), Message::ShowEntity)
+            .bind(KeyCode::Char(' '), Message::TogglePeek)
+            .bind(KeyCode::Char('N'), Message::TakeSnapshot)
+            .bind(KeyCode::Char('U'), Message::ShowDiff)
+            .bind(KeyCode::Char('*'), Message::TogglePin)
+            .bind(KeyCode::Char('W'), Message::ToggleWatch)
+            .bind(KeyCode::Char('X'), Message::ClearWatches)
+            .bind(KeyCode::Char('r'), Message::Retry)
+            .bind(KeyCode::Char('R'), Message::Refresh)
+            .bind(KeyCode::Char('S'), Message::NameView)
+            .bind(KeyCode::Char('M'), Message::OpenPalette)
+            .bind(KeyCode::Tab, Message::NextView)
+            .bind(KeyCode::Esc, Message::Dismiss)
+            .bind_ctrl('c', Message::Quit)
+            .bind(KeyCode::Char('q'), Message::Quit);
+
+        keymap
+    }
+
+    /// The [`Message`] bound to the given key, if any.
+    pub fn message(&self, key: KeyEvent) -> Option<Message> {
+        // Shift is ignored since it is already reflected in the character of the key.
+        let modifiers = key.modifiers.difference(KeyModifiers::SHIFT);
+        self.bindings
+            .iter()
+            .find(|binding| binding.code == key.code && binding.modifiers == modifiers)
+            .map(|binding| binding.message.clone())
+    }
+
+    /// The keys bound to the given messages as shown in the footer, one label for each set of
+    /// alternative keys in the order they were bound, such as `hjkl` and `←↓↑→` for moving.
+    ///
+    /// Messages are compared by their variant, ignoring any data they hold.
+    pub fn labels(&self, messages: &[Message]) -> Vec<String> {
+        let keys: Vec<Vec<&Binding>> = messages
+            .iter()
+            .map(|message| {
+                self.bindings
+                    .iter()
+                    .filter(|binding| {
+                        std::mem::discriminant(&binding.message) == std::mem::discriminant(message)
+                    })
+                    .collect()
+            })
+            .collect();
+        let alternatives = keys.iter().map(Vec::len).min().unwrap_or(0);
+        (0..alternatives)
+            .map(|n| label(keys.iter().map(|bindings| bindings[n])))
+            .collect()
+    }
+
+    fn bind_with(&mut self, code: KeyCode, modifiers: KeyModifiers, message: Message) -> &mut Self {
+        self.bindings.push(Binding {
+            code,
+            modifiers,
+            message,
+        });
+        self
+    }
+
+    fn bind(&mut self, code: KeyCode, message: Message) -> &mut Self {
+        self.bind_with(code, KeyModifiers::NONE, message)
+    }
+
+    fn bind_ctrl(&mut self, ch: char, message: Message) -> &mut Self {
+        self.bind_with(KeyCode::Char(ch), KeyModifiers::CONTROL, message)
+    }
+
+    fn bind_alt(&mut self, ch: char, message: Message) -> &mut Self {
+        self.bind_with(KeyCode::Char(ch), KeyModifiers::ALT, message)
+    }
+}
+
+/// The keys of the bindings side by side, with a modifier they all share written once at the start
+/// like `C-bnpf`.
+fn label<'a>(bindings: impl Iterator<Item = &'a Binding> + Clone) -> String {
+    let mut modifiers = bindings.clone().map(|binding| binding.modifiers);
+    let first = modifiers.next().unwrap_or(KeyModifiers::NONE);
+    let shared = modifiers.all(|modifiers| modifiers == first);
+    let keys: Vec<String> = bindings
+        .map(|binding| match shared {
+            true => key_label(binding.code),
+            false => format!(
+                "{}{}",
+                modifier_label(binding.modifiers),
+                key_label(binding.code)
+            ),
+        })
+        .collect();
+    // Keys longer than a character would run together.
+    let separator = match keys.iter().any(|key| key.chars().count() > 1) {
+        true => " ",
+        false => "",
+    };
+    match shared {
+        true => format!("{}{}", modifier_label(first), keys.join(separator)),
+        false => keys.join(separator),
+    }
+}
+
+fn modifier_label(modifiers: KeyModifiers) -> &'static str {
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        "C-"
+    } else if modifiers.contains(KeyModifiers::ALT) {
+        "M-"
+    } else {
+        ""
+    }
+}
+
+fn key_label(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(ch) => ch.to_string(),
+        KeyCode::Left => String::from("←"),
+        KeyCode::Right => String::from("→"),
+        KeyCode::Up => String::from("↑"),
+        KeyCode::Down => String::from("↓"),
+        KeyCode::PageUp => String::from("pgup"),
+        KeyCode::PageDown => String::from("pgdn"),
+        KeyCode::Delete => String::from("del"),
+        code => code.to_string().to_lowercase(),
+    }
+}
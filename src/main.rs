@@ -1,86 +1,538 @@
-use bevy_remote::builtin_methods::{BrpDestroyParams, BrpRemoveParams};
-use brp::{handle_components_querying, EntityMeta};
+use action_log::{ActionLog, LogEntry, LogPane};
+use anyhow::{bail, Context};
+use bevy_ecs::entity::Entity;
+use bevy_remote::builtin_methods::{
+    BrpDestroyParams, BrpGetParams, BrpInsertParams, BrpRemoveParams, BrpReparentParams,
+    BrpSpawnParams,
+};
+use brp::{
+    handle_components_querying, BrpMutateComponentParams, EntityId, EntityLabel, EntityMeta,
+    EntityQueryOptions,
+};
+use clap::Parser;
+use cli::SocketSource;
+use clipboard::Clipboard;
+use components::{ComponentChanges, ComponentErrors, ComponentListOptions, ComponentRow};
+use config::Config;
+use diagnosis::Diagnosis;
+use diff::{DiffPopup, DiffWidget, Snapshot};
 use disqualified::ShortName;
-use inspector::{Inspector, InspectorState, ValueType};
-use keybinds::{KeybindDisplay, KeybindSet};
+use entity_filter::EntityFilter;
+use events::EventGate;
+use export::{Export, Exported};
+use inspector::{BoolStyle, Inspector, InspectorState, ValueType};
+use keybinds::{KeybindCondition, KeybindDisplay, KeybindSet};
+use keymap::Keymap;
 use paginated_list::{PaginatedList, PaginatedListState};
+use palette::{
+    CallHistory, MethodPalette, MethodPaletteWidget, MethodResponse, MethodResponseWidget,
+};
+use peek::{PeekCache, PeekWidget};
+use picker::{ComponentPicker, ComponentPickerWidget};
+use popup::{EntityPopup, ErrorPopup};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
-    style::{palette::material::WHITE, Color, Style, Stylize},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{palette::material::WHITE, Style, Stylize},
+    symbols::border,
     text::{Line, Span, Text},
-    widgets::{Block, BorderType, Borders, Padding, Paragraph},
+    widgets::{Block, Borders, Padding, Paragraph, Wrap},
     Frame,
 };
-use serde_json::Value;
+use registry::{Registry, RegistryStatus};
+use search::Search;
+use serde_json::{Number, Value};
+use session::Session;
+use stats::RequestRates;
 use std::{
-    net::SocketAddr,
+    collections::{HashMap, HashSet},
     sync::{
         atomic::{AtomicBool, Ordering},
-        mpsc, Arc,
+        mpsc, Arc, Condvar, Mutex, PoisonError,
     },
     thread,
+    time::{Duration, Instant},
 };
+use text_input::{InputResult, TextInput};
+use theme::Theme;
+use transport::Socket;
+use views::{View, ViewNameInput};
+use watch::{Watch, WatchPane, WatchValues};
 
+mod action_log;
 mod brp;
+mod cli;
+mod clipboard;
+mod components;
+mod config;
+mod debug_log;
+mod diagnosis;
+mod diff;
+mod docs;
+mod editor;
+mod entity_filter;
 mod events;
+mod export;
 mod inspector;
 mod keybinds;
+mod keymap;
+mod once;
 mod paginated_list;
+mod palette;
+mod peek;
+mod picker;
+mod popup;
+mod registry;
+mod search;
+mod session;
+mod stats;
+mod text_input;
+mod theme;
+mod transport;
+mod views;
+mod watch;
 
-const PRIMARY_COLOR: Color = Color::Rgb(37, 160, 101);
+/// The height of the log pane including its border.
+const LOG_PANE_HEIGHT: u16 = 7;
+/// The most watches shown at once, the watch pane is as tall as needed up to this.
+const MAX_WATCH_PANE_LINES: u16 = 6;
+/// The smallest terminal the UI is laid out in, smaller ones just show a message.
+const MIN_TERMINAL_WIDTH: u16 = 40;
+const MIN_TERMINAL_HEIGHT: u16 = 10;
+/// The narrowest a panel is made when resizing, if the terminal is wide enough.
+const MIN_PANEL_WIDTH: u16 = 16;
+/// The largest relative width of a panel.
+const MAX_PANEL_RATIO: u16 = 8;
+/// The narrowest the body is shown as three panels, below which just the focused panel is shown
+/// and moving left and right pages between them.
+const COMPACT_WIDTH: u16 = 60;
+/// The frames of the spinner shown while connecting.
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+/// The frames of the spinner when drawing with just ASCII.
+const ASCII_SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+/// How long each frame of the spinner is shown for.
+const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+/// How long to wait before fetching the registry again after failing to.
+const REGISTRY_RETRY_DELAY: Duration = Duration::from_secs(5);
 
 struct Model {
     state: State,
-    socket: SocketAddr,
+    socket: Socket,
+    /// Where the socket was given, shown while connecting.
+    socket_source: SocketSource,
     message_tx: mpsc::Sender<Message>,
     keybinds: KeybindSet,
+    keymap: Keymap,
+    /// How the components panel lists components.
+    component_options: ComponentListOptions,
+    /// The groups of the components panel shown as just their header while grouping.
+    collapsed_groups: HashSet<String>,
+    /// An error being shown over the UI until dismissed.
+    popup: Option<ErrorPopup>,
+    /// A summary of an entity shown over the UI until dismissed.
+    entity_popup: Option<EntityPopup>,
+    /// Whether the components of the entity highlighted in the entities list are shown over the
+    /// other panels while it is focused.
+    peeking: bool,
+    peek_cache: PeekCache,
+    /// The components captured to compare later ones against.
+    snapshot: Option<Snapshot>,
+    /// The differences from the snapshot shown over the UI until dismissed.
+    diff: Option<DiffPopup>,
+    /// A short message shown in the footer, such as the outcome of an action.
+    status: Option<Status>,
+    /// Whether to show swatches next to colors in the inspector.
+    color_swatches: bool,
+    /// Whether components are fetched strictly, failing loudly if any can't be rather than
+    /// listing them as failed.
+    strict_components: bool,
+    /// The outcomes of recent actions.
+    log: ActionLog,
+    /// The methods called from the method palette.
+    call_history: CallHistory,
+    /// Whether the log pane is shown below the panels.
+    show_log: bool,
+    /// Whether the rate of BRP requests is shown over the body.
+    show_request_rates: bool,
+    /// Whether entities are shown with a second line of details.
+    entity_details: bool,
+    entity_label: EntityLabel,
+    /// Wakes the entity querying thread so it queries immediately.
+    entities_waker: WakeToken,
+    /// Stops the threads that run for as long as the app, set on quitting.
+    shutdown: ThreadQuitToken,
+    /// The components entities are listed with or without, shared with the entity querying
+    /// thread.
+    entity_filter: Arc<Mutex<EntityFilter>>,
+    /// The components kept at the value they had rather than updating live, by type path, shared
+    /// with the component querying thread.
+    frozen_components: Arc<Mutex<HashSet<String>>>,
+    /// The types registered in the app, fetched when first needed.
+    registry: RegistryStatus,
+    /// The export of the world in progress.
+    export: Option<Export>,
+    clipboard: Clipboard,
+    /// Entities listed before all others, kept for as long as brptui runs.
+    pins: HashSet<Entity>,
+    /// Fields shown with their live values in the watch pane, kept for as long as brptui runs.
+    watches: Vec<Watch>,
+    watch_values: WatchValues,
+    watch_thread_quitter: Option<ThreadQuitToken>,
+    spinner_thread_quitter: Option<ThreadQuitToken>,
+    /// Why the app couldn't be reached when last disconnected.
+    diagnosis: Option<Diagnosis>,
+    /// The value to open in an external editor once the current messages are handled.
+    external_edit: Option<ExternalEdit>,
+    /// Whether actions that change the app are disabled.
+    read_only: bool,
+    /// The only components fetched for the selected entity, or every component it has if empty.
+    fixed_components: Vec<String>,
+    /// Whether moving past either end of a list moves to the other end.
+    wrap_selection: bool,
+    /// Whether moving past either end of the inspector moves to the other end.
+    inspector_wrap: bool,
+    /// Whether the selection flashes for a frame when it moves.
+    flash_selection: bool,
+    bool_style: BoolStyle,
+    /// Whether the inspector lists the fields of objects alphabetically.
+    sort_fields: bool,
+    /// The paths of the objects and arrays collapsed in the inspector by component type, so they
+    /// stay collapsed when selecting another entity.
+    collapsed: HashMap<String, HashSet<String>>,
+    /// The relative widths of the entities, components and inspector panels.
+    panel_ratios: [u16; 3],
+    theme: Theme,
+    views: Vec<View>,
+    /// The view last saved or switched to, shown in the header.
+    active_view: Option<usize>,
+    /// Whether the terminal was too small to lay out the UI when last drawn.
+    too_small: bool,
+    /// The panel shown in place of all three while it is focused. Focusing another panel shows
+    /// them all again.
+    maximized: Option<Focus>,
+    /// The entity to select once it is listed, such as the one selected in the last session,
+    /// before losing the connection or a duplicate that was just spawned.
+    reselect: Option<Entity>,
 }
 
 impl Model {
-    fn new(message_tx: mpsc::Sender<Message>, keybinds: KeybindSet) -> Self {
+    fn new(
+        message_tx: mpsc::Sender<Message>,
+        keybinds: KeybindSet,
+        keymap: Keymap,
+        socket: Socket,
+    ) -> Self {
         Self {
-            state: Default::default(),
-            socket: brp::DEFAULT_SOCKET,
+            state: State::Connecting { frame: 0 },
+            socket,
+            socket_source: SocketSource::Default,
             message_tx,
             keybinds,
+            keymap,
+            component_options: ComponentListOptions::default(),
+            collapsed_groups: HashSet::new(),
+            popup: None,
+            entity_popup: None,
+            peeking: false,
+            peek_cache: PeekCache::default(),
+            snapshot: None,
+            diff: None,
+            status: None,
+            color_swatches: true,
+            strict_components: false,
+            log: ActionLog::default(),
+            call_history: CallHistory::default(),
+            show_log: false,
+            show_request_rates: false,
+            entity_details: false,
+            entity_label: EntityLabel::default(),
+            entities_waker: WakeToken::default(),
+            shutdown: ThreadQuitToken::new(),
+            entity_filter: Arc::default(),
+            frozen_components: Arc::default(),
+            registry: RegistryStatus::default(),
+            export: None,
+            clipboard: Clipboard::default(),
+            pins: HashSet::new(),
+            watches: Vec::new(),
+            watch_values: WatchValues::new(),
+            watch_thread_quitter: None,
+            spinner_thread_quitter: None,
+            diagnosis: None,
+            external_edit: None,
+            collapsed: HashMap::new(),
+            read_only: false,
+            fixed_components: Vec::new(),
+            wrap_selection: true,
+            inspector_wrap: false,
+            flash_selection: true,
+            bool_style: BoolStyle::default(),
+            sort_fields: false,
+            panel_ratios: [1, 1, 2],
+            theme: Theme::default(),
+            views: Vec::new(),
+            active_view: None,
+            maximized: None,
+            too_small: false,
+            reselect: None,
         }
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 enum State {
-    Connected {
-        focus: Focus,
-        entities: Vec<EntityMeta>,
-        entities_list: PaginatedListState,
-        components: Vec<(String, Value)>,
-        components_list: PaginatedListState,
-        components_thread_quitter: Option<ThreadQuitToken>,
-        inspector: InspectorState,
+    /// Waiting for the first response from the app, either since launching or retrying.
+    Connecting {
+        /// The frame of the spinner shown.
+        frame: usize,
     },
-    #[default]
+    Connected(Box<Connected>),
     Disconnected,
     Done,
 }
 
+impl State {
+    fn connected(&self) -> Option<&Connected> {
+        match self {
+            State::Connected(connected) => Some(connected),
+            _ => None,
+        }
+    }
+
+    fn connected_mut(&mut self) -> Option<&mut Connected> {
+        match self {
+            State::Connected(connected) => Some(connected),
+            _ => None,
+        }
+    }
+}
+
+/// Everything shown while connected to the app, boxed in [`State::Connected`] as it is far larger
+/// than the other states.
 #[derive(Debug)]
+struct Connected {
+    focus: Focus,
+    entities: Vec<EntityMeta>,
+    entities_list: PaginatedListState,
+    components: Vec<(String, Value)>,
+    components_list: PaginatedListState,
+    component_changes: ComponentChanges,
+    component_errors: ComponentErrors,
+    components_thread_quitter: Option<ThreadQuitToken>,
+    /// The entity whose components are being fetched for the first time.
+    components_loading: Option<Entity>,
+    inspector: InspectorState,
+    /// The entity picked up to be given a new parent.
+    reparenting: Option<Entity>,
+    search: Search,
+    /// The overlay for inserting a component, open while focused.
+    picker: Option<ComponentPicker>,
+    /// The overlay for calling any method, open while focused.
+    palette: Option<MethodPalette>,
+    /// The result of the last method called from the palette, shown while focused.
+    response: Option<MethodResponse>,
+    /// The type path of the component shown next to the inspector to compare against.
+    compared: Option<String>,
+    compared_inspector: InspectorState,
+    /// The type path of the component the inspector stays on while moving between entities,
+    /// rather than following the components list.
+    locked: Option<String>,
+    /// The page number being typed while focused on [`Focus::EntitiesPage`] or
+    /// [`Focus::ComponentsPage`].
+    page_input: TextInput,
+    /// The entity id being typed while focused on [`Focus::EntityId`].
+    entity_input: TextInput,
+    /// The component filter being typed while focused on [`Focus::EntityFilter`].
+    entity_filter_input: TextInput,
+    /// The name being typed while focused on [`Focus::ViewName`].
+    view_name: Option<ViewNameInput>,
+}
+
+#[derive(Debug, Clone)]
 enum Message {
+    Key(KeyEvent),
+    /// The terminal changed size and needs drawing again.
+    Resize,
+    /// Time to show the next frame of the spinner.
+    Tick,
     MoveLeft,
     MoveRight,
     MoveUp,
     MoveDown,
     PageUp,
     PageDown,
+    /// Move the inspector up by half its height.
+    HalfPageUp,
+    /// Move the inspector down by half its height.
+    HalfPageDown,
     Home,
     End,
     Delete,
+    Search,
+    /// Start typing a filter for the fields shown in the inspector.
+    Filter,
+    /// Start typing the number of a page to jump to in the focused list.
+    TypePage,
+    /// Select the first item on the page at the given index of the list the page number was
+    /// typed for.
+    GoToPage(usize),
+    /// Start typing the id of an entity to select.
+    TypeEntityId,
+    /// Select the entity with the given id, clearing the search if it hides it.
+    GoToEntity(EntityId),
+    /// Start typing the components entities are listed with or without.
+    FilterEntities,
+    /// List just the entities matching the typed components.
+    SetEntityFilter,
+    FocusPanel(Focus),
+    AddComponent,
+    InsertComponent,
+    /// Spawn a copy of the selected entity with the same components.
+    Duplicate,
+    /// An entity was spawned and should be selected once it is listed.
+    Spawned(Entity),
+    /// Change the selected enum in the inspector to its next variant, or previous if backwards.
+    CycleVariant {
+        backwards: bool,
+    },
+    /// Flip the selected boolean in the inspector.
+    Toggle,
+    /// Start typing a new value for the selected number or string in the inspector.
+    Edit,
+    /// Set the selected value in the inspector to the typed value.
+    SubmitEdit,
+    /// Copy the selected value in the inspector as JSON.
+    Copy,
+    /// Show the selected component next to the inspector to compare against, or stop comparing.
+    ToggleCompare,
+    /// Show the focused panel in place of all three, or all three again if it already is.
+    ToggleMaximize,
+    /// Make the focused panel narrower, or wider if grow.
+    ResizePanel {
+        grow: bool,
+    },
+    /// Collapse the selected object or array in the inspector, or expand it if it already is.
+    ToggleCollapse,
+    /// Expand every collapsed object and array in the inspector.
+    ExpandAll,
+    /// Go back to the top of every list with nothing searched, filtered or collapsed.
+    ResetView,
+    /// Copy the path of the selected value in the inspector, such as `translation[0]`.
+    CopyPath,
+    /// Select the entity referenced by the selected value in the inspector.
+    FollowEntity,
+    /// Open the selected value in the inspector in an external editor.
+    OpenEditor,
+    /// The editor was closed, with the edited text or why editing failed.
+    ExternalEditFinished(ExternalEdit, Result<String, String>),
+    /// Replace the selected value in the inspector with JSON from the clipboard.
+    Paste,
+    ToggleGrouping,
+    /// Collapse the group of the selected component to its header, or expand the selected header.
+    ToggleCollapseGroup,
+    ToggleMarkers,
+    /// Switch between listing components by their short names and their full type paths.
+    ToggleFullPaths,
+    /// Order components by when they last changed instead of by type path, or back again.
+    ToggleRecentFirst,
+    ToggleSwatches,
+    /// Switch between listing the fields of objects in the inspector alphabetically and in the
+    /// order the app sent them.
+    ToggleSortFields,
+    /// Switch between fetching components strictly and leniently.
+    ToggleStrict,
+    /// Stop or resume updating the selected component.
+    ToggleFreeze,
+    /// Keep the inspector on the selected component type while moving between entities, or stop.
+    ToggleLock,
+    ToggleLog,
+    /// Copy every keybind to the clipboard as a cheatsheet.
+    CopyKeybinds,
+    /// Open the docs.rs page of the selected component's type in the browser.
+    OpenDocs,
+    ToggleRequestRates,
+    ToggleEntityDetails,
+    /// Show a summary of the selected entity in a popup.
+    ShowEntity,
+    /// Show or hide the components of the entity highlighted in the entities list over the other
+    /// panels.
+    TogglePeek,
+    /// Capture the components of the selected entity to compare against later.
+    TakeSnapshot,
+    /// Show what changed between the snapshot and the components of the selected entity.
+    ShowDiff,
+    CycleEntityLabel,
+    TogglePin,
+    /// Watch the selected field in the inspector, or stop watching it if it already is.
+    ToggleWatch,
+    /// Stop watching every field.
+    ClearWatches,
     SpawnComponnentsThread,
     UpdateEntities(Vec<EntityMeta>),
-    UpdateComponents(Vec<(String, Value)>),
+    UpdateComponents(Vec<(String, Value)>, ComponentErrors),
+    /// The components of the entity are being fetched for the first time, or `None` once they
+    /// won't be.
+    ComponentsLoading(Option<Entity>),
+    UpdateWatches(WatchValues),
+    /// The fetched registry, [`None`] if the app doesn't provide it, or why fetching it failed.
+    UpdateRegistry(Result<Option<Registry>, String>),
+    ExportWorld,
+    /// The number of entities exported so far.
+    ExportProgress(usize),
+    /// The file the world was exported to, or [`None`] if the export was cancelled.
+    ExportFinished(Result<Option<Exported>, String>),
     CommunicationFailed,
+    /// Why the app couldn't be reached, sent before the first [`Message::CommunicationFailed`]
+    /// of each run of failures.
+    Diagnosed(Diagnosis),
+    Reparent,
+    Retry,
+    /// Query the entities and components of the selected entity again right away.
+    Refresh,
+    /// Start typing a name to save the current search, filter, component ordering, pins and
+    /// focus as a view.
+    NameView,
+    /// Save the current view with the typed name.
+    SaveView,
+    /// Switch to the next saved view.
+    NextView,
+    /// Open the palette for calling any method.
+    OpenPalette,
+    /// Call the method typed in the palette.
+    CallMethod,
+    /// The result of a method called from the palette.
+    ShowResponse {
+        method: String,
+        value: Value,
+    },
+    ShowError(ErrorPopup),
+    Log(LogEntry),
+    Dismiss,
     Quit,
 }
 
+impl Message {
+    /// If this message changes the connected app, which isn't allowed in read-only mode.
+    fn mutates(&self) -> bool {
+        matches!(
+            self,
+            Message::Delete
+                | Message::AddComponent
+                | Message::InsertComponent
+                | Message::Duplicate
+                | Message::CycleVariant { .. }
+                | Message::Toggle
+                | Message::Edit
+                | Message::SubmitEdit
+                | Message::Paste
+                | Message::OpenEditor
+                | Message::Reparent
+                // Methods of the app can do anything.
+                | Message::OpenPalette
+        )
+    }
+}
+
 /// Areas that a user can focus on.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 enum Focus {
@@ -91,52 +543,396 @@ enum Focus {
     Components,
     /// The panel displaying the value of the selected component.
     Inspector,
+    /// The component being compared, shown next to the inspector.
+    Compared,
+    /// The filter of the inspector.
+    Filter,
+    /// Typing the number of a page of the entities panel to jump to.
+    EntitiesPage,
+    /// Typing the id of an entity to select.
+    EntityId,
+    /// Typing the components entities are listed with or without.
+    EntityFilter,
+    /// Typing the number of a page of the components panel to jump to.
+    ComponentsPage,
+    /// Typing a new value for the selected value in the inspector.
+    Edit,
     /// The searchbar
     Search,
+    /// The overlay for picking a component to insert.
+    Picker,
+    /// The overlay for calling any method.
+    Palette,
+    /// The result of a method called from the palette.
+    Response,
+    /// Typing the name to save the current view as.
+    ViewName,
+}
+
+impl Focus {
+    /// If keys go to a text input while focused, before the keymap.
+    fn is_text_input(self) -> bool {
+        matches!(
+            self,
+            Focus::Filter
+                | Focus::EntitiesPage
+                | Focus::EntityId
+                | Focus::EntityFilter
+                | Focus::ComponentsPage
+                | Focus::Edit
+                | Focus::Search
+                | Focus::Picker
+                | Focus::Palette
+                | Focus::ViewName
+        )
+    }
 }
 
 fn main() -> std::io::Result<()> {
+    let args = cli::Args::parse();
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            std::process::exit(1);
+        }
+    };
+    let session = Session::load();
+    let (socket, socket_source) = args.socket(session.socket);
+    if let Some(path) = &args.log_file {
+        if let Err(err) = debug_log::start(path) {
+            eprintln!("error: failed to create {}: {err}", path.display());
+            std::process::exit(1);
+        }
+    }
+
+    if args.once || !args.query.is_empty() {
+        std::process::exit(once::run(
+            &socket,
+            args.query,
+            config.name_component.as_deref(),
+        ));
+    }
+
     let mut terminal = ratatui::init();
 
+    // The footer shows the keys bound for movement, which change with the preset.
+    let keymap = Keymap::new(args.keymap);
+    let movement = keymap
+        .labels(&[
+            Message::MoveLeft,
+            Message::MoveDown,
+            Message::MoveUp,
+            Message::MoveRight,
+        ])
+        .join("/");
+    let first_label = |messages: &[Message]| {
+        keymap
+            .labels(messages)
+            .into_iter()
+            .next()
+            .unwrap_or_default()
+    };
+    let page = first_label(&[Message::PageUp, Message::PageDown]);
+    let half_page = first_label(&[Message::HalfPageDown, Message::HalfPageUp]);
+
     // Keybinds will be displayed in the order they are added
     let mut keybinds = KeybindSet::new();
     keybinds
-        .always("s", "search")
+        .when_not(
+            "s",
+            "search",
+            KeybindCondition::Focus(vec![
+                Focus::Search,
+                Focus::Picker,
+                Focus::Palette,
+                Focus::Response,
+                Focus::Filter,
+                Focus::Edit,
+                Focus::EntitiesPage,
+                Focus::ComponentsPage,
+                Focus::EntityId,
+                Focus::EntityFilter,
+                Focus::ViewName,
+            ]),
+        )
+        .when_focus("enter", "done", [Focus::Search, Focus::Filter])
+        .when_focus("esc", "clear", [Focus::Search, Focus::Filter])
         .when_focus("x", "despawn", [Focus::Entities])
+        .mutating()
         .when_focus("x", "remove", [Focus::Components])
-        .when_focus("[]", "move page", [Focus::Entities, Focus::Components])
+        .mutating()
+        .when_focus("a", "add", [Focus::Entities, Focus::Components])
+        .mutating()
+        .when_focus("tab", "method/params", [Focus::Palette])
+        .when_focus("↓↑", "recent", [Focus::Palette])
+        .when_focus("enter", "call", [Focus::Palette])
+        .when_focus("esc", "cancel", [Focus::Palette])
+        .when_any(
+            "esc",
+            "close",
+            [
+                KeybindCondition::Overlay,
+                KeybindCondition::Focus(vec![Focus::Response]),
+            ],
+        )
+        .when_focus(
+            "M",
+            "call method",
+            [Focus::Entities, Focus::Components, Focus::Inspector],
+        )
+        .mutating()
+        .when_focus("↓↑", "move", [Focus::Picker])
+        .when_focus("enter", "insert", [Focus::Picker])
+        .when_focus("esc", "cancel", [Focus::Picker])
+        .when_focus("p", "reparent", [Focus::Entities])
+        .mutating()
+        .when_focus("D", "duplicate", [Focus::Entities])
+        .mutating()
+        .when_focus("d", "details", [Focus::Entities])
+        .when_focus("i", "labels", [Focus::Entities])
+        .when_focus("I", "summary", [Focus::Entities])
+        .when_focus("space", "peek", [Focus::Entities])
+        .when_focus("*", "pin", [Focus::Entities])
+        .when_focus("y", "copy curl", [Focus::Entities])
+        .when_focus(page, "move page", [Focus::Entities, Focus::Components])
+        .when_focus(":", "go to page", [Focus::Entities, Focus::Components])
+        .when_focus("#", "go to entity", [Focus::Entities])
+        .when_focus("F", "filter by components", [Focus::Entities])
+        .when_focus(
+            "enter",
+            "go",
+            [Focus::EntitiesPage, Focus::ComponentsPage, Focus::EntityId],
+        )
+        .when_focus("enter", "apply", [Focus::EntityFilter])
+        .when_focus("esc", "cancel", [Focus::EntityFilter])
+        .when_focus(
+            "esc",
+            "cancel",
+            [Focus::EntitiesPage, Focus::ComponentsPage, Focus::EntityId],
+        )
+        .when_focus("g", "group", [Focus::Components])
+        .when_focus("G", "fold group", [Focus::Components])
+        .when_focus("m", "markers", [Focus::Components])
+        .when_focus("T", "full paths", [Focus::Components])
+        .when_focus("o", "recent first", [Focus::Components])
+        .when_focus("!", "strict", [Focus::Components])
+        .when_focus("Z", "freeze", [Focus::Components, Focus::Inspector])
+        .when_focus("H", "lock", [Focus::Components, Focus::Inspector])
+        .when_focus("B", "docs", [Focus::Components, Focus::Inspector])
+        .when_focus(
+            "N",
+            "snapshot",
+            [Focus::Entities, Focus::Components, Focus::Inspector],
+        )
+        .when_focus(
+            "U",
+            "diff snapshot",
+            [Focus::Entities, Focus::Components, Focus::Inspector],
+        )
         .when_inspector_value("t", "toggle", [ValueType::Bool])
-        .when_inspector_value("e", "edit", [ValueType::Number, ValueType::String])
-        .when_connected("hjkl/←↓↑→", "move")
-        .always("q", "quit");
+        .mutating()
+        .when_inspector_value(
+            "e",
+            "edit",
+            [ValueType::Number, ValueType::Entity, ValueType::String],
+        )
+        .mutating()
+        .when_inspector_value("v/V", "cycle variant", [ValueType::Enum])
+        .mutating()
+        .when_focus("enter", "set", [Focus::Edit])
+        .when_focus("esc", "cancel", [Focus::Edit])
+        .when_focus("/", "filter", [Focus::Inspector])
+        .when_inspector_value(
+            "enter",
+            "collapse",
+            [ValueType::Object, ValueType::Array, ValueType::Tuple],
+        )
+        .when_focus(half_page, "half page", [Focus::Inspector, Focus::Compared])
+        .when_focus("C", "expand all", [Focus::Inspector])
+        .when_focus(
+            "0",
+            "reset view",
+            [
+                Focus::Entities,
+                Focus::Components,
+                Focus::Inspector,
+                Focus::Compared,
+            ],
+        )
+        .when_inspector_value("f", "follow", [ValueType::Entity])
+        .when_focus("y", "copy", [Focus::Inspector])
+        .when_focus("Y", "copy path", [Focus::Inspector])
+        .when_focus("P", "paste", [Focus::Inspector])
+        .mutating()
+        .when_focus("O", "open in editor", [Focus::Inspector])
+        .mutating()
+        .when_focus("w", "swatches", [Focus::Inspector])
+        .when_focus("A", "sort fields", [Focus::Inspector])
+        .when_focus("W", "watch", [Focus::Inspector])
+        .add("X", "clear watches", KeybindCondition::Watches)
+        .when_focus(
+            "c",
+            "compare",
+            [Focus::Components, Focus::Inspector, Focus::Compared],
+        )
+        .when_focus(
+            "z",
+            "zoom",
+            [
+                Focus::Entities,
+                Focus::Components,
+                Focus::Inspector,
+                Focus::Compared,
+            ],
+        )
+        .when_focus(
+            "<>",
+            "resize",
+            [
+                Focus::Entities,
+                Focus::Components,
+                Focus::Inspector,
+                Focus::Compared,
+            ],
+        )
+        .when_connected("123", "panel")
+        .when_all(
+            movement,
+            "move",
+            [
+                KeybindCondition::Connected,
+                !KeybindCondition::Focus(vec![
+                    Focus::Search,
+                    Focus::Picker,
+                    Focus::Palette,
+                    Focus::Filter,
+                    Focus::Edit,
+                    Focus::EntitiesPage,
+                    Focus::ComponentsPage,
+                    Focus::EntityId,
+                    Focus::EntityFilter,
+                    Focus::ViewName,
+                ]),
+            ],
+        )
+        .when_focus("enter", "save", [Focus::ViewName])
+        .when_focus("esc", "cancel", [Focus::ViewName])
+        .when_focus(
+            "S",
+            "save view",
+            [Focus::Entities, Focus::Components, Focus::Inspector],
+        )
+        .add("tab", "next view", KeybindCondition::Views)
+        .when_connected("E", "export")
+        .when_connected("R", "refresh")
+        .add("r", "retry", KeybindCondition::Disconnected)
+        .always("L", "log")
+        .always("K", "copy keybinds")
+        .always("q", "quit")
+        .priority(u8::MAX);
 
     let (tx, rx) = mpsc::channel();
-    let mut model = Model::new(tx.clone(), keybinds);
+    let mut model = Model::new(tx.clone(), keybinds, keymap, socket);
+    model.socket_source = socket_source;
+    model.reselect = session.selected_entity;
+    model.read_only = args.read_only || config.read_only;
+    model.fixed_components = args.components;
+    model.entity_label = config.entity_label;
+    model.wrap_selection = config.wrap_selection;
+    model.inspector_wrap = config.inspector_wrap;
+    model.flash_selection = config.flash_selection;
+    model.bool_style = config.bool_style;
+    model.sort_fields = config.sort_fields;
+    brp::set_min_request_delay(Duration::from_millis(config.min_request_delay));
+    model.theme = config.theme;
+    model.theme.ascii = args.ascii || config.ascii;
+    model.views = views::load();
+    model.panel_ratios = config
+        .panel_ratios
+        .map(|ratio| ratio.clamp(1, MAX_PANEL_RATIO));
 
     // Spawn crossterm event handler thread.
     let events_tx = tx.clone();
-    thread::spawn(move || events::handle_events(events_tx));
+    let events_gate = EventGate::default();
+    let gate = events_gate.clone();
+    let quit = model.shutdown.clone();
+    let events_thread = thread::spawn(move || events::handle_events(events_tx, gate, quit));
+
+    spawn_spinner_thread(&mut model);
 
     // Spawn BRP entity querying thread.
     let querying_tx = tx.clone();
-    thread::spawn(move || brp::handle_entity_querying(querying_tx, &model.socket));
+    let socket = model.socket.clone();
+    let waker = model.entities_waker.clone();
+    let options = EntityQueryOptions {
+        extra_components: args.extra_components,
+        name_component: config.name_component,
+        warm_start: args.refresh_once_then_watch,
+    };
+    let entity_filter = model.entity_filter.clone();
+    let quit = model.shutdown.clone();
+    thread::spawn(move || {
+        brp::handle_entity_querying(querying_tx, &socket, options, entity_filter, waker, quit)
+    });
 
     while !matches!(model.state, State::Done) {
-        let mut next_msg = Some(rx.recv().unwrap());
-
-        // Process updates as long as they return a non-None message.
-        // Render after every update so stateful widgets can update their state.
-        while let Some(msg) = next_msg {
-            next_msg = update(&mut model, msg);
-            terminal.draw(|f| view(&mut model, f))?;
+        // Everything already waiting is handled before drawing once, rather than drawing after
+        // each message. The worker threads send their updates far enough apart that there is
+        // rarely more than one waiting.
+        let first = rx.recv().unwrap();
+        let pending: Vec<_> = std::iter::once(first).chain(rx.try_iter()).collect();
+        let mut drawn = true;
+        for msg in pending {
+            if matches!(model.state, State::Done) {
+                break;
+            }
+            // Lists only move their cursor once rendered, so key presses wait for the changes
+            // before them to be drawn.
+            if !drawn && matches!(msg, Message::Key(_)) {
+                terminal.draw(|f| view(&mut model, f))?;
+            }
+            let mut next_msg = update(&mut model, msg);
+            // Process updates as long as they return a non-None message, rendering before each
+            // so stateful widgets can update their state, such as the selected entity.
+            while let Some(msg) = next_msg {
+                terminal.draw(|f| view(&mut model, f))?;
+                next_msg = update(&mut model, msg);
+            }
+            drawn = false;
+        }
+        // The editor is given the terminal until it exits, its result is handled like any other
+        // message.
+        if let Some(edit) = model.external_edit.take() {
+            let paused = events_gate.pause();
+            ratatui::restore();
+            let result = editor::edit(&edit.text).map_err(|err| format!("{err:#}"));
+            terminal = ratatui::init();
+            drop(paused);
+            let _ = tx.send(Message::ExternalEditFinished(edit, result));
         }
+        terminal.draw(|f| view(&mut model, f))?;
     }
 
+    // The terminal mustn't be read from once restored. The other threads may be waiting on the app
+    // so they are left to stop on their own.
+    let _ = events_thread.join();
     ratatui::restore();
     Ok(())
 }
 
 fn view(model: &mut Model, frame: &mut Frame) {
+    let theme = model.theme;
+    let area = frame.area();
+    frame.render_widget(Block::default().bg(theme.background), area);
+    model.too_small = area.width < MIN_TERMINAL_WIDTH || area.height < MIN_TERMINAL_HEIGHT;
+    if model.too_small {
+        let text =
+            format!("Terminal too small, need at least {MIN_TERMINAL_WIDTH}x{MIN_TERMINAL_HEIGHT}");
+        frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: true }), area);
+        return;
+    }
+
     let layout = Layout::default()
         .constraints([
             Constraint::Length(1), // Header
@@ -147,109 +943,571 @@ fn view(model: &mut Model, frame: &mut Frame) {
         .spacing(1)
         .split(frame.area());
 
+    // Split the log pane off the bottom of the body.
+    let (body_area, log_area) = if model.show_log {
+        let [body, log] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(LOG_PANE_HEIGHT)])
+                .spacing(1)
+                .areas(layout[1]);
+        (body, Some(log))
+    } else {
+        (layout[1], None)
+    };
+    // And the watch pane off the bottom of what is left, including its border.
+    let (body_area, watch_area) = if model.watches.is_empty() {
+        (body_area, None)
+    } else {
+        let lines = (model.watches.len() as u16).min(MAX_WATCH_PANE_LINES);
+        let [body, watch] = Layout::vertical([Constraint::Fill(1), Constraint::Length(lines + 1)])
+            .spacing(1)
+            .areas(body_area);
+        (body, Some(watch))
+    };
+
     // Header
-    let text = Text::styled(" brptui ", Style::default().fg(WHITE).bg(PRIMARY_COLOR));
-    frame.render_widget(Paragraph::new(text), layout[0]);
+    let [title_area, search_area] =
+        Layout::horizontal([Constraint::Length(8), Constraint::Fill(1)])
+            .spacing(2)
+            .areas(layout[0]);
+    let text = Text::styled(" brptui ", Style::default().fg(WHITE).bg(theme.accent));
+    frame.render_widget(Paragraph::new(text), title_area);
+    if let Some(Connected {
+        focus,
+        search,
+        view_name,
+        ..
+    }) = model.state.connected()
+    {
+        let searching = *focus == Focus::Search;
+        if let (Focus::ViewName, Some(view_name)) = (focus, view_name) {
+            let line = Line::from(vec![
+                Span::styled("save view as ", theme.dim()),
+                Span::raw(view_name.input.value()),
+                Span::raw(theme.cursor()).fg(theme.accent),
+            ]);
+            frame.render_widget(line, search_area);
+        } else if searching || !search.query().is_empty() {
+            let mut line = Line::from(vec![
+                Span::styled("/ ", theme.dim()),
+                Span::raw(search.query()),
+            ]);
+            if searching {
+                line.push_span(Span::raw(theme.cursor()).fg(theme.accent));
+            }
+            frame.render_widget(line, search_area);
+        }
+    }
+
+    let mut header_right = Vec::new();
+    if let Some(view) = model.active_view.and_then(|n| model.views.get(n)) {
+        header_right.push(Span::styled("view ", theme.dim()));
+        header_right.push(Span::raw(view.name.as_str()).bold());
+    }
+    if model.read_only {
+        if !header_right.is_empty() {
+            header_right.push(Span::raw("  "));
+        }
+        header_right.push(Span::styled("read-only mode", theme.dim()));
+    }
+    frame.render_widget(Line::from(header_right).right_aligned(), layout[0]);
 
     // Body
     match &mut model.state {
-        State::Connected {
-            focus,
-            entities,
-            entities_list,
-            components,
-            components_list,
-            inspector,
-            ..
-        } => {
-            let body_layout = Layout::new(
-                Direction::Horizontal,
-                [
-                    Constraint::Fill(1),
-                    Constraint::Fill(1),
-                    Constraint::Fill(2),
-                ],
-            )
-            .split(layout[1]);
+        State::Connected(connected) => {
+            let Connected {
+                focus,
+                entities,
+                entities_list,
+                components,
+                components_list,
+                component_errors,
+                components_loading,
+                inspector,
+                reparenting,
+                search,
+                compared,
+                compared_inspector,
+                locked,
+                page_input,
+                entity_input,
+                entity_filter_input,
+                ..
+            } = &mut **connected;
+            // The overlays, such as the search, are shown over the entities when compact.
+            let compact =
+                (body_area.width < COMPACT_WIDTH).then(|| panel_index(*focus).unwrap_or_default());
+            let maximized = model
+                .maximized
+                .and_then(panel_index)
+                .filter(|index| panel_index(*focus) == Some(*index));
+            let constraints = match compact.or(maximized) {
+                Some(index) => {
+                    let mut constraints = [Constraint::Length(0); 3];
+                    constraints[index] = Constraint::Fill(1);
+                    constraints
+                }
+                None => panel_constraints(model.panel_ratios, body_area.width),
+            };
+            let body_layout = Layout::new(Direction::Horizontal, constraints).split(body_area);
 
-            let entities_block = Block::default()
+            let mut entities_block = Block::default()
                 .borders(Borders::RIGHT)
-                .border_type(BorderType::Thick)
-                .border_style(border_style(matches!(
-                    focus,
-                    Focus::Entities | Focus::Components
-                )));
+                .border_set(theme.border_set(border::THICK))
+                .border_style(border_style(
+                    matches!(
+                        focus,
+                        Focus::Entities
+                            | Focus::EntitiesPage
+                            | Focus::EntityId
+                            | Focus::EntityFilter
+                            | Focus::Components
+                            | Focus::ComponentsPage
+                    ),
+                    &theme,
+                ));
+            if let Some(moving) = reparenting {
+                let label = entities
+                    .iter()
+                    .find(|e| e.id == *moving)
+                    .map(|e| e.label(model.entity_label))
+                    .unwrap_or_else(|| moving.to_string());
+                entities_block =
+                    entities_block.title(format!("moving {label}").fg(theme.accent).bold());
+            }
+            if *focus == Focus::EntitiesPage {
+                entities_block = entities_block.title(input_title("page", page_input, &theme));
+            }
+            if *focus == Focus::EntityId {
+                entities_block = entities_block.title(input_title("entity", entity_input, &theme));
+            }
+            let entity_filter = model
+                .entity_filter
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone();
+            if *focus == Focus::EntityFilter {
+                entities_block = entities_block.title_bottom(input_title(
+                    "components",
+                    entity_filter_input,
+                    &theme,
+                ));
+            } else if !entity_filter.is_empty() {
+                entities_block = entities_block.title_bottom(entity_filter.title(&theme));
+            }
 
-            let components_block = Block::default().padding(Padding::horizontal(1));
+            let mut components_block = Block::default().padding(Padding::horizontal(1));
+            let hidden = components::hidden_count(components, model.component_options);
+            if hidden > 0 {
+                components_block =
+                    components_block.title(Span::styled(format!("{hidden} hidden"), theme.dim()));
+            }
+            if *focus == Focus::ComponentsPage {
+                components_block = components_block.title(input_title("page", page_input, &theme));
+            }
+            // Shown over the components of the last entity until the first of this one arrive.
+            let loading = components_loading.is_some();
+            if loading {
+                components_block = components_block.title(
+                    Line::styled(theme.symbol("loading…", "loading..."), theme.dim())
+                        .right_aligned(),
+                );
+            }
 
             let inspector_block = Block::default()
                 .padding(Padding::left(1))
                 .borders(Borders::LEFT)
-                .border_type(BorderType::Thick)
-                .border_style(border_style(matches!(
+                .border_set(theme.border_set(border::THICK))
+                .border_style(border_style(
+                    matches!(
+                        focus,
+                        Focus::Components
+                            | Focus::ComponentsPage
+                            | Focus::Inspector
+                            | Focus::Compared
+                            | Focus::Filter
+                            | Focus::Edit
+                    ),
+                    &theme,
+                ));
+
+            let matches = listed_entities(search, entities, &model.pins);
+            let entities_inner = entities_block.inner(body_layout[0]);
+            let mut entities_widget = PaginatedList::new(
+                matches.iter().map(|n| {
+                    let meta = &entities[*n];
+                    let mut title = meta.title(model.entity_label, &theme);
+                    if model.pins.contains(&meta.id) {
+                        let pin = theme.symbol("● ", "* ");
+                        title.spans.insert(0, Span::raw(pin).fg(theme.accent));
+                    }
+                    title
+                }),
+                matches!(
                     focus,
-                    Focus::Components | Focus::Inspector
-                )));
+                    Focus::Entities | Focus::EntitiesPage | Focus::EntityId | Focus::EntityFilter
+                ),
+            )
+            .theme(theme)
+            .wrap(model.wrap_selection)
+            .flash(model.flash_selection)
+            .block(entities_block);
+            if model.entity_details {
+                entities_widget =
+                    entities_widget.subtitles(matches.iter().map(|n| entities[*n].subtitle()));
+            }
+            frame.render_stateful_widget(entities_widget, body_layout[0], entities_list);
+            // Explain an empty list so it isn't mistaken for a lost connection.
+            if matches.is_empty() {
+                let text = if entities.is_empty() && !entity_filter.is_empty() {
+                    String::from("No entities match the component filter")
+                } else if entities.is_empty() {
+                    String::from("World has no entities")
+                } else {
+                    format!("No entities match '{}'", search.query())
+                };
+                frame.render_widget(Paragraph::new(text).bold(), entities_inner);
+            }
 
-            frame.render_stateful_widget(
-                PaginatedList::new(
-                    entities.iter().map(EntityMeta::title),
-                    *focus == Focus::Entities,
-                )
-                .block(entities_block),
-                body_layout[0],
-                entities_list,
+            let rows = components::component_rows(
+                components,
+                model.component_options,
+                &model.collapsed_groups,
             );
-
-            if !components.is_empty() {
+            if !rows.is_empty() {
                 frame.render_stateful_widget(
                     PaginatedList::new(
-                        components
-                            .iter()
-                            .map(|(name, _)| ShortName(name).to_string())
-                            .map(Span::raw)
-                            .map(Span::bold)
-                            .map(Line::from),
-                        *focus == Focus::Components,
+                        components::row_lines(
+                            &rows,
+                            components,
+                            component_errors,
+                            &model
+                                .frozen_components
+                                .lock()
+                                .unwrap_or_else(PoisonError::into_inner),
+                            model.component_options,
+                            &theme,
+                        ),
+                        matches!(focus, Focus::Components | Focus::ComponentsPage),
+                    )
+                    .theme(theme)
+                    .wrap(model.wrap_selection)
+                    .flash(model.flash_selection)
+                    .unselectable(
+                        rows.iter()
+                            .enumerate()
+                            .filter(|(_, row)| matches!(row, ComponentRow::Group(_)))
+                            .map(|(n, _)| n),
                     )
                     .block(components_block),
                     body_layout[1],
                     components_list,
                 );
             } else {
+                let text = if matches.is_empty() {
+                    "No entity selected"
+                } else if loading {
+                    theme.symbol("Loading components…", "Loading components...")
+                } else {
+                    "Nothing to show"
+                };
                 frame.render_widget(
-                    Paragraph::new("Nothing to show")
-                        .bold()
-                        .block(components_block),
+                    Paragraph::new(text).bold().block(components_block),
                     body_layout[1],
                 );
             }
 
-            if let Some(selected_component) = components.get(components_list.selected()) {
-                frame.render_stateful_widget(
-                    Inspector::new(&selected_component.1, *focus == Focus::Inspector)
+            let selected_component = inspected_component(
+                components,
+                components_list,
+                model.component_options,
+                &model.collapsed_groups,
+                locked.as_ref(),
+            )
+            .and_then(|n| components.get(n));
+            // The compared component takes the right half of the inspector panel.
+            let (inspector_area, compared_area) = match compared {
+                Some(_) => {
+                    let [left, right] =
+                        Layout::horizontal([Constraint::Fill(1); 2]).areas(body_layout[2]);
+                    (left, Some(right))
+                }
+                None => (body_layout[2], None),
+            };
+            let selected_error = selected_component
+                .and_then(|(type_path, _)| Some((type_path, component_errors.get(type_path)?)));
+            if let Some((type_path, err)) = selected_error {
+                let text = vec![
+                    Line::raw(format!("Failed to fetch {}", ShortName(type_path))).bold(),
+                    Line::default(),
+                    Line::raw(err.as_str()).fg(theme.error),
+                ];
+                frame.render_widget(
+                    Paragraph::new(text)
+                        .wrap(Wrap { trim: true })
                         .block(inspector_block),
-                    body_layout[2],
+                    inspector_area,
+                );
+            } else if let Some((type_path, value)) = selected_component {
+                let mut inspector_widget = Inspector::new(
+                    value,
+                    matches!(focus, Focus::Inspector | Focus::Filter | Focus::Edit),
+                )
+                .theme(theme)
+                .wrap(model.inspector_wrap)
+                .flash(model.flash_selection)
+                .bool_style(model.bool_style)
+                .sort_fields(model.sort_fields)
+                .swatches(model.color_swatches)
+                .entities(entities, model.entity_label);
+                if let Some(collapsed) = model.collapsed.get(type_path) {
+                    inspector_widget = inspector_widget.collapsed(collapsed);
+                }
+                let mut inspector_block = inspector_block;
+                if compared.is_some() {
+                    inspector_block =
+                        inspector_block.title(ShortName(type_path).to_string().bold());
+                }
+                if locked.is_some() {
+                    inspector_block = inspector_block.title(Span::styled(
+                        format!("locked: {}", ShortName(type_path)),
+                        Style::default().fg(theme.accent),
+                    ));
+                }
+                let filtering = *focus == Focus::Filter;
+                if filtering || !inspector.filter.value().is_empty() {
+                    let mut line = Line::from(vec![
+                        Span::styled("/ ", theme.dim()),
+                        Span::raw(inspector.filter.value().to_string()),
+                    ]);
+                    if filtering {
+                        line.push_span(Span::raw(theme.cursor()).fg(theme.accent));
+                    }
+                    inspector_block = inspector_block.title(line.right_aligned());
+                }
+                if let RegistryStatus::Available(registry) = &model.registry {
+                    inspector_widget = inspector_widget.types(registry, type_path);
+                    // Just the summary line of the docs fits.
+                    if let Some(docs) = registry
+                        .description(type_path)
+                        .and_then(|docs| docs.lines().next())
+                    {
+                        inspector_block =
+                            inspector_block.title_bottom(Span::styled(docs.trim(), theme.dim()));
+                    }
+                }
+                frame.render_stateful_widget(
+                    inspector_widget.block(inspector_block),
+                    inspector_area,
                     inspector,
                 );
+            } else if let Some(locked) = locked {
+                let inspector_block = inspector_block.title(Span::styled(
+                    format!("locked: {}", ShortName(locked)),
+                    Style::default().fg(theme.accent),
+                ));
+                frame.render_widget(
+                    Paragraph::new(format!("No {} on this entity", ShortName(locked)))
+                        .bold()
+                        .block(inspector_block),
+                    inspector_area,
+                );
+            }
+            if let (Some(compared), Some(area)) = (compared, compared_area) {
+                let block = Block::default()
+                    .title(ShortName(compared).to_string().bold())
+                    .padding(Padding::left(1))
+                    .borders(Borders::LEFT)
+                    .border_set(theme.border_set(border::PLAIN))
+                    .border_style(border_style(*focus == Focus::Compared, &theme));
+                match components
+                    .iter()
+                    .find(|(type_path, _)| type_path == compared)
+                {
+                    Some((type_path, value)) => {
+                        let mut compared_widget = Inspector::new(value, *focus == Focus::Compared)
+                            .theme(theme)
+                            .wrap(model.inspector_wrap)
+                            .flash(model.flash_selection)
+                            .bool_style(model.bool_style)
+                            .sort_fields(model.sort_fields)
+                            .swatches(model.color_swatches)
+                            .entities(entities, model.entity_label);
+                        if let Some(collapsed) = model.collapsed.get(type_path) {
+                            compared_widget = compared_widget.collapsed(collapsed);
+                        }
+                        if let RegistryStatus::Available(registry) = &model.registry {
+                            compared_widget = compared_widget.types(registry, type_path);
+                        }
+                        frame.render_stateful_widget(
+                            compared_widget.block(block),
+                            area,
+                            compared_inspector,
+                        );
+                    }
+                    None => frame.render_widget(
+                        Paragraph::new(format!("No {} on this entity", ShortName(compared)))
+                            .bold()
+                            .block(block),
+                        area,
+                    ),
+                }
+            }
+
+            // Peeking covers the panels right of the entities, or the right half of the entities
+            // when they are the only panel shown.
+            let peeked = selected_entity(search, entities, entities_list, &model.pins)
+                .map(|n| &entities[n])
+                .filter(|_| model.peeking && *focus == Focus::Entities);
+            if let Some(entity) = peeked {
+                let area = match body_layout[1].union(body_layout[2]) {
+                    area if area.width > 0 => area,
+                    _ => Rect {
+                        x: body_area.x + body_area.width / 2,
+                        width: body_area.width - body_area.width / 2,
+                        ..body_area
+                    },
+                };
+                let components = match components_loading {
+                    None => Some(components.as_slice()),
+                    Some(_) => model.peek_cache.get(entity.id),
+                };
+                frame.render_widget(
+                    PeekWidget::new(entity.title(model.entity_label, &theme), components)
+                        .theme(theme)
+                        .bool_style(model.bool_style),
+                    area,
+                );
             }
         }
+        State::Connecting { frame: spinner } => {
+            let text = Line::from(vec![
+                Span::raw(spinner_frame(*spinner, &theme).to_string()).fg(theme.accent),
+                Span::raw(" Connecting to ").bold(),
+                Span::raw(model.socket.to_string()).bold(),
+                Span::styled(format!(" ({})", model.socket_source), theme.dim()),
+            ]);
+            frame.render_widget(Paragraph::new(text), body_area);
+        }
         State::Disconnected => {
-            frame.render_widget(Paragraph::new("Disconnected"), layout[1]);
+            let mut text = vec![Line::raw("Disconnected").bold(), Line::default()];
+            if let Some(diagnosis) = &model.diagnosis {
+                text.push(Line::raw(diagnosis.summary(&model.socket)));
+                text.push(Line::styled(diagnosis.hint(), theme.dim()));
+                text.push(Line::default());
+            }
+            text.push(
+                Line::from(vec![
+                    Span::raw("press "),
+                    Span::raw("r").bold(),
+                    Span::raw(" to retry"),
+                ])
+                .patch_style(theme.dim()),
+            );
+            frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: true }), body_area);
         }
         State::Done => {}
     }
 
+    // Watches
+    if let Some(watch_area) = watch_area {
+        let watch_block = Block::default()
+            .title(Span::styled("watch", theme.dim()))
+            .borders(Borders::TOP)
+            .border_set(theme.border_set(border::THICK))
+            .border_style(theme.dim());
+        let mut watch_pane = WatchPane::new(&model.watches, &model.watch_values)
+            .theme(theme)
+            .block(watch_block);
+        if let Some(Connected { entities, .. }) = model.state.connected() {
+            watch_pane = watch_pane.entities(entities, model.entity_label);
+        }
+        frame.render_widget(watch_pane, watch_area);
+    }
+
+    // Log
+    if let Some(log_area) = log_area {
+        let log_block = Block::default()
+            .title(Span::styled("log", theme.dim()))
+            .borders(Borders::TOP)
+            .border_set(theme.border_set(border::THICK))
+            .border_style(theme.dim());
+        frame.render_widget(
+            LogPane::new(&model.log).theme(theme).block(log_block),
+            log_area,
+        );
+    }
+
     // Footer
-    let active_keybinds = model.keybinds.active_keybinds(&model.state);
-    frame.render_widget(KeybindDisplay(&active_keybinds[..]), layout[2]);
+    let status = match (&model.export, &model.status) {
+        (Some(export), _) => Some(export.line(&theme)),
+        (None, Some(status)) => Some(status.line(&theme)),
+        (None, None) => None,
+    };
+    // The status takes priority, the keybinds get whatever space is left.
+    let status_width = status.as_ref().map_or(0, |line| line.width() as u16 + 2);
+    let [keybinds_area, status_area] =
+        Layout::horizontal([Constraint::Fill(1), Constraint::Length(status_width)])
+            .areas(layout[2]);
+    let active_keybinds = model.keybinds.active_keybinds(model);
+    frame.render_widget(KeybindDisplay(&active_keybinds[..], theme), keybinds_area);
+    if let Some(status) = status {
+        frame.render_widget(status.right_aligned(), status_area);
+    }
+
+    if model.show_request_rates {
+        frame.render_widget(&RequestRates::now().theme(theme), body_area);
+    }
+
+    if let Some(Connected {
+        picker: Some(picker),
+        ..
+    }) = model.state.connected_mut()
+    {
+        frame.render_stateful_widget(
+            ComponentPickerWidget::new(&model.registry)
+                .theme(theme)
+                .wrap(model.wrap_selection),
+            frame.area(),
+            picker,
+        );
+    }
+
+    if let Some(Connected {
+        palette, response, ..
+    }) = model.state.connected_mut()
+    {
+        if let Some(palette) = palette {
+            frame.render_stateful_widget(
+                MethodPaletteWidget::new(&model.call_history).theme(theme),
+                frame.area(),
+                palette,
+            );
+        }
+        if let Some(response) = response {
+            frame.render_stateful_widget(
+                MethodResponseWidget::new(theme).wrap(model.inspector_wrap),
+                frame.area(),
+                response,
+            );
+        }
+    }
+
+    if let Some(popup) = &model.entity_popup {
+        frame.render_widget(popup.widget(theme), frame.area());
+    }
+    if let Some(diff) = &mut model.diff {
+        frame.render_stateful_widget(DiffWidget::new(theme), frame.area(), diff);
+    }
+    if let Some(popup) = &model.popup {
+        frame.render_widget(popup.widget(theme), frame.area());
+    }
 }
 
 macro_rules! handle_movement {
     ($state:expr, {
         $($focus_pattern:pat => $list:ident $method:ident $(=> $after:expr)?),* $(,)?
     }) => {
-        if let State::Connected { focus, $($list,)* .. } = $state {
+        if let Some(Connected { focus, $($list,)* .. }) = $state {
             match focus {
                 $(
                     $focus_pattern => {
@@ -264,165 +1522,2409 @@ macro_rules! handle_movement {
 }
 
 fn update(model: &mut Model, msg: Message) -> Option<Message> {
-    match (msg, &mut model.state) {
-        // Navigation between panels
-        (Message::MoveLeft, State::Connected { focus, .. }) => {
-            *focus = match *focus {
-                Focus::Components => Focus::Entities,
-                Focus::Inspector => Focus::Components,
-                _ => *focus,
-            };
-        }
+    debug_log::log(format_args!("message {msg:?}"));
+    if model.read_only && msg.mutates() {
+        model.status = Some(Status::error("Disabled in read-only mode"));
+        return None;
+    }
+    // Nothing is shown to act on while the terminal is too small, and lists only move their
+    // cursor once rendered. Keys like `q` may be being typed into a text input, so just those
+    // with Ctrl quit from one.
+    if let (true, Message::Key(key)) = (model.too_small, &msg) {
+        let typing = model
+            .state
+            .connected()
+            .is_some_and(|connected| connected.focus.is_text_input());
+        return model.keymap.message(*key).filter(|msg| {
+            matches!(msg, Message::Quit)
+                && (!typing || key.modifiers.contains(KeyModifiers::CONTROL))
+        });
+    }
 
+    match (msg, model.state.connected_mut()) {
+        // Key presses go to the picker or search while they are focused, otherwise they are
+        // looked up in the keymap.
         (
-            Message::MoveRight,
-            State::Connected {
-                focus, components, ..
+            Message::Key(key),
+            Some(Connected {
+                focus,
+                picker: Some(picker),
+                ..
+            }),
+        ) if *focus == Focus::Picker => match picker.search.input.handle_key(key) {
+            InputResult::Changed => picker.list.select(0),
+            InputResult::Submitted => return Some(Message::InsertComponent),
+            InputResult::Cancelled => return Some(Message::Dismiss),
+            InputResult::Ignored => match key.code {
+                KeyCode::Up => picker.list.select_previous(),
+                KeyCode::Down => picker.list.select_next(),
+                KeyCode::PageUp => picker.list.select_previous_page(),
+                KeyCode::PageDown => picker.list.select_next_page(),
+                _ => return model.keymap.message(key),
             },
-        ) => {
-            *focus = match *focus {
-                Focus::Entities if !components.is_empty() => Focus::Components,
-                Focus::Components => Focus::Inspector,
-                _ => *focus,
-            };
-        }
-
-        (Message::MoveLeft | Message::MoveRight, _) => {}
-
-        // Movement within panels
-        (Message::MoveUp, state) => {
-            handle_movement!(state, {
-                Focus::Entities => entities_list select_previous => Message::SpawnComponnentsThread,
-                Focus::Components => components_list select_previous,
-                Focus::Inspector => inspector select_previous,
-            });
-        }
-
-        (Message::MoveDown, state) => {
-            handle_movement!(state, {
-                Focus::Entities => entities_list select_next => Message::SpawnComponnentsThread,
-                Focus::Components => components_list select_next,
-                Focus::Inspector => inspector select_next,
-            });
-        }
-
-        (Message::PageUp, state) => {
-            handle_movement!(state, {
-                Focus::Entities => entities_list select_previous_page => Message::SpawnComponnentsThread,
-                Focus::Components => components_list select_previous_page,
-            });
-        }
-
-        (Message::PageDown, state) => {
-            handle_movement!(state, {
-                Focus::Entities => entities_list select_next_page => Message::SpawnComponnentsThread,
-                Focus::Components => components_list select_next_page,
-            });
-        }
-
-        (Message::Home, state) => {
-            handle_movement!(state, {
-                Focus::Entities => entities_list select_first,
-                Focus::Components => components_list select_first,
-                Focus::Inspector => inspector select_first,
-            });
-        }
-
-        (Message::End, state) => {
-            handle_movement!(state, {
-                Focus::Entities => entities_list select_last,
-                Focus::Components => components_list select_last,
-                Focus::Inspector => inspector select_last,
-            });
-        }
-
-        // Deletion operations
+        },
         (
-            Message::Delete,
-            State::Connected {
+            Message::Key(key),
+            Some(Connected {
                 focus,
-                entities,
+                search,
                 entities_list,
+                ..
+            }),
+        ) if *focus == Focus::Search => match search.input.handle_key(key) {
+            InputResult::Changed => {
+                entities_list.select(0);
+                return Some(Message::SpawnComponnentsThread);
+            }
+            InputResult::Submitted => *focus = Focus::Entities,
+            InputResult::Cancelled => {
+                search.input.clear();
+                *focus = Focus::Entities;
+                return Some(Message::SpawnComponnentsThread);
+            }
+            InputResult::Ignored => return model.keymap.message(key),
+        },
+        (
+            Message::Key(key),
+            Some(Connected {
+                focus,
                 components,
                 components_list,
+                inspector,
+                locked,
                 ..
-            },
+            }),
+        ) if *focus == Focus::Edit => match inspector.edit.as_mut()?.handle_key(key) {
+            InputResult::Changed => {
+                inspector.edit_error = edit_error(
+                    &model.registry,
+                    components,
+                    model.component_options,
+                    &model.collapsed_groups,
+                    components_list,
+                    locked.as_ref(),
+                    inspector,
+                );
+            }
+            InputResult::Submitted => return Some(Message::SubmitEdit),
+            InputResult::Cancelled => {
+                inspector.edit = None;
+                *focus = Focus::Inspector;
+            }
+            InputResult::Ignored => return model.keymap.message(key),
+        },
+        (
+            Message::Key(key),
+            Some(Connected {
+                focus, inspector, ..
+            }),
+        ) if *focus == Focus::Filter => match inspector.filter.handle_key(key) {
+            // The inspector filters its fields as it renders.
+            InputResult::Changed => {}
+            InputResult::Submitted => *focus = Focus::Inspector,
+            InputResult::Cancelled => {
+                inspector.filter.clear();
+                *focus = Focus::Inspector;
+            }
+            InputResult::Ignored => return model.keymap.message(key),
+        },
+        (
+            Message::Key(key),
+            Some(Connected {
+                focus: focus @ (Focus::EntitiesPage | Focus::ComponentsPage),
+                page_input,
+                ..
+            }),
         ) => {
-            let socket = model.socket;
-            match focus {
-                Focus::Entities => {
-                    let entity = entities.remove(entities_list.selected()).id;
-                    thread::spawn(move || {
-                        let _ = brp::destroy_request(&socket, BrpDestroyParams { entity });
-                    });
+            // Only page numbers can be typed.
+            if matches!(key.code, KeyCode::Char(ch) if !ch.is_ascii_digit()) {
+                return None;
+            }
+            let result = page_input.handle_key(key);
+            match result {
+                InputResult::Changed => {}
+                InputResult::Submitted | InputResult::Cancelled => {
+                    let page = page_input.value().parse::<usize>().ok();
+                    if let Some(page) = page.filter(|_| result == InputResult::Submitted) {
+                        return Some(Message::GoToPage(page.saturating_sub(1)));
+                    }
+                    *focus = match focus {
+                        Focus::EntitiesPage => Focus::Entities,
+                        _ => Focus::Components,
+                    };
                 }
-                Focus::Components => {
-                    let entity = entities[entities_list.selected()].id;
-                    let (component, _) = components.remove(components_list.selected());
-                    thread::spawn(move || {
-                        let _ = brp::remove_request(
-                            &socket,
-                            BrpRemoveParams {
-                                entity,
-                                components: vec![component.to_owned()],
-                            },
-                        );
-                    });
+                InputResult::Ignored => return model.keymap.message(key),
+            }
+        }
+        (
+            Message::Key(key),
+            Some(Connected {
+                focus: focus @ Focus::EntityId,
+                entity_input,
+                ..
+            }),
+        ) => {
+            // Ids are typed as shown, like `12v3`, or as their bits.
+            if matches!(key.code, KeyCode::Char(ch) if !ch.is_ascii_digit() && ch != 'v') {
+                return None;
+            }
+            match entity_input.handle_key(key) {
+                InputResult::Changed => {}
+                InputResult::Submitted => {
+                    *focus = Focus::Entities;
+                    let text = entity_input.value();
+                    match text.parse() {
+                        Ok(id) => return Some(Message::GoToEntity(id)),
+                        Err(_) if text.is_empty() => {}
+                        Err(_) => {
+                            model.status = Some(Status::error(format!("{text} isn't an entity id")))
+                        }
+                    }
                 }
-                _ => {}
+                InputResult::Cancelled => *focus = Focus::Entities,
+                InputResult::Ignored => return model.keymap.message(key),
             }
         }
-        (Message::Delete, _) => {}
-
-        // Thread management
         (
-            Message::SpawnComponnentsThread,
-            State::Connected {
-                entities,
-                entities_list,
-                components_thread_quitter,
+            Message::Key(key),
+            Some(Connected {
+                focus: Focus::Palette,
+                palette: Some(palette),
                 ..
+            }),
+        ) => match palette.input().handle_key(key) {
+            InputResult::Changed => palette.error = None,
+            InputResult::Submitted => return Some(Message::CallMethod),
+            InputResult::Cancelled => return Some(Message::Dismiss),
+            InputResult::Ignored => match key.code {
+                KeyCode::Tab => palette.editing_params = !palette.editing_params,
+                KeyCode::Up => palette.browse_history(&model.call_history, true),
+                KeyCode::Down => palette.browse_history(&model.call_history, false),
+                _ => return model.keymap.message(key),
             },
+        },
+        // Just moving around the response and dismissing it are allowed while it is shown.
+        (
+            Message::Key(key),
+            Some(Connected {
+                focus: Focus::Response,
+                ..
+            }),
+        ) => {
+            return model.keymap.message(key).filter(|msg| {
+                matches!(
+                    msg,
+                    Message::MoveUp
+                        | Message::MoveDown
+                        | Message::HalfPageUp
+                        | Message::HalfPageDown
+                        | Message::Home
+                        | Message::End
+                        | Message::Dismiss
+                        | Message::Quit
+                )
+            })
+        }
+        (
+            Message::Key(key),
+            Some(Connected {
+                focus: focus @ Focus::EntityFilter,
+                entity_filter_input,
+                ..
+            }),
+        ) => match entity_filter_input.handle_key(key) {
+            InputResult::Changed => {}
+            InputResult::Submitted => return Some(Message::SetEntityFilter),
+            InputResult::Cancelled => *focus = Focus::Entities,
+            InputResult::Ignored => return model.keymap.message(key),
+        },
+        (
+            Message::Key(key),
+            Some(Connected {
+                focus,
+                view_name: Some(view_name),
+                ..
+            }),
+        ) if *focus == Focus::ViewName => match view_name.input.handle_key(key) {
+            InputResult::Changed => {}
+            InputResult::Submitted => return Some(Message::SaveView),
+            InputResult::Cancelled => *focus = view_name.return_focus,
+            InputResult::Ignored => return model.keymap.message(key),
+        },
+        (Message::Key(key), _) => return model.keymap.message(key),
+        // The UI is drawn after every message.
+        (Message::Resize, _) => {}
+        (Message::Tick, _) => {
+            if let State::Connecting { frame } = &mut model.state {
+                *frame = frame.wrapping_add(1);
+            } else if let Some(mut quitter) = model.spinner_thread_quitter.take() {
+                quitter.quit();
+            }
+        }
+
+        (Message::Search, Some(Connected { focus, .. })) => {
+            *focus = Focus::Search;
+        }
+        (Message::Search, _) => {}
+
+        (
+            Message::Filter,
+            Some(Connected {
+                focus: focus @ Focus::Inspector,
+                ..
+            }),
+        ) => {
+            *focus = Focus::Filter;
+        }
+        (Message::Filter, _) => {}
+
+        (
+            Message::TypePage,
+            Some(Connected {
+                focus: focus @ (Focus::Entities | Focus::Components),
+                page_input,
+                ..
+            }),
+        ) => {
+            page_input.clear();
+            *focus = match focus {
+                Focus::Entities => Focus::EntitiesPage,
+                _ => Focus::ComponentsPage,
+            };
+        }
+        (Message::TypePage, _) => {}
+
+        (
+            Message::TypeEntityId,
+            Some(Connected {
+                focus: focus @ Focus::Entities,
+                entity_input,
+                ..
+            }),
+        ) => {
+            entity_input.clear();
+            *focus = Focus::EntityId;
+        }
+        (Message::TypeEntityId, _) => {}
+        (
+            Message::GoToEntity(id),
+            Some(Connected {
+                focus,
+                entities,
+                entities_list,
+                search,
+                ..
+            }),
+        ) => {
+            let Some(entity) = entities.iter().map(|e| e.id).find(|e| id.matches(*e)) else {
+                model.status = Some(Status::error(format!("No entity {id} in the world")));
+                return None;
+            };
+            let mut listed = listed_entities(search, entities, &model.pins);
+            if !listed.iter().any(|n| entities[*n].id == entity) {
+                search.input.clear();
+                listed = listed_entities(search, entities, &model.pins);
+            }
+            let index = listed.iter().position(|n| entities[*n].id == entity)?;
+            entities_list.select(index);
+            *focus = Focus::Entities;
+            return Some(Message::SpawnComponnentsThread);
+        }
+        (Message::GoToEntity(_), _) => {}
+
+        (
+            Message::FilterEntities,
+            Some(Connected {
+                focus: focus @ Focus::Entities,
+                entity_filter_input,
+                ..
+            }),
+        ) => {
+            let filter = model
+                .entity_filter
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            *entity_filter_input = TextInput::with_value(filter.text());
+            *focus = Focus::EntityFilter;
+        }
+        (Message::FilterEntities, _) => {}
+        (
+            Message::SetEntityFilter,
+            Some(Connected {
+                focus: focus @ Focus::EntityFilter,
+                entity_filter_input,
+                ..
+            }),
+        ) => {
+            let registry = match &model.registry {
+                RegistryStatus::Available(registry) => Some(registry),
+                _ => None,
+            };
+            let filter = match EntityFilter::parse(entity_filter_input.value(), registry) {
+                Ok(filter) => filter,
+                Err(err) => {
+                    model.status = Some(Status::error(err));
+                    return None;
+                }
+            };
+            model.status = Some(Status::info(match filter.is_empty() {
+                true => String::from("Listing every entity"),
+                false => format!("Listing entities {}", filter.title(&model.theme)),
+            }));
+            *model
+                .entity_filter
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner) = filter;
+            *focus = Focus::Entities;
+            model.entities_waker.wake();
+        }
+        (Message::SetEntityFilter, _) => {}
+
+        // Views
+        (
+            Message::NameView,
+            Some(Connected {
+                focus: focus @ (Focus::Entities | Focus::Components | Focus::Inspector),
+                view_name,
+                ..
+            }),
+        ) => {
+            let name = model
+                .active_view
+                .and_then(|n| model.views.get(n))
+                .map(|view| view.name.clone())
+                .unwrap_or_default();
+            *view_name = Some(ViewNameInput {
+                input: TextInput::with_value(name),
+                return_focus: *focus,
+            });
+            *focus = Focus::ViewName;
+        }
+        (Message::NameView, _) => {}
+
+        (
+            Message::SaveView,
+            Some(Connected {
+                focus,
+                search,
+                inspector,
+                view_name,
+                ..
+            }),
+        ) => {
+            let ViewNameInput {
+                input,
+                return_focus,
+            } = view_name.take()?;
+            *focus = return_focus;
+            let name = input.value().trim();
+            if name.is_empty() {
+                return None;
+            }
+            let mut pins: Vec<_> = model.pins.iter().copied().collect();
+            pins.sort();
+            let view = View {
+                name: name.to_string(),
+                search: search.query().to_string(),
+                filter: inspector.filter.value().to_string(),
+                components: model.component_options,
+                pins,
+                panel: panel_index(return_focus).unwrap_or_default(),
+            };
+            let index = match model.views.iter().position(|v| v.name == view.name) {
+                Some(index) => {
+                    model.views[index] = view;
+                    index
+                }
+                None => {
+                    model.views.push(view);
+                    model.views.len() - 1
+                }
+            };
+            model.active_view = Some(index);
+            model.status = Some(match views::save(&model.views) {
+                Ok(()) => Status::info(format!("Saved view {name}")),
+                Err(err) => Status::error(format!("Failed to save views: {err}")),
+            });
+        }
+        (Message::SaveView, _) => {}
+
+        (
+            Message::NextView,
+            Some(Connected {
+                focus: Focus::Entities | Focus::Components | Focus::Inspector | Focus::Compared,
+                ..
+            }),
+        ) => {
+            if model.views.is_empty() {
+                model.status = Some(Status::error("No saved views, press S to save one"));
+                return None;
+            }
+            let index = model.active_view.map_or(0, |n| (n + 1) % model.views.len());
+            return apply_view(model, index);
+        }
+        (Message::NextView, _) => {}
+
+        // Calling methods
+        (
+            Message::OpenPalette,
+            Some(Connected {
+                focus: focus @ (Focus::Entities | Focus::Components | Focus::Inspector),
+                palette,
+                ..
+            }),
+        ) => {
+            *palette = Some(MethodPalette::new(*focus));
+            *focus = Focus::Palette;
+        }
+        (Message::OpenPalette, _) => {}
+        (
+            Message::CallMethod,
+            Some(Connected {
+                focus,
+                palette: palette_slot @ Some(_),
+                ..
+            }),
+        ) => {
+            let palette = palette_slot.as_mut()?;
+            let (call, params) = match palette.call() {
+                Ok(call) => call,
+                Err(err) => {
+                    palette.error = Some(err);
+                    return None;
+                }
+            };
+            *focus = palette.return_focus;
+            *palette_slot = None;
+            model.call_history.push(call.clone());
+            model.status = Some(Status::info(format!(
+                "Calling {}{}",
+                call.method,
+                model.theme.ellipsis()
+            )));
+
+            let socket = model.socket.clone();
+            let tx = model.message_tx.clone();
+            thread::spawn(
+                move || match brp::custom_request(&socket, &call.method, params) {
+                    Ok(value) => {
+                        let entry = LogEntry::info(format!("Called {}", call.method));
+                        let _ = tx.send(Message::Log(entry));
+                        let _ = tx.send(Message::ShowResponse {
+                            method: call.method,
+                            value,
+                        });
+                    }
+                    Err(err) => {
+                        let title = format!("Failed to call {}", call.method);
+                        let entry = LogEntry::error(format!("{title}: {err:#}"));
+                        let _ = tx.send(Message::Log(entry));
+                        let _ = tx.send(Message::ShowError(ErrorPopup::new(title, &err)));
+                    }
+                },
+            );
+        }
+        (Message::CallMethod, _) => {}
+        (
+            Message::ShowResponse { method, value },
+            Some(Connected {
+                focus, response, ..
+            }),
+        ) => {
+            // Another overlay may have been opened while waiting.
+            if matches!(focus, Focus::Picker | Focus::Palette) {
+                return None;
+            }
+            let return_focus = match response.take() {
+                Some(previous) => previous.return_focus,
+                None => *focus,
+            };
+            *response = Some(MethodResponse {
+                method,
+                value,
+                inspector: InspectorState::default(),
+                return_focus,
+            });
+            *focus = Focus::Response;
+        }
+        (Message::ShowResponse { .. }, _) => {}
+
+        (
+            Message::GoToPage(page),
+            Some(Connected {
+                focus,
+                entities_list,
+                components_list,
+                ..
+            }),
+        ) => match focus {
+            Focus::Entities | Focus::EntitiesPage => {
+                *focus = Focus::Entities;
+                entities_list.select_page(page);
+                return Some(Message::SpawnComponnentsThread);
+            }
+            Focus::Components | Focus::ComponentsPage => {
+                *focus = Focus::Components;
+                components_list.select_page(page);
+            }
+            _ => {}
+        },
+        (Message::GoToPage(_), _) => {}
+
+        // Component insertion
+        (
+            Message::AddComponent,
+            Some(Connected {
+                focus: focus @ (Focus::Entities | Focus::Components),
+                entities,
+                entities_list,
+                search,
+                picker,
+                ..
+            }),
+        ) => {
+            let entity =
+                entities[selected_entity(search, entities, entities_list, &model.pins)?].id;
+            *picker = Some(ComponentPicker::new(entity, *focus));
+            *focus = Focus::Picker;
+        }
+        (Message::AddComponent, _) => {}
+
+        (
+            Message::InsertComponent,
+            Some(Connected {
+                focus,
+                picker: picker_slot @ Some(_),
+                ..
+            }),
+        ) => {
+            let picker = picker_slot.as_mut()?;
+            let (component, value) = match &model.registry {
+                RegistryStatus::Available(registry) => {
+                    let n = *picker
+                        .search
+                        .matches(&registry.components)
+                        .get(picker.list.selected())?;
+                    let type_path = registry.components[n].type_path.clone();
+                    let value = registry.default_value(&type_path);
+                    (type_path, value)
+                }
+                // Without the registry the query is taken as the type path.
+                RegistryStatus::Unavailable if !picker.search.query().trim().is_empty() => (
+                    picker.search.query().trim().to_string(),
+                    Value::Object(Default::default()),
+                ),
+                _ => return None,
+            };
+            let entity = picker.entity;
+            *focus = picker.return_focus;
+            *picker_slot = None;
+
+            let socket = model.socket.clone();
+            let tx = model.message_tx.clone();
+            thread::spawn(move || {
+                let params = BrpInsertParams {
+                    entity,
+                    components: [(component.clone(), value)].into_iter().collect(),
+                };
+                match brp::insert_request(&socket, params) {
+                    Ok(()) => {
+                        let _ = tx.send(Message::Log(LogEntry::info(format!(
+                            "Inserted {} into entity {entity}",
+                            ShortName(&component)
+                        ))));
+                        // The components thread only fetches the components the entity had
+                        // when it was spawned.
+                        let _ = tx.send(Message::SpawnComponnentsThread);
+                    }
+                    Err(err) => {
+                        let title = format!("Failed to insert {}", ShortName(&component));
+                        let entry = LogEntry::error(format!("{title}: {err}"));
+                        let _ = tx.send(Message::Log(entry));
+                        let _ = tx.send(Message::ShowError(ErrorPopup::new(title, &err)));
+                    }
+                }
+            });
+        }
+        (Message::InsertComponent, _) => {}
+
+        (
+            Message::Duplicate,
+            Some(Connected {
+                focus: Focus::Entities,
+                entities,
+                entities_list,
+                components,
+                component_errors,
+                components_loading,
+                search,
+                ..
+            }),
+        ) => {
+            // Until the first components of the selected entity arrive, those of the last are kept.
+            if components_loading.is_some() {
+                model.status = Some(Status::error("The components are still loading"));
+                return None;
+            }
+            let original =
+                &entities[selected_entity(search, entities, entities_list, &model.pins)?];
+            let entity = original.id;
+            let parent = original.parent;
+            let (copied, hierarchy): (Vec<_>, Vec<_>) =
+                components.iter().cloned().partition(|(type_path, _)| {
+                    !brp::HIERARCHY_COMPONENTS.contains(&type_path.as_str())
+                });
+            // Components that failed to be fetched have no value to copy.
+            let mut skipped: Vec<_> = component_errors
+                .keys()
+                .map(|type_path| format!("{}, it couldn't be fetched", ShortName(type_path)))
+                .collect();
+            skipped.extend(hierarchy.iter().map(|(type_path, _)| {
+                format!("{}, it is part of the hierarchy", ShortName(type_path))
+            }));
+
+            let socket = model.socket.clone();
+            let tx = model.message_tx.clone();
+            thread::spawn(move || {
+                let log = |entry| {
+                    let _ = tx.send(Message::Log(entry));
+                };
+                let params = BrpSpawnParams {
+                    components: copied.iter().cloned().collect(),
+                };
+                let spawned = match brp::spawn_request(&socket, params) {
+                    Ok(response) => response.entity,
+                    // Any one component that can't be inserted fails the whole spawn, so they are
+                    // inserted one at a time to find and skip those.
+                    Err(_) => {
+                        let params = BrpSpawnParams {
+                            components: Default::default(),
+                        };
+                        let spawned = match brp::spawn_request(&socket, params) {
+                            Ok(response) => response.entity,
+                            Err(err) => {
+                                let title = format!("Failed to duplicate {entity}");
+                                log(LogEntry::error(format!("{title}: {err}")));
+                                let _ = tx.send(Message::ShowError(ErrorPopup::new(title, &err)));
+                                return;
+                            }
+                        };
+                        for (type_path, value) in copied {
+                            let params = BrpInsertParams {
+                                entity: spawned,
+                                components: [(type_path.clone(), value)].into_iter().collect(),
+                            };
+                            if let Err(err) = brp::insert_request(&socket, params) {
+                                skipped.push(format!("{}: {err}", ShortName(&type_path)));
+                            }
+                        }
+                        spawned
+                    }
+                };
+                if let Some(parent) = parent {
+                    let params = BrpReparentParams {
+                        entities: vec![spawned],
+                        parent: Some(parent),
+                    };
+                    if let Err(err) = brp::reparent_request(&socket, params) {
+                        log(LogEntry::error(format!(
+                            "Failed to move {spawned} under {parent}: {err}"
+                        )));
+                    }
+                }
+                for reason in skipped {
+                    log(LogEntry::error(format!("Skipped {reason}")));
+                }
+                log(LogEntry::info(format!(
+                    "Duplicated entity {entity} as {spawned}"
+                )));
+                let _ = tx.send(Message::Spawned(spawned));
+            });
+        }
+        (Message::Duplicate, _) => {}
+        (Message::Spawned(entity), _) => {
+            model.reselect = Some(entity);
+            return Some(Message::Refresh);
+        }
+
+        (Message::UpdateRegistry(registry), state) => {
+            model.registry = match registry {
+                Ok(Some(registry)) => RegistryStatus::Available(registry),
+                Ok(None) => RegistryStatus::Unavailable,
+                Err(err) => {
+                    model.registry = RegistryStatus::Failed(Instant::now());
+                    return Some(Message::Log(LogEntry::error(format!(
+                        "Failed to fetch the registry, trying again shortly: {err}"
+                    ))));
+                }
+            };
+            if let Some(Connected {
+                picker: Some(picker),
+                ..
+            }) = state
+            {
+                picker.search.invalidate();
+                picker.list.select(0);
+            }
+            // The components given with `--components` are still fetched, the app may know of
+            // them without them being in the registry.
+            if let RegistryStatus::Available(registry) = &model.registry {
+                let unknown: Vec<_> = model
+                    .fixed_components
+                    .iter()
+                    .filter(|type_path| {
+                        !registry
+                            .components
+                            .iter()
+                            .any(|component| component.type_path == **type_path)
+                    })
+                    .map(String::as_str)
+                    .collect();
+                if !unknown.is_empty() {
+                    return Some(Message::Log(LogEntry::error(format!(
+                        "Not registered as components: {}",
+                        unknown.join(", ")
+                    ))));
+                }
+            }
+        }
+
+        // Exporting
+        (Message::ExportWorld, Some(Connected { entities, .. })) => {
+            if model.export.is_some() {
+                return None;
+            }
+            let entities: Vec<_> = entities.iter().map(|meta| meta.id).collect();
+            let quitter = ThreadQuitToken::new();
+            model.export = Some(Export {
+                quitter: quitter.clone(),
+                done: 0,
+                total: entities.len(),
+            });
+            let socket = model.socket.clone();
+            let tx = model.message_tx.clone();
+            thread::spawn(move || export::export_world(tx, &socket, entities, quitter));
+        }
+        (Message::ExportWorld, _) => {}
+        (Message::ExportProgress(done), _) => {
+            if let Some(export) = &mut model.export {
+                export.done = done;
+            }
+        }
+        (Message::ExportFinished(result), _) => {
+            let total = model.export.take().map(|export| export.total);
+            let entry = match result {
+                Ok(Some(Exported { path, skipped: 0 })) => LogEntry::info(format!(
+                    "Exported {} entities to {}",
+                    total.unwrap_or_default(),
+                    path.display()
+                )),
+                Ok(Some(Exported { path, skipped })) => LogEntry::error(format!(
+                    "Exported {} entities to {}, skipping {skipped} that couldn't be fetched",
+                    total.unwrap_or_default().saturating_sub(skipped),
+                    path.display()
+                )),
+                Ok(None) => LogEntry::info("Cancelled the export"),
+                Err(err) => LogEntry::error(format!("Failed to export the world: {err}")),
+            };
+            return Some(Message::Log(entry));
+        }
+
+        // Editing
+        (
+            Message::CycleVariant { backwards },
+            Some(Connected {
+                locked,
+                focus: Focus::Inspector,
+                entities,
+                entities_list,
+                components,
+                components_list,
+                inspector,
+                search,
+                ..
+            }),
+        ) => {
+            if inspector.selected_value_type() != Some(ValueType::Enum) {
+                return None;
+            }
+            let RegistryStatus::Available(registry) = &model.registry else {
+                model.status = Some(Status::error(
+                    "Cycling variants needs the registry, which the app doesn't provide",
+                ));
+                return None;
+            };
+            let entity =
+                entities[selected_entity(search, entities, entities_list, &model.pins)?].id;
+            let index = inspected_component(
+                components,
+                components_list,
+                model.component_options,
+                &model.collapsed_groups,
+                locked.as_ref(),
+            )?;
+            let (type_path, component_value) = &mut components[index];
+            let path = inspector.selected_path()?.to_string();
+            let variants = registry.variants(type_path, &path)?;
+            if variants.is_empty() {
+                return None;
+            }
+
+            let field = inspector::value_at_path_mut(component_value, &path)?;
+            let current = match field {
+                Value::String(name) => Some(name.as_str()),
+                Value::Object(map) => map.keys().next().map(String::as_str),
+                _ => None,
+            };
+            let position = current
+                .and_then(|current| variants.iter().position(|v| *v == current))
+                .unwrap_or_default();
+            let next = if backwards {
+                (position + variants.len() - 1) % variants.len()
+            } else {
+                (position + 1) % variants.len()
+            };
+            let name = variants[next];
+            // Update the value right away so cycling again continues from the new variant.
+            let value = registry.variant_value(type_path, &path, name)?;
+            *field = value.clone();
+
+            let list: Vec<_> = variants
+                .iter()
+                .map(|v| {
+                    if *v == name {
+                        format!("[{v}]")
+                    } else {
+                        v.to_string()
+                    }
+                })
+                .collect();
+            model.status = Some(Status::info(list.join("  ")));
+
+            let change = FieldChange {
+                entity,
+                component: type_path.clone(),
+                reflect_path: reflect_path(&model.registry, type_path, &path),
+                value,
+                component_value: component_value.clone(),
+            };
+            spawn_set_field(
+                model.socket.clone(),
+                model.message_tx.clone(),
+                change,
+                format!("Set {}{path} to {name}", ShortName(type_path)),
+            );
+        }
+        (Message::CycleVariant { .. }, _) => {}
+
+        (
+            Message::Toggle,
+            Some(Connected {
+                locked,
+                focus: Focus::Inspector,
+                entities,
+                entities_list,
+                components,
+                components_list,
+                inspector,
+                search,
+                ..
+            }),
+        ) => {
+            let entity =
+                entities[selected_entity(search, entities, entities_list, &model.pins)?].id;
+            let index = inspected_component(
+                components,
+                components_list,
+                model.component_options,
+                &model.collapsed_groups,
+                locked.as_ref(),
+            )?;
+            let (type_path, component_value) = &mut components[index];
+            let path = inspector.selected_path()?.to_string();
+            let Value::Bool(field) = inspector::value_at_path_mut(component_value, &path)? else {
+                return None;
+            };
+            *field = !*field;
+            let value = Value::Bool(*field);
+
+            let change = FieldChange {
+                entity,
+                component: type_path.clone(),
+                reflect_path: reflect_path(&model.registry, type_path, &path),
+                value: value.clone(),
+                component_value: component_value.clone(),
+            };
+            spawn_set_field(
+                model.socket.clone(),
+                model.message_tx.clone(),
+                change,
+                format!("Set {}{path} to {value}", ShortName(type_path)),
+            );
+        }
+        (Message::Toggle, _) => {}
+
+        (
+            Message::Edit,
+            Some(Connected {
+                locked,
+                focus: focus @ Focus::Inspector,
+                components,
+                components_list,
+                inspector,
+                ..
+            }),
+        ) => {
+            let index = inspected_component(
+                components,
+                components_list,
+                model.component_options,
+                &model.collapsed_groups,
+                locked.as_ref(),
+            )?;
+            let text =
+                match inspector::value_at_path(&components[index].1, inspector.selected_path()?)? {
+                    Value::Number(n) => n.to_string(),
+                    Value::String(s) => s.clone(),
+                    _ => return None,
+                };
+            inspector.edit = Some(TextInput::with_value(text));
+            inspector.edit_error = edit_error(
+                &model.registry,
+                components,
+                model.component_options,
+                &model.collapsed_groups,
+                components_list,
+                locked.as_ref(),
+                inspector,
+            );
+            *focus = Focus::Edit;
+        }
+        (Message::Edit, _) => {}
+
+        (
+            Message::SubmitEdit,
+            Some(Connected {
+                locked,
+                focus: focus @ Focus::Edit,
+                entities,
+                entities_list,
+                components,
+                components_list,
+                inspector,
+                search,
+                ..
+            }),
+        ) => {
+            let text = inspector.edit.as_ref()?.value().to_string();
+            let entity =
+                entities[selected_entity(search, entities, entities_list, &model.pins)?].id;
+            let index = inspected_component(
+                components,
+                components_list,
+                model.component_options,
+                &model.collapsed_groups,
+                locked.as_ref(),
+            )?;
+            let (type_path, component_value) = &mut components[index];
+            let path = inspector.selected_path()?.to_string();
+            let field = inspector::value_at_path_mut(component_value, &path)?;
+            // Enter does nothing until the error shown next to the value is fixed.
+            let value = match parse_edit(&model.registry, type_path, &path, field, &text) {
+                Ok(value) => value,
+                Err(err) => {
+                    inspector.edit_error = Some(err.to_string());
+                    return None;
+                }
+            };
+            *field = value.clone();
+            inspector.edit = None;
+            *focus = Focus::Inspector;
+
+            let change = FieldChange {
+                entity,
+                component: type_path.clone(),
+                reflect_path: reflect_path(&model.registry, type_path, &path),
+                value: value.clone(),
+                component_value: component_value.clone(),
+            };
+            spawn_set_field(
+                model.socket.clone(),
+                model.message_tx.clone(),
+                change,
+                format!("Set {}{path} to {value}", ShortName(type_path)),
+            );
+        }
+        (Message::SubmitEdit, _) => {}
+
+        // Clipboard
+        (
+            Message::Copy,
+            Some(Connected {
+                locked,
+                focus: Focus::Inspector,
+                components,
+                components_list,
+                inspector,
+                ..
+            }),
+        ) => {
+            let index = inspected_component(
+                components,
+                components_list,
+                model.component_options,
+                &model.collapsed_groups,
+                locked.as_ref(),
+            )?;
+            let (type_path, component_value) = &components[index];
+            let path = inspector.selected_path()?;
+            let value = inspector::value_at_path(component_value, path)?;
+            let text = serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string());
+            model.status = Some(match model.clipboard.copy(text) {
+                Ok(()) => Status::info(format!("Copied {}{path}", ShortName(type_path))),
+                Err(err) => Status::error(format!("Failed to copy: {err}")),
+            });
+        }
+        (
+            Message::Copy,
+            Some(Connected {
+                focus: Focus::Entities,
+                entities,
+                entities_list,
+                components,
+                components_loading,
+                search,
+                ..
+            }),
+        ) => {
+            if components_loading.is_some() {
+                model.status = Some(Status::error("The components are still loading"));
+                return None;
+            }
+            let entity =
+                entities[selected_entity(search, entities, entities_list, &model.pins)?].id;
+            let params = BrpGetParams {
+                entity,
+                components: components
+                    .iter()
+                    .map(|(type_path, _)| type_path.clone())
+                    .collect(),
+                strict: model.strict_components,
+            };
+            let copied = brp::get_command(&model.socket, params)
+                .and_then(|command| model.clipboard.copy(command));
+            model.status = Some(match copied {
+                Ok(()) => Status::info(format!("Copied a command to get {entity}")),
+                Err(err) => Status::error(format!("Failed to copy: {err}")),
+            });
+        }
+        (Message::Copy, _) => {}
+
+        (
+            Message::CopyPath,
+            Some(Connected {
+                focus: Focus::Inspector,
+                inspector,
+                ..
+            }),
+        ) => {
+            let path = inspector.selected_path()?.trim_start_matches('.');
+            if path.is_empty() {
+                return None;
+            }
+            model.status = Some(match model.clipboard.copy(path.to_string()) {
+                Ok(()) => Status::info(format!("Copied path {path}")),
+                Err(err) => Status::error(format!("Failed to copy: {err}")),
+            });
+        }
+        (Message::CopyPath, _) => {}
+        (
+            Message::FollowEntity,
+            Some(Connected {
+                locked,
+                focus: Focus::Inspector,
+                components,
+                components_list,
+                inspector,
+                ..
+            }),
+        ) => {
+            if inspector.selected_value_type() != Some(ValueType::Entity) {
+                return None;
+            }
+            let index = inspected_component(
+                components,
+                components_list,
+                model.component_options,
+                &model.collapsed_groups,
+                locked.as_ref(),
+            )?;
+            let bits = inspector::value_at_path(&components[index].1, inspector.selected_path()?)?
+                .as_u64()?;
+            let Ok(entity) = Entity::try_from_bits(bits) else {
+                model.status = Some(Status::error(format!("{bits} isn't an entity")));
+                return None;
+            };
+            return Some(Message::GoToEntity(EntityId::from(entity)));
+        }
+        (Message::FollowEntity, _) => {}
+
+        // Collapsing
+        (
+            Message::ToggleCollapse,
+            Some(Connected {
+                locked,
+                focus: Focus::Inspector,
+                components,
+                components_list,
+                inspector,
+                ..
+            }),
+        ) => {
+            let index = inspected_component(
+                components,
+                components_list,
+                model.component_options,
+                &model.collapsed_groups,
+                locked.as_ref(),
+            )?;
+            let (type_path, component_value) = &components[index];
+            let path = inspector.selected_path()?;
+            if !matches!(
+                inspector::value_at_path(component_value, path)?,
+                Value::Object(_) | Value::Array(_)
+            ) {
+                return None;
+            }
+            let collapsed = model.collapsed.entry(type_path.clone()).or_default();
+            if !collapsed.remove(path) {
+                collapsed.insert(path.to_string());
+            }
+        }
+        (Message::ToggleCollapse, _) => {}
+
+        (Message::ExpandAll, _) => model.collapsed.clear(),
+        (
+            Message::ResetView,
+            Some(Connected {
+                focus:
+                    focus @ (Focus::Entities | Focus::Components | Focus::Inspector | Focus::Compared),
+                entities_list,
+                components_list,
+                inspector,
+                compared_inspector,
+                search,
+                ..
+            }),
+        ) => {
+            *focus = Focus::Entities;
+            search.input.clear();
+            entities_list.select(0);
+            components_list.select(0);
+            *inspector = InspectorState::default();
+            *compared_inspector = InspectorState::default();
+            model.collapsed.clear();
+            let mut filter = model
+                .entity_filter
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if !filter.is_empty() {
+                *filter = EntityFilter::default();
+                model.entities_waker.wake();
+            }
+            model.status = Some(Status::info("View reset"));
+            return Some(Message::SpawnComponnentsThread);
+        }
+        (Message::ResetView, _) => {}
+
+        // Layout
+        (Message::ResizePanel { grow }, Some(Connected { focus, .. })) => {
+            let index = panel_index(*focus)?;
+            let ratio = &mut model.panel_ratios[index];
+            *ratio = match grow {
+                true => (*ratio + 1).min(MAX_PANEL_RATIO),
+                false => ratio.saturating_sub(1).max(1),
+            };
+        }
+        (Message::ResizePanel { .. }, _) => {}
+
+        (Message::ToggleMaximize, Some(Connected { focus, .. })) => {
+            let index = panel_index(*focus)?;
+            model.maximized = match model.maximized {
+                Some(panel) if panel_index(panel) == Some(index) => None,
+                _ => Some(*focus),
+            };
+        }
+        (Message::ToggleMaximize, _) => {}
+
+        (
+            Message::ToggleCompare,
+            Some(Connected {
+                focus: focus @ (Focus::Components | Focus::Inspector | Focus::Compared),
+                components,
+                components_list,
+                compared,
+                ..
+            }),
+        ) => {
+            if compared.take().is_some() {
+                if *focus == Focus::Compared {
+                    *focus = Focus::Inspector;
+                }
+                return None;
+            }
+            let rows = components::component_rows(
+                components,
+                model.component_options,
+                &model.collapsed_groups,
+            );
+            let index = rows
+                .get(components_list.selected())
+                .and_then(ComponentRow::component)?;
+            *compared = Some(components[index].0.clone());
+        }
+        (Message::ToggleCompare, _) => {}
+
+        (
+            Message::ToggleLock,
+            Some(Connected {
+                focus: Focus::Components | Focus::Inspector,
+                components,
+                components_list,
+                locked,
+                ..
+            }),
+        ) => {
+            if let Some(type_path) = locked.take() {
+                model.status = Some(Status::info(format!("Unlocked {}", ShortName(&type_path))));
+                return None;
+            }
+            let rows = components::component_rows(
+                components,
+                model.component_options,
+                &model.collapsed_groups,
+            );
+            let index = rows
+                .get(components_list.selected())
+                .and_then(ComponentRow::component)?;
+            let type_path = components[index].0.clone();
+            model.status = Some(Status::info(format!(
+                "Locked the inspector to {}",
+                ShortName(&type_path)
+            )));
+            *locked = Some(type_path);
+        }
+        (Message::ToggleLock, _) => {}
+
+        (
+            Message::Paste,
+            Some(Connected {
+                locked,
+                focus: Focus::Inspector,
+                entities,
+                entities_list,
+                components,
+                components_list,
+                inspector,
+                search,
+                ..
+            }),
+        ) => {
+            let entity =
+                entities[selected_entity(search, entities, entities_list, &model.pins)?].id;
+            let index = inspected_component(
+                components,
+                components_list,
+                model.component_options,
+                &model.collapsed_groups,
+                locked.as_ref(),
+            )?;
+            let (type_path, component_value) = &mut components[index];
+            let path = inspector.selected_path()?.to_string();
+            let target = inspector::value_at_path_mut(component_value, &path)?;
+            let value = match read_pasted_value(
+                &mut model.clipboard,
+                &model.registry,
+                type_path,
+                &path,
+                target,
+            ) {
+                Ok(value) => value,
+                Err(err) => {
+                    model.popup = Some(ErrorPopup::new("Failed to paste", &err));
+                    return None;
+                }
+            };
+            *target = value.clone();
+
+            let change = FieldChange {
+                entity,
+                component: type_path.clone(),
+                reflect_path: reflect_path(&model.registry, type_path, &path),
+                value,
+                component_value: component_value.clone(),
+            };
+            spawn_set_field(
+                model.socket.clone(),
+                model.message_tx.clone(),
+                change,
+                format!("Pasted into {}{path}", ShortName(type_path)),
+            );
+        }
+        (Message::Paste, _) => {}
+        (
+            Message::OpenEditor,
+            Some(Connected {
+                locked,
+                focus: Focus::Inspector,
+                entities,
+                entities_list,
+                components,
+                components_list,
+                inspector,
+                search,
+                ..
+            }),
+        ) => {
+            let entity =
+                entities[selected_entity(search, entities, entities_list, &model.pins)?].id;
+            let index = inspected_component(
+                components,
+                components_list,
+                model.component_options,
+                &model.collapsed_groups,
+                locked.as_ref(),
+            )?;
+            let (type_path, component_value) = &components[index];
+            let path = inspector.selected_path()?.to_string();
+            let value = inspector::value_at_path(component_value, &path)?;
+            model.external_edit = Some(ExternalEdit {
+                entity,
+                component: type_path.clone(),
+                path,
+                text: serde_json::to_string_pretty(value).ok()?,
+            });
+        }
+        (Message::OpenEditor, _) => {}
+        (Message::ExternalEditFinished(_, Err(err)), _) => {
+            model.popup = Some(ErrorPopup::new("Failed to edit", &anyhow::Error::msg(err)));
+        }
+        (
+            Message::ExternalEditFinished(edit, Ok(text)),
+            Some(Connected {
+                entities,
+                entities_list,
+                components,
+                search,
+                ..
+            }),
+        ) => {
+            if text.trim() == edit.text.trim() {
+                model.status = Some(Status::info("Nothing changed"));
+                return None;
+            }
+            let value: Value = match serde_json::from_str(&text) {
+                Ok(value) => value,
+                Err(err) => {
+                    let err = anyhow::Error::new(err).context("The edited value isn't valid JSON");
+                    model.popup = Some(ErrorPopup::new("Failed to edit", &err));
+                    return None;
+                }
+            };
+            // The components are those of the selected entity, which may have changed.
+            let selected = selected_entity(search, entities, entities_list, &model.pins);
+            let component = components
+                .iter_mut()
+                .find(|(type_path, _)| *type_path == edit.component)
+                .filter(|_| selected.map(|n| entities[n].id) == Some(edit.entity));
+            let Some((type_path, component_value)) = component else {
+                model.status = Some(Status::error(format!(
+                    "{} is no longer on the entity",
+                    ShortName(&edit.component)
+                )));
+                return None;
+            };
+            let target = inspector::value_at_path_mut(component_value, &edit.path)?;
+            if let Err(err) =
+                check_replacement(&model.registry, type_path, &edit.path, target, &value)
+            {
+                model.popup = Some(ErrorPopup::new("Failed to edit", &err));
+                return None;
+            }
+            *target = value.clone();
+
+            let change = FieldChange {
+                entity: edit.entity,
+                component: type_path.clone(),
+                reflect_path: reflect_path(&model.registry, type_path, &edit.path),
+                value,
+                component_value: component_value.clone(),
+            };
+            spawn_set_field(
+                model.socket.clone(),
+                model.message_tx.clone(),
+                change,
+                format!("Edited {}{}", ShortName(type_path), edit.path),
+            );
+        }
+        (Message::ExternalEditFinished(..), _) => {}
+
+        // Navigation between panels
+        (Message::MoveLeft, Some(Connected { focus, .. })) => {
+            *focus = match *focus {
+                Focus::Components => Focus::Entities,
+                Focus::Inspector => Focus::Components,
+                Focus::Compared => Focus::Inspector,
+                _ => *focus,
+            };
+        }
+
+        (
+            Message::MoveRight,
+            Some(Connected {
+                focus,
+                components,
+                compared,
+                ..
+            }),
+        ) => {
+            let has_rows = !components::component_rows(
+                components,
+                model.component_options,
+                &model.collapsed_groups,
+            )
+            .is_empty();
+            // The compared component can only be focused while the entity has it.
+            let has_compared = compared
+                .as_ref()
+                .is_some_and(|compared| components.iter().any(|(path, _)| path == compared));
+            *focus = match *focus {
+                Focus::Entities if has_rows => Focus::Components,
+                Focus::Components => Focus::Inspector,
+                Focus::Inspector if has_compared => Focus::Compared,
+                _ => *focus,
+            };
+        }
+
+        (Message::MoveLeft | Message::MoveRight, _) => {}
+
+        (
+            Message::FocusPanel(panel),
+            Some(Connected {
+                focus, components, ..
+            }),
+        ) => {
+            let has_rows = !components::component_rows(
+                components,
+                model.component_options,
+                &model.collapsed_groups,
+            )
+            .is_empty();
+            if panel == Focus::Entities || has_rows {
+                *focus = panel;
+            }
+        }
+        (Message::FocusPanel(_), _) => {}
+
+        // Movement within panels
+        (
+            msg @ (Message::MoveUp
+            | Message::MoveDown
+            | Message::HalfPageUp
+            | Message::HalfPageDown
+            | Message::Home
+            | Message::End),
+            _,
+        ) if model.diff.is_some() => {
+            let diff = model.diff.as_mut()?;
+            match msg {
+                Message::MoveUp => diff.scroll_up(),
+                Message::MoveDown => diff.scroll_down(),
+                Message::HalfPageUp => diff.scroll_up_half_page(),
+                Message::HalfPageDown => diff.scroll_down_half_page(),
+                Message::Home => diff.scroll_to_top(),
+                _ => diff.scroll_to_bottom(),
+            }
+        }
+        (
+            msg @ (Message::MoveUp
+            | Message::MoveDown
+            | Message::HalfPageUp
+            | Message::HalfPageDown
+            | Message::Home
+            | Message::End),
+            Some(Connected {
+                focus: Focus::Response,
+                response: Some(response),
+                ..
+            }),
+        ) => {
+            let inspector = &mut response.inspector;
+            match msg {
+                Message::MoveUp => inspector.select_previous(),
+                Message::MoveDown => inspector.select_next(),
+                Message::HalfPageUp => inspector.select_previous_half_page(),
+                Message::HalfPageDown => inspector.select_next_half_page(),
+                Message::Home => inspector.select_first(),
+                _ => inspector.select_last(),
+            }
+        }
+        (Message::MoveUp, state) => {
+            handle_movement!(state, {
+                Focus::Entities => entities_list select_previous => Message::SpawnComponnentsThread,
+                Focus::Components => components_list select_previous,
+                Focus::Inspector | Focus::Filter => inspector select_previous,
+                Focus::Compared => compared_inspector select_previous,
+            });
+        }
+
+        (Message::MoveDown, state) => {
+            handle_movement!(state, {
+                Focus::Entities => entities_list select_next => Message::SpawnComponnentsThread,
+                Focus::Components => components_list select_next,
+                Focus::Inspector | Focus::Filter => inspector select_next,
+                Focus::Compared => compared_inspector select_next,
+            });
+        }
+
+        (Message::PageUp, state) => {
+            handle_movement!(state, {
+                Focus::Entities => entities_list select_previous_page => Message::SpawnComponnentsThread,
+                Focus::Components => components_list select_previous_page,
+            });
+        }
+
+        (Message::PageDown, state) => {
+            handle_movement!(state, {
+                Focus::Entities => entities_list select_next_page => Message::SpawnComponnentsThread,
+                Focus::Components => components_list select_next_page,
+            });
+        }
+
+        (Message::HalfPageUp, state) => {
+            handle_movement!(state, {
+                Focus::Inspector => inspector select_previous_half_page,
+                Focus::Compared => compared_inspector select_previous_half_page,
+            });
+        }
+
+        (Message::HalfPageDown, state) => {
+            handle_movement!(state, {
+                Focus::Inspector => inspector select_next_half_page,
+                Focus::Compared => compared_inspector select_next_half_page,
+            });
+        }
+
+        (Message::Home, state) => {
+            handle_movement!(state, {
+                Focus::Entities => entities_list select_first,
+                Focus::Components => components_list select_first,
+                Focus::Inspector => inspector select_first,
+                Focus::Compared => compared_inspector select_first,
+            });
+        }
+
+        (Message::End, state) => {
+            handle_movement!(state, {
+                Focus::Entities => entities_list select_last,
+                Focus::Components => components_list select_last,
+                Focus::Inspector => inspector select_last,
+                Focus::Compared => compared_inspector select_last,
+            });
+        }
+
+        // Deletion operations
+        (
+            Message::Delete,
+            Some(Connected {
+                focus,
+                entities,
+                entities_list,
+                components,
+                components_list,
+                search,
+                ..
+            }),
+        ) => {
+            let socket = model.socket.clone();
+            let tx = model.message_tx.clone();
+            let selected_entity = selected_entity(search, entities, entities_list, &model.pins)?;
+            match focus {
+                Focus::Entities => {
+                    let entity = entities.remove(selected_entity).id;
+                    search.invalidate();
+                    thread::spawn(move || {
+                        match brp::destroy_request(&socket, BrpDestroyParams { entity }) {
+                            Ok(()) => {
+                                let _ = tx.send(Message::Log(LogEntry::info(format!(
+                                    "Despawned entity {entity}"
+                                ))));
+                                let _ = tx.send(Message::Refresh);
+                            }
+                            Err(err) => {
+                                let title = format!("Failed to despawn {entity}");
+                                let entry = LogEntry::error(format!("{title}: {err}"));
+                                let _ = tx.send(Message::Log(entry));
+                                let _ = tx.send(Message::ShowError(ErrorPopup::new(title, &err)));
+                            }
+                        }
+                    });
+                }
+                Focus::Components => {
+                    let rows = components::component_rows(
+                        components,
+                        model.component_options,
+                        &model.collapsed_groups,
+                    );
+                    let index = rows
+                        .get(components_list.selected())
+                        .and_then(ComponentRow::component)?;
+                    let entity = entities[selected_entity].id;
+                    let (component, _) = components.remove(index);
+                    thread::spawn(move || {
+                        let params = BrpRemoveParams {
+                            entity,
+                            components: vec![component.to_owned()],
+                        };
+                        match brp::remove_request(&socket, params) {
+                            Ok(()) => {
+                                let _ = tx.send(Message::Log(LogEntry::info(format!(
+                                    "Removed {} from entity {entity}",
+                                    ShortName(&component)
+                                ))));
+                            }
+                            Err(err) => {
+                                let title = format!("Failed to remove {}", ShortName(&component));
+                                let entry = LogEntry::error(format!("{title}: {err}"));
+                                let _ = tx.send(Message::Log(entry));
+                                let _ = tx.send(Message::ShowError(ErrorPopup::new(title, &err)));
+                            }
+                        }
+                    });
+                }
+                _ => {}
+            }
+        }
+        (Message::Delete, _) => {}
+
+        // Hierarchy operations
+        (
+            Message::Reparent,
+            Some(Connected {
+                focus: Focus::Entities,
+                entities,
+                entities_list,
+                reparenting,
+                search,
+                ..
+            }),
+        ) => {
+            let target =
+                entities[selected_entity(search, entities, entities_list, &model.pins)?].id;
+            let Some(entity) = reparenting.take() else {
+                *reparenting = Some(target);
+                model.status = Some(Status::info(format!(
+                    "Select the new parent of {target} and press p again, or itself to unparent"
+                )));
+                return None;
+            };
+            // Selecting the picked up entity again moves it to the root.
+            let parent = if target == entity {
+                None
+            } else if brp::is_descendant(entities, target, entity) {
+                model.status = Some(Status::error(format!(
+                    "Can't parent {entity} to its own descendant {target}"
+                )));
+                return None;
+            } else {
+                Some(target)
+            };
+            let socket = model.socket.clone();
+            let tx = model.message_tx.clone();
+            thread::spawn(move || {
+                let params = BrpReparentParams {
+                    entities: vec![entity],
+                    parent,
+                };
+                let entry = match (brp::reparent_request(&socket, params), parent) {
+                    (Ok(()), Some(parent)) => {
+                        LogEntry::info(format!("Moved {entity} under {parent}"))
+                    }
+                    (Ok(()), None) => LogEntry::info(format!("Moved {entity} to the root")),
+                    (Err(err), _) => LogEntry::error(format!("Failed to reparent {entity}: {err}")),
+                };
+                let _ = tx.send(Message::Log(entry));
+            });
+        }
+        (Message::Reparent, _) => {}
+
+        // Display options
+        (Message::ToggleGrouping, _) => {
+            let mut options = model.component_options;
+            options.grouped = !options.grouped;
+            set_component_options(
+                &mut model.state,
+                &mut model.component_options,
+                options,
+                &model.collapsed_groups,
+            );
+        }
+        (
+            Message::ToggleCollapseGroup,
+            Some(Connected {
+                focus: Focus::Components,
+                components,
+                components_list,
+                ..
+            }),
+        ) => {
+            let options = model.component_options;
+            if !options.grouped {
+                model.status = Some(Status::error(
+                    "Components aren't grouped, press g to group them",
+                ));
+                return None;
+            }
+            let rows = components::component_rows(components, options, &model.collapsed_groups);
+            let group = match rows.get(components_list.selected())? {
+                ComponentRow::Component(n) => {
+                    let group = components::module_prefix(&components[*n].0).to_string();
+                    model.collapsed_groups.insert(group.clone());
+                    group
+                }
+                ComponentRow::Collapsed { group, .. } => {
+                    model.collapsed_groups.remove(group);
+                    group.clone()
+                }
+                ComponentRow::Group(_) => return None,
+            };
+            // The cursor stays on the group, on its header once collapsed or its first component
+            // once expanded.
+            let rows = components::component_rows(components, options, &model.collapsed_groups);
+            if let Some(row) = rows.iter().position(|row| match row {
+                ComponentRow::Collapsed { group: other, .. } => *other == group,
+                ComponentRow::Component(n) => components::module_prefix(&components[*n].0) == group,
+                ComponentRow::Group(_) => false,
+            }) {
+                components_list.select(row);
+            }
+        }
+        (Message::ToggleCollapseGroup, _) => {}
+        (Message::ToggleMarkers, _) => {
+            let mut options = model.component_options;
+            options.hide_markers = !options.hide_markers;
+            set_component_options(
+                &mut model.state,
+                &mut model.component_options,
+                options,
+                &model.collapsed_groups,
+            );
+        }
+        (Message::ToggleFullPaths, _) => {
+            let mut options = model.component_options;
+            options.full_paths = !options.full_paths;
+            set_component_options(
+                &mut model.state,
+                &mut model.component_options,
+                options,
+                &model.collapsed_groups,
+            );
+        }
+        (Message::ToggleRecentFirst, _) => {
+            let mut options = model.component_options;
+            options.recent_first = !options.recent_first;
+            set_component_options(
+                &mut model.state,
+                &mut model.component_options,
+                options,
+                &model.collapsed_groups,
+            );
+        }
+        (Message::ToggleSwatches, _) => {
+            model.color_swatches = !model.color_swatches;
+        }
+        (Message::ToggleSortFields, _) => {
+            model.sort_fields = !model.sort_fields;
+        }
+        (
+            Message::ToggleFreeze,
+            Some(Connected {
+                focus: Focus::Components | Focus::Inspector,
+                components,
+                components_list,
+                ..
+            }),
+        ) => {
+            let rows = components::component_rows(
+                components,
+                model.component_options,
+                &model.collapsed_groups,
+            );
+            let index = rows
+                .get(components_list.selected())
+                .and_then(ComponentRow::component)?;
+            let type_path = &components[index].0;
+            let name = ShortName(type_path);
+            let mut frozen = model
+                .frozen_components
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let text = if frozen.remove(type_path) {
+                format!("{name} updates live again")
+            } else {
+                frozen.insert(type_path.clone());
+                format!("Froze {name} at its current value")
+            };
+            model.status = Some(Status::info(text));
+        }
+        (Message::ToggleFreeze, _) => {}
+        (Message::ToggleStrict, _) => {
+            model.strict_components = !model.strict_components;
+            model.status = Some(Status::info(match model.strict_components {
+                true => "Fetching components strictly, any that fail are reported",
+                false => "Fetching components leniently, any that fail are listed",
+            }));
+            return Some(Message::SpawnComponnentsThread);
+        }
+
+        // Thread management
+        (
+            Message::SpawnComponnentsThread,
+            Some(Connected {
+                entities,
+                entities_list,
+                components,
+                component_changes,
+                components_thread_quitter,
+                components_loading,
+                search,
+                ..
+            }),
         ) => {
             if let Some(quitter) = components_thread_quitter {
                 quitter.quit();
             }
+            component_changes.reset();
+            let Some(selected) = selected_entity(search, entities, entities_list, &model.pins)
+            else {
+                *components_loading = None;
+                components.clear();
+                ensure_valid_focus(&mut model.state, model.component_options);
+                return None;
+            };
+            // The components are of the last entity until the first of this one arrive.
+            *components_loading = Some(entities[selected].id);
             let tx = model.message_tx.clone();
-            let socket = model.socket;
-            let entity = entities[entities_list.selected()].id;
+            let socket = model.socket.clone();
+            let entity = entities[selected].id;
+            let fixed_components = model.fixed_components.clone();
+            let frozen_components = model.frozen_components.clone();
+            let strict = model.strict_components;
             let quitter = ThreadQuitToken::new();
             *components_thread_quitter = Some(quitter.clone());
-            thread::spawn(move || handle_components_querying(tx, &socket, entity, quitter));
+            thread::spawn(move || {
+                handle_components_querying(
+                    tx,
+                    &socket,
+                    entity,
+                    fixed_components,
+                    frozen_components,
+                    strict,
+                    quitter,
+                )
+            });
         }
         (Message::SpawnComponnentsThread, _) => {}
 
         // State updates
-        (Message::UpdateEntities(new_entities), State::Connected { entities, .. }) => {
-            *entities = new_entities;
+        (
+            Message::UpdateEntities(new_entities),
+            Some(Connected {
+                entities,
+                entities_list,
+                search,
+                ..
+            }),
+        ) => {
+            fetch_registry(&mut model.registry, &model.socket, &model.message_tx);
+            // Most updates of a still world change nothing, which leaves the cached search intact.
+            if *entities != new_entities {
+                // Keep the same entity selected rather than the same row as entities are spawned
+                // and despawned above it.
+                let selected = selected_entity(search, entities, entities_list, &model.pins)
+                    .map(|n| entities[n].id);
+                *entities = new_entities;
+                search.invalidate();
+                let listed = listed_entities(search, entities, &model.pins);
+                if let Some(index) = listed
+                    .iter()
+                    .position(|n| Some(entities[*n].id) == selected)
+                {
+                    entities_list.select(index);
+                }
+            }
+            let entity = model
+                .reselect
+                .filter(|entity| entities.iter().any(|e| e.id == *entity))?;
+            model.reselect = None;
+            return Some(Message::GoToEntity(EntityId::from(entity)));
         }
         (Message::UpdateEntities(new_entities), _) => {
-            model.state = State::Connected {
+            model.diagnosis = None;
+            model.state = State::Connected(Box::new(Connected {
                 focus: Focus::default(),
                 entities: new_entities,
                 entities_list: PaginatedListState::default(),
                 components: Vec::new(),
                 components_list: PaginatedListState::default(),
+                component_changes: ComponentChanges::default(),
+                component_errors: ComponentErrors::new(),
                 components_thread_quitter: None,
+                components_loading: None,
                 inspector: InspectorState::default(),
-            };
+                reparenting: None,
+                search: Search::default(),
+                picker: None,
+                palette: None,
+                response: None,
+                compared: None,
+                compared_inspector: InspectorState::default(),
+                locked: None,
+                page_input: TextInput::default(),
+                entity_input: TextInput::default(),
+                entity_filter_input: TextInput::default(),
+                view_name: None,
+            }));
+            fetch_registry(&mut model.registry, &model.socket, &model.message_tx);
+            if let (
+                Some(entity),
+                Some(Connected {
+                    entities,
+                    entities_list,
+                    ..
+                }),
+            ) = (model.reselect.take(), model.state.connected_mut())
+            {
+                let listed = listed_entities(&mut Search::default(), entities, &model.pins);
+                if let Some(index) = listed.iter().position(|n| entities[*n].id == entity) {
+                    entities_list.select(index);
+                }
+            }
             return Some(Message::SpawnComponnentsThread);
         }
 
-        (Message::UpdateComponents(new_components), State::Connected { components, .. }) => {
+        (
+            Message::UpdateComponents(mut new_components, errors),
+            Some(Connected {
+                entities,
+                entities_list,
+                components,
+                components_list,
+                component_changes,
+                component_errors,
+                components_loading,
+                search,
+                ..
+            }),
+        ) => {
+            *components_loading = None;
+            if model.peeking {
+                if let Some(n) = selected_entity(search, entities, entities_list, &model.pins) {
+                    model.peek_cache.insert(entities[n].id, &new_components);
+                }
+            }
+            *component_errors = errors;
+            let options = model.component_options;
+            component_changes.update(components, &new_components);
+            components::sort(&mut new_components, component_changes, options);
+            // Components move around as they change, so the selected one is followed.
+            if options.recent_first {
+                let selected =
+                    components::component_rows(components, options, &model.collapsed_groups)
+                        .get(components_list.selected())
+                        .and_then(ComponentRow::component)
+                        .map(|n| &components[n].0);
+                if let Some(row) = selected
+                    .and_then(|type_path| {
+                        new_components
+                            .iter()
+                            .position(|(new_path, _)| new_path == type_path)
+                    })
+                    .and_then(|n| {
+                        let new_rows = components::component_rows(
+                            &new_components,
+                            options,
+                            &model.collapsed_groups,
+                        );
+                        components::row_of_component(&new_rows, n)
+                    })
+                {
+                    components_list.select(row);
+                }
+            }
             *components = new_components;
+            ensure_valid_focus(&mut model.state, model.component_options);
         }
-        (Message::UpdateComponents(_), _) => {}
+        (Message::UpdateComponents(..), _) => {}
+        (
+            Message::ComponentsLoading(entity),
+            Some(Connected {
+                components_loading, ..
+            }),
+        ) => *components_loading = entity,
+        (Message::ComponentsLoading(_), _) => {}
 
         // State transitions
-        (Message::CommunicationFailed, _) => {
+        (Message::CommunicationFailed, state) => {
+            if let Some(Connected {
+                entities,
+                entities_list,
+                search,
+                ..
+            }) = state
+            {
+                model.reselect = selected_entity(search, entities, entities_list, &model.pins)
+                    .map(|n| entities[n].id);
+            }
             model.state = State::Disconnected;
+            // The app may have been replaced by one with different types.
+            if !matches!(model.registry, RegistryStatus::Loading) {
+                model.registry = RegistryStatus::Unknown;
+            }
+            model.collapsed.clear();
+        }
+        (Message::Diagnosed(diagnosis), _) => model.diagnosis = Some(diagnosis),
+        (Message::Retry, _) => {
+            if matches!(model.state, State::Disconnected) {
+                model.state = State::Connecting { frame: 0 };
+                spawn_spinner_thread(model);
+                model.entities_waker.wake();
+            }
+        }
+        (Message::Refresh, Some(Connected { .. })) => {
+            model.entities_waker.wake();
+            // Respawning the thread lists the components again as well as fetching them.
+            return Some(Message::SpawnComponnentsThread);
+        }
+        (Message::Refresh, _) => {}
+
+        // Popups
+        (Message::ShowError(popup), _) => {
+            model.popup = Some(popup);
+        }
+        (Message::Log(entry), _) => {
+            model.status = Some(Status {
+                text: entry.text.clone(),
+                error: entry.error,
+            });
+            model.log.push(entry);
+        }
+        (Message::CopyKeybinds, _) => {
+            let count = model.keybinds.len();
+            model.status = Some(match model.clipboard.copy(model.keybinds.cheatsheet()) {
+                Ok(()) => Status::info(format!("Copied {count} keybinds")),
+                Err(err) => Status::error(format!("Failed to copy: {err}")),
+            });
+        }
+        (
+            Message::OpenDocs,
+            Some(Connected {
+                focus: focus @ (Focus::Components | Focus::Inspector),
+                components,
+                components_list,
+                locked,
+                ..
+            }),
+        ) => {
+            // The inspector may be locked to another component than the one selected in the list.
+            let locked = locked.as_ref().filter(|_| *focus == Focus::Inspector);
+            let index = inspected_component(
+                components,
+                components_list,
+                model.component_options,
+                &model.collapsed_groups,
+                locked,
+            )?;
+            let type_path = &components[index].0;
+            let is_enum = match &model.registry {
+                RegistryStatus::Available(registry) => {
+                    registry.field_kind(type_path, "") == Some("Enum")
+                }
+                _ => false,
+            };
+            let url = docs::url(type_path, is_enum);
+            // The url is shown either way since it is only a guess at where the docs are.
+            model.status = Some(match docs::open(&url) {
+                Ok(()) => Status::info(format!("Opened {url}")),
+                Err(err) => Status::error(format!("{err}, the docs may be at {url}")),
+            });
+        }
+        (Message::OpenDocs, _) => {}
+        (Message::ToggleLog, _) => {
+            model.show_log = !model.show_log;
+        }
+        (Message::ToggleRequestRates, _) => {
+            model.show_request_rates = !model.show_request_rates;
+        }
+        (Message::ToggleEntityDetails, _) => {
+            model.entity_details = !model.entity_details;
+        }
+        (
+            Message::ShowEntity,
+            Some(Connected {
+                focus: Focus::Entities,
+                entities,
+                entities_list,
+                components,
+                components_loading,
+                search,
+                ..
+            }),
+        ) => {
+            if components_loading.is_some() {
+                model.status = Some(Status::error("The components are still loading"));
+                return None;
+            }
+            let entity = &entities[selected_entity(search, entities, entities_list, &model.pins)?];
+            model.entity_popup = Some(EntityPopup::new(entity, entities, components));
+        }
+        (Message::ShowEntity, _) => {}
+        (
+            Message::TogglePeek,
+            Some(Connected {
+                focus: Focus::Entities,
+                ..
+            }),
+        ) => {
+            model.peeking = !model.peeking;
+            model.peek_cache.clear();
+        }
+        (Message::TogglePeek, _) => {}
+        (
+            msg @ (Message::TakeSnapshot | Message::ShowDiff),
+            Some(Connected {
+                focus: Focus::Entities | Focus::Components | Focus::Inspector,
+                entities,
+                entities_list,
+                components,
+                components_loading,
+                search,
+                ..
+            }),
+        ) => {
+            if components_loading.is_some() {
+                model.status = Some(Status::error("The components are still loading"));
+                return None;
+            }
+            let entity =
+                entities[selected_entity(search, entities, entities_list, &model.pins)?].id;
+            let snapshot = Snapshot {
+                entity,
+                components: components.clone(),
+            };
+            // The keys depend on the preset, so the hints use whichever are bound.
+            let key = |msg: Message| {
+                let labels = model.keymap.labels(&[msg]);
+                labels.into_iter().next().unwrap_or_default()
+            };
+            if matches!(msg, Message::TakeSnapshot) {
+                model.status = Some(Status::info(format!(
+                    "Took a snapshot of entity {entity}, press {} to see what changed since",
+                    key(Message::ShowDiff)
+                )));
+                model.snapshot = Some(snapshot);
+                return None;
+            }
+            let Some(before) = &model.snapshot else {
+                model.status = Some(Status::error(format!(
+                    "No snapshot to compare against, press {} to take one",
+                    key(Message::TakeSnapshot)
+                )));
+                return None;
+            };
+            let lines = diff::diff(&before.components, &snapshot.components);
+            if lines.is_empty() {
+                model.status = Some(Status::info("Nothing changed since the snapshot"));
+                return None;
+            }
+            model.diff = Some(DiffPopup::new(before, &snapshot, lines));
+        }
+        (Message::TakeSnapshot | Message::ShowDiff, _) => {}
+        (Message::CycleEntityLabel, _) => {
+            model.entity_label = model.entity_label.next();
+        }
+        (
+            Message::TogglePin,
+            Some(Connected {
+                focus: Focus::Entities,
+                entities,
+                entities_list,
+                search,
+                ..
+            }),
+        ) => {
+            let entity =
+                entities[selected_entity(search, entities, entities_list, &model.pins)?].id;
+            if !model.pins.remove(&entity) {
+                model.pins.insert(entity);
+            }
+            // Keep the same entity selected as it moves in the list.
+            let listed = listed_entities(search, entities, &model.pins);
+            if let Some(index) = listed.iter().position(|n| entities[*n].id == entity) {
+                entities_list.select(index);
+            }
+        }
+        (Message::TogglePin, _) => {}
+
+        // Watches
+        (
+            Message::ToggleWatch,
+            Some(Connected {
+                locked,
+                focus: Focus::Inspector,
+                entities,
+                entities_list,
+                components,
+                components_list,
+                inspector,
+                search,
+                ..
+            }),
+        ) => {
+            let entity =
+                entities[selected_entity(search, entities, entities_list, &model.pins)?].id;
+            let index = inspected_component(
+                components,
+                components_list,
+                model.component_options,
+                &model.collapsed_groups,
+                locked.as_ref(),
+            )?;
+            let (type_path, value) = &components[index];
+            let watch = Watch {
+                entity,
+                component: type_path.clone(),
+                path: inspector.selected_path()?.to_string(),
+            };
+            let name = format!("{}{}", ShortName(type_path), watch.path);
+            if let Some(n) = model.watches.iter().position(|w| *w == watch) {
+                model.watches.remove(n);
+                model.status = Some(Status::info(format!("Stopped watching {name}")));
+            } else {
+                // Shown right away rather than as gone until the first fetch.
+                model
+                    .watch_values
+                    .insert((entity, type_path.clone()), value.clone());
+                model.watches.push(watch);
+                model.status = Some(Status::info(format!("Watching {name}")));
+            }
+            spawn_watch_thread(model);
+        }
+        (Message::ToggleWatch, _) => {}
+        (Message::ClearWatches, _) => {
+            if model.watches.is_empty() {
+                return None;
+            }
+            model.watches.clear();
+            spawn_watch_thread(model);
+            model.status = Some(Status::info("Stopped watching everything"));
+        }
+        (Message::UpdateWatches(values), _) => model.watch_values = values,
+        (Message::Dismiss, state) => {
+            if model.popup.is_some() {
+                model.popup = None;
+            } else if model.entity_popup.is_some() {
+                model.entity_popup = None;
+            } else if model.diff.is_some() {
+                model.diff = None;
+            } else if model.peeking {
+                model.peeking = false;
+                model.peek_cache.clear();
+            } else if let Some(export) = &mut model.export {
+                export.quitter.quit();
+            } else {
+                if let Some(Connected {
+                    focus,
+                    reparenting,
+                    picker,
+                    palette,
+                    response,
+                    inspector,
+                    components,
+                    components_list,
+                    components_thread_quitter,
+                    components_loading,
+                    ..
+                }) = state
+                {
+                    // The overlays close on their own, leaving what is under them alone.
+                    if let Some(palette) = palette.take() {
+                        *focus = palette.return_focus;
+                    } else if let Some(response) = response.take() {
+                        *focus = response.return_focus;
+                    } else if let Some(entity) =
+                        components_loading.take_if(|_| reparenting.is_none() && picker.is_none())
+                    {
+                        if let Some(quitter) = components_thread_quitter {
+                            quitter.quit();
+                        }
+                        // The components shown are still those of the last entity, which actions
+                        // would otherwise apply to this one.
+                        components.clear();
+                        components_list.select_first();
+                        model.status = Some(Status::info(format!(
+                            "Stopped fetching the components of {entity}"
+                        )));
+                        ensure_valid_focus(&mut model.state, model.component_options);
+                        return None;
+                    } else {
+                        *reparenting = None;
+                        if let Some(picker) = picker.take() {
+                            *focus = picker.return_focus;
+                        }
+                        if *focus == Focus::Inspector {
+                            inspector.filter.clear();
+                        }
+                    }
+                }
+                model.status = None;
+            }
         }
-        (Message::Quit, _) => {
+        (Message::Quit, state) => {
+            let selected_entity = match state {
+                Some(Connected {
+                    entities,
+                    entities_list,
+                    search,
+                    ..
+                }) => selected_entity(search, entities, entities_list, &model.pins)
+                    .map(|n| entities[n].id),
+                _ => model.reselect,
+            };
+            Session {
+                socket: Some(model.socket.clone()),
+                selected_entity,
+            }
+            .save();
+            // The threads stop before their next request rather than failing to send its result.
+            if let Some(Connected {
+                components_thread_quitter: Some(quitter),
+                ..
+            }) = state
+            {
+                quitter.quit();
+            }
+            for quitter in [
+                &mut model.watch_thread_quitter,
+                &mut model.spinner_thread_quitter,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                quitter.quit();
+            }
+            model.shutdown.quit();
+            model.entities_waker.wake();
             model.state = State::Done;
         }
     };
@@ -430,11 +3932,467 @@ fn update(model: &mut Model, msg: Message) -> Option<Message> {
     None
 }
 
-fn border_style(focused: bool) -> Style {
+/// The indices into `entities` in the order they are listed, the entities matching the search
+/// with pinned entities first.
+fn listed_entities(
+    search: &mut Search,
+    entities: &[EntityMeta],
+    pins: &HashSet<Entity>,
+) -> Vec<usize> {
+    let matches = search.matches(entities);
+    if pins.is_empty() {
+        return matches.to_vec();
+    }
+    // The search order is kept within pinned and unpinned entities.
+    let (mut listed, unpinned): (Vec<_>, Vec<_>) = matches
+        .iter()
+        .partition(|n| pins.contains(&entities[**n].id));
+    listed.extend(unpinned);
+    listed
+}
+
+/// The index into `entities` of the entity selected in the entities list, accounting for the
+/// search and pins.
+fn selected_entity(
+    search: &mut Search,
+    entities: &[EntityMeta],
+    entities_list: &PaginatedListState,
+    pins: &HashSet<Entity>,
+) -> Option<usize> {
+    listed_entities(search, entities, pins)
+        .get(entities_list.selected())
+        .copied()
+}
+
+/// Read JSON from the clipboard and check that it can replace `target`, the value at `path` within
+/// a component of type `type_path`.
+fn read_pasted_value(
+    clipboard: &mut Clipboard,
+    registry: &RegistryStatus,
+    type_path: &str,
+    path: &str,
+    target: &Value,
+) -> anyhow::Result<Value> {
+    let text = clipboard.paste()?;
+    let value: Value =
+        serde_json::from_str(text.trim()).context("The clipboard doesn't contain valid JSON")?;
+    check_replacement(registry, type_path, path, target, &value)?;
+    Ok(value)
+}
+
+/// The value typed to replace the `target` at `path` in a component, or why it can't replace it.
+fn parse_edit(
+    registry: &RegistryStatus,
+    type_path: &str,
+    path: &str,
+    target: &Value,
+    text: &str,
+) -> anyhow::Result<Value> {
+    let value = match target {
+        Value::Number(_) => match text.trim().parse::<Number>() {
+            // Integers past 64 bits would be sent rounded to the nearest float.
+            Ok(n) if n.is_f64() && is_integer_literal(text.trim()) => {
+                bail!("{} is too large to set exactly", text.trim())
+            }
+            Ok(n) => Value::Number(n),
+            Err(_) => bail!("'{text}' isn't a number"),
+        },
+        _ => Value::String(text.to_string()),
+    };
+    check_replacement(registry, type_path, path, target, &value)?;
+    Ok(value)
+}
+
+/// The index into `components` of the component shown in the inspector, the `locked` one if there
+/// is one and otherwise the one selected in the components list.
+fn inspected_component(
+    components: &[(String, Value)],
+    components_list: &PaginatedListState,
+    options: ComponentListOptions,
+    collapsed: &HashSet<String>,
+    locked: Option<&String>,
+) -> Option<usize> {
+    match locked {
+        Some(locked) => components
+            .iter()
+            .position(|(type_path, _)| type_path == locked),
+        None => components::component_rows(components, options, collapsed)
+            .get(components_list.selected())
+            .and_then(ComponentRow::component),
+    }
+}
+
+/// Why the text being edited in the inspector can't be set, if it can't.
+fn edit_error(
+    registry: &RegistryStatus,
+    components: &[(String, Value)],
+    options: ComponentListOptions,
+    collapsed: &HashSet<String>,
+    components_list: &PaginatedListState,
+    locked: Option<&String>,
+    inspector: &InspectorState,
+) -> Option<String> {
+    let text = inspector.edit.as_ref()?.value();
+    let index = inspected_component(components, components_list, options, collapsed, locked)?;
+    let (type_path, component_value) = &components[index];
+    let path = inspector.selected_path()?;
+    let target = inspector::value_at_path(component_value, path)?;
+    parse_edit(registry, type_path, path, target, text)
+        .err()
+        .map(|err| err.to_string())
+}
+
+/// Check `value` can replace the `target` at `path` in a component, using the registry if it is
+/// available or else just the kinds of the values.
+fn check_replacement(
+    registry: &RegistryStatus,
+    type_path: &str,
+    path: &str,
+    target: &Value,
+    value: &Value,
+) -> anyhow::Result<()> {
+    let is_container = |value: &Value| value.is_object() || value.is_array();
+    if is_container(value) && !is_container(target) {
+        bail!("Can't put {} in place of a single value", json_kind(value));
+    }
+    if let RegistryStatus::Available(registry) = registry {
+        registry
+            .validate(type_path, path, value)
+            .map_err(anyhow::Error::msg)?;
+    } else if !target.is_null() && json_kind(target) != json_kind(value) {
+        bail!(
+            "Can't put {} in place of {}",
+            json_kind(value),
+            json_kind(target)
+        );
+    }
+    Ok(())
+}
+
+/// The kind of a JSON value for error messages.
+fn json_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a bool",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// The path of the field at `path` of the given component in Bevy's reflection path syntax, if
+/// the registry can translate it. The component itself has no path to set it with.
+fn reflect_path(registry: &RegistryStatus, type_path: &str, path: &str) -> Option<String> {
+    match registry {
+        RegistryStatus::Available(registry) => registry
+            .reflect_path(type_path, path)
+            .filter(|path| !path.is_empty()),
+        _ => None,
+    }
+}
+
+/// A value opened in an external editor, to set once it is closed.
+#[derive(Debug, Clone)]
+struct ExternalEdit {
+    entity: Entity,
+    component: String,
+    /// The path of the value as used by the inspector.
+    path: String,
+    /// The value as JSON, as given to the editor.
+    text: String,
+}
+
+/// A change to a field of a component, see [`spawn_set_field`].
+struct FieldChange {
+    entity: Entity,
+    component: String,
+    /// The path to the field in Bevy's reflection path syntax, if it has one.
+    reflect_path: Option<String>,
+    /// The new value of the field.
+    value: Value,
+    /// The whole component with the change made, for when the field can't be set on its own.
+    component_value: Value,
+}
+
+/// Set a field of a component on a worker thread, logging `done` once it succeeds.
+///
+/// Just the field is sent with `bevy/mutate_component` when it has a reflection path so changes
+/// the app makes to the rest of the component in the meantime aren't undone. Otherwise, or if the
+/// app doesn't have that method, the whole component is inserted again.
+fn spawn_set_field(socket: Socket, tx: mpsc::Sender<Message>, change: FieldChange, done: String) {
+    thread::spawn(move || {
+        let FieldChange {
+            entity,
+            component,
+            reflect_path,
+            value,
+            component_value,
+        } = change;
+        let mutated = reflect_path.map(|path| {
+            let params = BrpMutateComponentParams {
+                entity,
+                component: component.clone(),
+                path,
+                value,
+            };
+            brp::mutate_component_request(&socket, params)
+        });
+        let result = match mutated {
+            Some(Err(err)) if brp::is_method_not_found(&err) => None,
+            mutated => mutated,
+        }
+        .unwrap_or_else(|| {
+            let params = BrpInsertParams {
+                entity,
+                components: [(component.clone(), component_value)].into_iter().collect(),
+            };
+            brp::insert_request(&socket, params)
+        });
+        match result {
+            Ok(()) => {
+                let _ = tx.send(Message::Log(LogEntry::info(done)));
+            }
+            Err(err) => {
+                let title = format!("Failed to set {}", ShortName(&component));
+                let entry = LogEntry::error(format!("{title}: {err}"));
+                let _ = tx.send(Message::Log(entry));
+                let _ = tx.send(Message::ShowError(ErrorPopup::new(title, &err)));
+            }
+        }
+    });
+}
+
+/// Change how the components panel lists components while keeping the same component selected.
+fn set_component_options(
+    state: &mut State,
+    current: &mut ComponentListOptions,
+    new: ComponentListOptions,
+    collapsed: &HashSet<String>,
+) {
+    if let Some(Connected {
+        components,
+        components_list,
+        component_changes,
+        ..
+    }) = state.connected_mut()
+    {
+        let selected = components::component_rows(components, *current, collapsed)
+            .get(components_list.selected())
+            .and_then(ComponentRow::component)
+            .map(|n| components[n].0.clone());
+        components::sort(components, component_changes, new);
+        let new_rows = components::component_rows(components, new, collapsed);
+        if let Some(row) = selected
+            .and_then(|type_path| components.iter().position(|(path, _)| *path == type_path))
+            .and_then(|n| components::row_of_component(&new_rows, n))
+        {
+            components_list.select(row);
+        }
+    }
+    *current = new;
+    ensure_valid_focus(state, new);
+}
+
+/// Move the focus back to the entities panel if the components panel it is on, or the inspector
+/// next to it, has nothing to show.
+fn ensure_valid_focus(state: &mut State, options: ComponentListOptions) {
+    if let Some(Connected {
+        focus:
+            focus @ (Focus::Components
+            | Focus::ComponentsPage
+            | Focus::Inspector
+            | Focus::Compared
+            | Focus::Filter
+            | Focus::Edit),
+        components,
+        ..
+    }) = state.connected_mut()
+    {
+        // Collapsed groups keep their header, so they don't change whether there are rows.
+        if components::component_rows(components, options, &HashSet::new()).is_empty() {
+            *focus = Focus::Entities;
+        }
+    }
+}
+
+/// Restore the search, filter, component ordering, pins and focus of the view at the given
+/// index.
+fn apply_view(model: &mut Model, index: usize) -> Option<Message> {
+    let view = model.views.get(index)?.clone();
+    model.active_view = Some(index);
+    model.pins = view.pins.into_iter().collect();
+    set_component_options(
+        &mut model.state,
+        &mut model.component_options,
+        view.components,
+        &model.collapsed_groups,
+    );
+    let Connected {
+        focus,
+        entities_list,
+        search,
+        inspector,
+        ..
+    } = model.state.connected_mut()?;
+    search.input = TextInput::with_value(view.search);
+    inspector.filter = TextInput::with_value(view.filter);
+    entities_list.select(0);
+    *focus = match view.panel {
+        1 => Focus::Components,
+        2 => Focus::Inspector,
+        _ => Focus::Entities,
+    };
+    model.status = Some(Status::info(format!("Switched to view {}", view.name)));
+    Some(Message::SpawnComponnentsThread)
+}
+
+/// Replace the thread fetching the watched values with one for the current watches, or just stop
+/// it if there are none.
+fn spawn_watch_thread(model: &mut Model) {
+    if let Some(mut quitter) = model.watch_thread_quitter.take() {
+        quitter.quit();
+    }
+    if model.watches.is_empty() {
+        model.watch_values.clear();
+        return;
+    }
+    let quitter = ThreadQuitToken::new();
+    model.watch_thread_quitter = Some(quitter.clone());
+    let socket = model.socket.clone();
+    let tx = model.message_tx.clone();
+    let watches = model.watches.clone();
+    thread::spawn(move || watch::handle_watch_querying(tx, &socket, watches, quitter));
+}
+
+/// Fetch the registry if it hasn't been yet, or if fetching it failed a while ago. The registry
+/// doesn't change while the app runs so once fetched it is kept.
+fn fetch_registry(registry: &mut RegistryStatus, socket: &Socket, tx: &mpsc::Sender<Message>) {
+    let due = match registry {
+        RegistryStatus::Unknown => true,
+        RegistryStatus::Failed(at) => at.elapsed() >= REGISTRY_RETRY_DELAY,
+        _ => false,
+    };
+    if !due {
+        return;
+    }
+    *registry = RegistryStatus::Loading;
+    let socket = socket.clone();
+    let tx = tx.clone();
+    thread::spawn(move || {
+        let registry = match brp::registry_schema_request(&socket) {
+            Ok(schemas) => Ok(Some(Registry::new(schemas))),
+            // Only an app without the method is taken as never providing the registry.
+            Err(err) if brp::is_method_not_found(&err) => Ok(None),
+            Err(err) => Err(format!("{err:#}")),
+        };
+        let _ = tx.send(Message::UpdateRegistry(registry));
+    });
+}
+
+/// Start the thread sending [`Message::Tick`] to animate the spinner while connecting, it is
+/// stopped by the first tick after connecting or failing to.
+fn spawn_spinner_thread(model: &mut Model) {
+    if let Some(mut quitter) = model.spinner_thread_quitter.take() {
+        quitter.quit();
+    }
+    let quitter = ThreadQuitToken::new();
+    model.spinner_thread_quitter = Some(quitter.clone());
+    let tx = model.message_tx.clone();
+    thread::spawn(move || loop {
+        thread::sleep(SPINNER_INTERVAL);
+        if quitter.should_quit() || tx.send(Message::Tick).is_err() {
+            return;
+        }
+    });
+}
+
+/// Whether the text is an integer with no fraction or exponent, such as `-12`.
+fn is_integer_literal(text: &str) -> bool {
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// The page number or entity id being typed, shown in the title of the list it is for.
+fn input_title<'a>(label: &'a str, input: &'a TextInput, theme: &Theme) -> Line<'a> {
+    Line::from(vec![
+        Span::styled(label, theme.dim()),
+        Span::raw(" "),
+        Span::raw(input.value()),
+        Span::raw(theme.cursor()).fg(theme.accent),
+    ])
+    .right_aligned()
+}
+
+/// The frame of the spinner to show while connecting.
+fn spinner_frame(frame: usize, theme: &Theme) -> char {
+    match theme.ascii {
+        true => ASCII_SPINNER[frame % ASCII_SPINNER.len()],
+        false => SPINNER[frame % SPINNER.len()],
+    }
+}
+
+/// The index of the focused panel in the body, if a panel is focused.
+fn panel_index(focus: Focus) -> Option<usize> {
+    match focus {
+        Focus::Entities | Focus::EntitiesPage | Focus::EntityId | Focus::EntityFilter => Some(0),
+        Focus::Components | Focus::ComponentsPage => Some(1),
+        Focus::Inspector | Focus::Compared | Focus::Filter | Focus::Edit => Some(2),
+        Focus::Search | Focus::Picker | Focus::Palette | Focus::Response | Focus::ViewName => None,
+    }
+}
+
+/// Constraints for the body panels in the given ratios, keeping panels that would be narrower
+/// than [`MIN_PANEL_WIDTH`] at that width.
+fn panel_constraints(ratios: [u16; 3], width: u16) -> [Constraint; 3] {
+    let total: u32 = ratios.iter().copied().map(u32::from).sum();
+    ratios.map(|ratio| {
+        let ratio_width = u32::from(width) * u32::from(ratio) / total.max(1);
+        if ratio_width < u32::from(MIN_PANEL_WIDTH) {
+            Constraint::Length(MIN_PANEL_WIDTH)
+        } else {
+            Constraint::Fill(ratio)
+        }
+    })
+}
+
+fn border_style(focused: bool, theme: &Theme) -> Style {
     if focused {
-        Style::default().fg(PRIMARY_COLOR)
+        Style::default().fg(theme.accent)
     } else {
-        Style::default().dim()
+        theme.dim()
+    }
+}
+
+/// A short message shown in the footer.
+#[derive(Debug)]
+struct Status {
+    text: String,
+    error: bool,
+}
+
+impl Status {
+    fn info(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            error: false,
+        }
+    }
+
+    fn error(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            error: true,
+        }
+    }
+
+    fn line(&self, theme: &Theme) -> Line<'_> {
+        let span = Span::raw(self.text.as_str());
+        Line::from(if self.error {
+            span.fg(theme.error)
+        } else {
+            span.fg(theme.accent)
+        })
     }
 }
 
@@ -458,3 +4416,220 @@ impl ThreadQuitToken {
         self.quit.load(Ordering::Relaxed)
     }
 }
+
+/// Lets a thread sleep until either a timeout passes or another thread wakes it.
+#[derive(Debug, Default, Clone)]
+struct WakeToken {
+    woken: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl WakeToken {
+    fn wake(&self) {
+        let (woken, condvar) = &*self.woken;
+        *woken.lock().unwrap_or_else(PoisonError::into_inner) = true;
+        condvar.notify_all();
+    }
+
+    /// Sleep for the given duration, returning early if woken.
+    fn sleep(&self, duration: Duration) {
+        let (woken, condvar) = &*self.woken;
+        // The flag is a plain bool, so it stays valid even if a thread panicked holding the lock.
+        let guard = woken.lock().unwrap_or_else(PoisonError::into_inner);
+        let (mut guard, _) = condvar
+            .wait_timeout_while(guard, duration, |woken| !*woken)
+            .unwrap_or_else(PoisonError::into_inner);
+        *guard = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keymap::KeymapPreset;
+    use ratatui::{backend::TestBackend, Terminal};
+    use serde_json::json;
+    use std::time::Instant;
+
+    /// A model connected to an app with the given entities, none of which have components.
+    fn connected_model(entities: Vec<EntityMeta>) -> Model {
+        let (tx, _) = mpsc::channel();
+        let socket = "127.0.0.1:1".parse().unwrap();
+        let mut model = Model::new(
+            tx,
+            KeybindSet::new(),
+            Keymap::new(KeymapPreset::Default),
+            socket,
+        );
+        // The registry isn't needed, and fetching it would fail without an app to ask.
+        model.registry = RegistryStatus::Unavailable;
+        update(&mut model, Message::UpdateEntities(entities));
+        model
+    }
+
+    fn draw(terminal: &mut Terminal<TestBackend>, model: &mut Model) {
+        terminal.draw(|frame| view(model, frame)).unwrap();
+    }
+
+    fn connected(model: &mut Model) -> &mut Connected {
+        model.state.connected_mut().unwrap()
+    }
+
+    /// The text of the drawn screen, one string for each row.
+    fn screen(terminal: &Terminal<TestBackend>) -> Vec<String> {
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tiny_terminals_ask_to_be_resized() {
+        let mut model = connected_model(entities(10));
+        let mut terminal = Terminal::new(TestBackend::new(5, 3)).unwrap();
+        draw(&mut terminal, &mut model);
+        assert!(model.too_small);
+        // As much of the message is shown as fits.
+        let screen = screen(&terminal);
+        let rows: Vec<_> = screen.iter().map(|row| row.trim_end()).collect();
+        assert_eq!(rows, ["Termi", "nal", "too"]);
+    }
+
+    #[test]
+    fn smallest_terminal_fits_every_pane() {
+        let mut model = connected_model(entities(10));
+        let mut terminal =
+            Terminal::new(TestBackend::new(MIN_TERMINAL_WIDTH, MIN_TERMINAL_HEIGHT)).unwrap();
+        connected(&mut model).components =
+            vec![(String::from("game::A"), json!({ "x": 1, "y": [2, 3] }))];
+        model.show_log = true;
+        model.log.push(LogEntry::info("Connected"));
+        for n in 0..MAX_WATCH_PANE_LINES + 2 {
+            let entity = entities(10)[n as usize].id;
+            model.watches.push(Watch {
+                entity,
+                component: String::from("game::A"),
+                path: String::from(".x"),
+            });
+        }
+        draw(&mut terminal, &mut model);
+        assert!(!model.too_small);
+        let screen = screen(&terminal).concat();
+        assert!(!screen.contains("Terminal too small"), "{screen}");
+    }
+
+    #[test]
+    fn moving_between_panels_keeps_their_selections() {
+        let mut model = connected_model(entities(10));
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        connected(&mut model).components = vec![
+            (String::from("game::A"), json!({ "x": 1, "y": 2, "z": 3 })),
+            (String::from("game::B"), json!({ "x": 4, "y": 5, "z": 6 })),
+        ];
+        connected(&mut model).entities_list.select(3);
+        draw(&mut terminal, &mut model);
+
+        update(&mut model, Message::MoveRight);
+        update(&mut model, Message::MoveDown);
+        draw(&mut terminal, &mut model);
+        update(&mut model, Message::MoveRight);
+        update(&mut model, Message::MoveDown);
+        update(&mut model, Message::MoveDown);
+        draw(&mut terminal, &mut model);
+        assert_eq!(connected(&mut model).focus, Focus::Inspector);
+        assert_eq!(connected(&mut model).inspector.selected_path(), Some(".y"));
+
+        update(&mut model, Message::MoveLeft);
+        update(&mut model, Message::MoveLeft);
+        draw(&mut terminal, &mut model);
+        update(&mut model, Message::MoveRight);
+        update(&mut model, Message::MoveRight);
+        draw(&mut terminal, &mut model);
+        let connected = connected(&mut model);
+        assert_eq!(connected.focus, Focus::Inspector);
+        assert_eq!(connected.entities_list.selected(), 3);
+        assert_eq!(connected.components_list.selected(), 1);
+        assert_eq!(connected.inspector.selected_path(), Some(".y"));
+    }
+
+    #[test]
+    fn inspector_keeps_the_selected_path_across_components() {
+        let mut model = connected_model(entities(1));
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        connected(&mut model).components = vec![
+            (String::from("game::A"), json!({ "x": 1, "z": 3 })),
+            (
+                String::from("game::B"),
+                json!({ "w": 0, "x": 4, "y": 5, "z": 6 }),
+            ),
+            (String::from("game::C"), json!({ "a": 7 })),
+        ];
+        update(&mut model, Message::MoveRight);
+        update(&mut model, Message::MoveRight);
+        draw(&mut terminal, &mut model);
+        update(&mut model, Message::MoveDown);
+        update(&mut model, Message::MoveDown);
+        draw(&mut terminal, &mut model);
+        assert_eq!(connected(&mut model).inspector.selected_path(), Some(".z"));
+
+        // B has the same field further down.
+        update(&mut model, Message::MoveLeft);
+        update(&mut model, Message::MoveDown);
+        draw(&mut terminal, &mut model);
+        assert_eq!(connected(&mut model).inspector.selected_path(), Some(".z"));
+
+        // C doesn't, so the selection stays in range.
+        update(&mut model, Message::MoveDown);
+        draw(&mut terminal, &mut model);
+        let path = connected(&mut model).inspector.selected_path();
+        assert!(matches!(path, Some("" | ".a")), "{path:?}");
+    }
+
+    fn entities(count: u32) -> Vec<EntityMeta> {
+        (0..count)
+            .map(|n| EntityMeta {
+                id: Entity::from_bits((1 << 32) + u64::from(n)),
+                name: Some(format!("Entity {n}")),
+                parent: None,
+                extra: HashMap::new(),
+            })
+            .collect()
+    }
+
+    /// The time to list the entities once per frame drawn, with
+    /// `cargo test --release bench_ -- --ignored --nocapture`.
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_list_entities() {
+        const RUNS: u32 = 50;
+        for count in [5_000, 200_000] {
+            let entities = entities(count);
+            let mut pins = HashSet::new();
+
+            for (label, query) in [("no search", ""), ("search", "ty 19")] {
+                let mut search = Search::default();
+                search.input = TextInput::with_value(query.to_string());
+                listed_entities(&mut search, &entities, &pins);
+                let start = Instant::now();
+                for _ in 0..RUNS {
+                    std::hint::black_box(listed_entities(&mut search, &entities, &pins));
+                }
+                println!("{count} entities, {label}: {:?}", start.elapsed() / RUNS);
+            }
+
+            pins.insert(entities[1000].id);
+            let mut search = Search::default();
+            let start = Instant::now();
+            for _ in 0..RUNS {
+                std::hint::black_box(listed_entities(&mut search, &entities, &pins));
+            }
+            println!(
+                "{count} entities, no search with a pin: {:?}",
+                start.elapsed() / RUNS
+            );
+        }
+    }
+}
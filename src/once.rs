@@ -0,0 +1,55 @@
+//! Running a single query without the TUI, for use in scripts.
+
+use crate::{
+    brp::{self, RemoteError},
+    transport::Socket,
+};
+use bevy_remote::builtin_methods::{BrpQuery, BrpQueryFilter, BrpQueryParams};
+use std::io;
+
+/// The exit code when the app can't be reached.
+pub const EXIT_CONNECTION_FAILED: i32 = 2;
+/// The exit code when the app responds with an error, such as for an unregistered component.
+pub const EXIT_REQUEST_FAILED: i32 = 3;
+
+/// Print the entities with all the given components as JSON, along with their names and the
+/// values of those components, returning the code to exit with.
+pub fn run(socket: &Socket, components: Vec<String>, name_component: Option<&str>) -> i32 {
+    let name = match brp::find_meta_components(socket, name_component, &[]) {
+        Ok(found) => found.name,
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            return EXIT_CONNECTION_FAILED;
+        }
+    };
+    let params = BrpQueryParams {
+        data: BrpQuery {
+            components,
+            option: name.into_iter().collect(),
+            ..Default::default()
+        },
+        filter: BrpQueryFilter::default(),
+    };
+    let mut rows = match brp::query_request(socket, params) {
+        Ok(rows) => rows,
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            return match err.downcast_ref::<RemoteError>() {
+                Some(_) => EXIT_REQUEST_FAILED,
+                None => EXIT_CONNECTION_FAILED,
+            };
+        }
+    };
+    rows.sort_by_key(|row| row.entity);
+
+    match serde_json::to_writer_pretty(io::stdout().lock(), &rows) {
+        Ok(()) => {
+            println!();
+            0
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            1
+        }
+    }
+}
@@ -5,21 +5,39 @@ use ratatui::{
     widgets::{Block, StatefulWidget, Widget},
 };
 
-use crate::PRIMARY_COLOR;
+use crate::theme::{self, Theme};
+
+/// The most pages shown as dots, past which they are too many to count at a glance and the page
+/// number is shown as text instead.
+const MAX_PAGE_DOTS: usize = 10;
+/// The rows below the items reserved for the pagination, a separator and then the page dots.
+const PAGINATION_ROWS: u16 = 2;
 
 #[derive(Debug)]
 pub struct PaginatedList<'a> {
     block: Option<Block<'a>>,
     items: Vec<Line<'a>>,
+    subtitles: Option<Vec<Option<Line<'a>>>>,
+    unselectable: Vec<usize>,
     focused: bool,
+    /// Whether moving past either end of the list moves to the other end.
+    wrap: bool,
+    /// Whether the selected item flashes when the cursor moves to it.
+    flash: bool,
+    theme: Theme,
 }
 
 impl<'a> PaginatedList<'a> {
     pub fn new<T: IntoIterator<Item = Line<'a>>>(items: T, focused: bool) -> Self {
         Self {
-            items: items.into_iter().map(Into::into).collect(),
+            items: items.into_iter().collect(),
+            subtitles: None,
+            unselectable: Vec::new(),
             block: None,
             focused,
+            wrap: true,
+            flash: false,
+            theme: Theme::default(),
         }
     }
 
@@ -27,12 +45,46 @@ impl<'a> PaginatedList<'a> {
         self.block = Some(block);
         self
     }
+
+    /// Show a second line below each item, making every item two rows tall. Items without a
+    /// subtitle are given an empty second line so pages stay a uniform size.
+    pub fn subtitles<T: IntoIterator<Item = Option<Line<'a>>>>(mut self, subtitles: T) -> Self {
+        self.subtitles = Some(subtitles.into_iter().collect());
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Whether moving up from the first item selects the last and moving down from the last
+    /// selects the first, otherwise the cursor stops at the ends.
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Flash the selected item for a frame when the cursor moves to it.
+    pub fn flash(mut self, flash: bool) -> Self {
+        self.flash = flash;
+        self
+    }
+
+    /// Mark the items at the given indices as not selectable, such as headers. The cursor will
+    /// skip over them.
+    pub fn unselectable(mut self, indices: impl IntoIterator<Item = usize>) -> Self {
+        self.unselectable = indices.into_iter().collect();
+        self
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct PaginatedListState {
     selected: usize,
     cursor_move: Option<CursorMove>,
+    /// Whether the cursor moved to another item since the last render.
+    moved: bool,
 }
 
 #[derive(Debug)]
@@ -43,6 +95,7 @@ enum CursorMove {
     NextPage,
     First,
     Last,
+    Page(usize),
 }
 
 impl PaginatedListState {
@@ -50,6 +103,11 @@ impl PaginatedListState {
         self.selected
     }
 
+    /// Select the item at the given index, this isn't deferred until the next render.
+    pub fn select(&mut self, index: usize) {
+        self.selected = index;
+    }
+
     pub fn select_previous(&mut self) {
         assert!(self.cursor_move.is_none(), "cursor_move is set");
         self.cursor_move = Some(CursorMove::Previous);
@@ -80,31 +138,88 @@ impl PaginatedListState {
         self.cursor_move = Some(CursorMove::Last);
     }
 
-    fn apply_cursor_move(&mut self, per_page: usize, items: usize) {
+    /// Select the first item on the page at the given index, or the last page if there aren't that
+    /// many.
+    pub fn select_page(&mut self, page: usize) {
+        assert!(self.cursor_move.is_none(), "cursor_move is set");
+        self.cursor_move = Some(CursorMove::Page(page));
+    }
+
+    fn apply_cursor_move(
+        &mut self,
+        per_page: usize,
+        items: usize,
+        unselectable: &[usize],
+        wrap: bool,
+    ) {
+        // There is nothing to move between, the arithmetic below assumes at least one item.
+        if items == 0 {
+            self.selected = 0;
+            self.cursor_move = None;
+            return;
+        }
+
         let total_pages = items.div_ceil(per_page);
+        let previous = self.selected;
+        // Which way to look for a selectable item if the cursor lands on an unselectable one.
+        let forwards = !matches!(
+            self.cursor_move,
+            Some(CursorMove::Previous | CursorMove::Last)
+        );
         match self.cursor_move {
-            Some(CursorMove::Previous) if self.selected == 0 => self.selected = items - 1,
-            Some(CursorMove::Previous) => self.selected -= 1,
+            Some(CursorMove::Previous) if self.selected == 0 && wrap => self.selected = items - 1,
+            Some(CursorMove::Previous) => self.selected = self.selected.saturating_sub(1),
 
-            Some(CursorMove::Next) if self.selected == items - 1 => self.selected = 0,
+            Some(CursorMove::Next) if self.selected == items - 1 && wrap => self.selected = 0,
             Some(CursorMove::Next) => self.selected += 1,
 
-            Some(CursorMove::PreviousPage) if self.selected < per_page => {
-                self.selected += per_page * (total_pages - 1)
+            // Pages keep the cursor's position on the page, clamped below if the page it lands on
+            // is the partially full last page.
+            Some(CursorMove::PreviousPage) => {
+                let page = self.selected / per_page;
+                let page = page.checked_sub(1).unwrap_or(total_pages - 1);
+                self.selected = page * per_page + self.selected % per_page;
             }
-            Some(CursorMove::PreviousPage) => self.selected -= per_page,
-
-            Some(CursorMove::NextPage) if self.selected >= per_page * (total_pages - 1) => {
-                self.selected -= per_page * (total_pages - 1)
+            Some(CursorMove::NextPage) => {
+                let page = (self.selected / per_page + 1) % total_pages;
+                self.selected = page * per_page + self.selected % per_page;
             }
-            Some(CursorMove::NextPage) => self.selected += per_page,
             Some(CursorMove::First) => self.selected = 0,
             Some(CursorMove::Last) => self.selected = usize::MAX,
+            Some(CursorMove::Page(page)) => self.selected = per_page * page.min(total_pages - 1),
 
             None => {}
         }
         self.selected = self.selected.min(items.saturating_sub(1));
         self.cursor_move = None;
+        self.skip_unselectable(items, unselectable, forwards, wrap);
+        self.moved = self.selected != previous;
+    }
+
+    /// Move the cursor off any unselectable item, preferring the given direction. When wrapping the
+    /// search carries on from the other end, otherwise it turns back.
+    fn skip_unselectable(
+        &mut self,
+        items: usize,
+        unselectable: &[usize],
+        forwards: bool,
+        wrap: bool,
+    ) {
+        if !unselectable.contains(&self.selected) {
+            return;
+        }
+        let is_selectable = |n: &usize| !unselectable.contains(n);
+        let after = (self.selected..items).find(is_selectable);
+        let before = (0..self.selected).rev().find(is_selectable);
+        let found = match (forwards, wrap) {
+            (true, true) => after.or_else(|| (0..self.selected).find(is_selectable)),
+            (false, true) => before.or_else(|| (self.selected..items).rev().find(is_selectable)),
+            (true, false) => after.or(before),
+            (false, false) => before.or(after),
+        };
+        if let Some(n) = found {
+            self.selected = n;
+        }
     }
 }
 
@@ -117,10 +232,16 @@ impl StatefulWidget for PaginatedList<'_> {
         }
         let area = self.block.inner_if_some(area);
 
-        let per_page = area.height as usize - 2;
+        let item_height = if self.subtitles.is_some() { 2 } else { 1 };
+        let (items_area, per_page) = page_layout(area, item_height);
         let total_pages = self.items.len().div_ceil(per_page);
 
-        state.apply_cursor_move(per_page, self.items.len());
+        state.apply_cursor_move(per_page, self.items.len(), &self.unselectable, self.wrap);
+        let flash = self.flash && self.focused && std::mem::take(&mut state.moved);
+
+        if area.is_empty() {
+            return;
+        }
 
         let page = state.selected / per_page;
         let page_items =
@@ -133,13 +254,29 @@ impl StatefulWidget for PaginatedList<'_> {
                 height: 1,
                 width: area.width,
                 x: area.x,
-                y: area.y + n as u16,
+                y: items_area.y + (n * item_height) as u16,
             };
+            let subtitle = self
+                .subtitles
+                .as_ref()
+                .and_then(|subtitles| subtitles.get(page * per_page + n)?.as_ref());
+            if let Some(subtitle) = subtitle.filter(|_| item_area.y + 1 < items_area.bottom()) {
+                let subtitle_area = Rect {
+                    x: area.x + 2,
+                    y: item_area.y + 1,
+                    width: area.width.saturating_sub(2),
+                    height: 1,
+                };
+                subtitle
+                    .clone()
+                    .patch_style(self.theme.dim())
+                    .render(subtitle_area, buf);
+            }
             if n != page_selected {
                 line.render(item_area, buf);
             } else {
                 let style = if self.focused {
-                    Style::default().fg(PRIMARY_COLOR)
+                    Style::default().fg(self.theme.selection)
                 } else {
                     Style::default()
                 };
@@ -147,25 +284,259 @@ impl StatefulWidget for PaginatedList<'_> {
                     .set_char('>')
                     .set_style(style.bold());
                 item_area.x += 2;
-                item_area.width -= 2;
+                item_area.width = item_area.width.saturating_sub(2);
                 line.clone().style(style).render(item_area, buf);
+                if flash {
+                    theme::flash(buf, item_area);
+                }
             }
         }
 
         // Render pagination
-        if total_pages > 1 {
-            let line = Line::from(
-                (0..total_pages)
-                    .map(|n| {
-                        if n != page {
-                            Span::raw("• ").bold().dim()
-                        } else {
-                            Span::raw("• ").bold()
-                        }
-                    })
-                    .collect::<Vec<Span>>(),
-            );
-            buf.set_line(area.x, area.y + area.height - 1, &line, area.width);
+        if total_pages > 1 && items_area.height < area.height {
+            let separator = Rect {
+                y: items_area.bottom(),
+                height: 1,
+                ..area
+            };
+            let dash = self.theme.symbol("╌", "-");
+            Line::styled(dash.repeat(separator.width as usize), self.theme.dim())
+                .render(separator, buf);
+            let row = Rect {
+                y: area.bottom() - 1,
+                height: 1,
+                ..area
+            };
+            let position = format!("{}/{}", state.selected + 1, self.items.len());
+            let dots_width = total_pages * 2;
+            if total_pages <= MAX_PAGE_DOTS && dots_width + position.len() < row.width as usize {
+                let line = Line::from(
+                    (0..total_pages)
+                        .map(|n| {
+                            let dot = self.theme.symbol("• ", "o ");
+                            if n != page {
+                                Span::styled(dot, self.theme.dim()).bold()
+                            } else {
+                                Span::raw(dot).bold()
+                            }
+                        })
+                        .collect::<Vec<Span>>(),
+                );
+                line.render(row, buf);
+                Line::styled(position, self.theme.dim())
+                    .right_aligned()
+                    .render(row, buf);
+            } else {
+                Line::from(vec![
+                    Span::styled("page ", self.theme.dim()),
+                    Span::raw((page + 1).to_string()).bold(),
+                    Span::styled(format!("/{total_pages}  {position}"), self.theme.dim()),
+                ])
+                .render(row, buf);
+            }
         }
     }
 }
+
+/// The area the items of a page are rendered in and how many fit in it, the one place the page
+/// size is worked out so the cursor and what is rendered agree.
+///
+/// The bottom rows are reserved for the pagination unless the area is too small to fit them, in
+/// which case the pagination isn't shown. They are reserved even when there is one page so the
+/// items don't move when more are added.
+fn page_layout(area: Rect, item_height: usize) -> (Rect, usize) {
+    let reserved_rows = if area.height > PAGINATION_ROWS {
+        PAGINATION_ROWS
+    } else {
+        0
+    };
+    let items_area = Rect {
+        height: area.height - reserved_rows,
+        ..area
+    };
+    let per_page = (items_area.height as usize / item_height).max(1);
+    (items_area, per_page)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Move the cursor of a list of `items` shown `per_page` to a page, returning where it lands.
+    fn move_cursor(
+        selected: usize,
+        cursor_move: CursorMove,
+        per_page: usize,
+        items: usize,
+        unselectable: &[usize],
+        wrap: bool,
+    ) -> usize {
+        let mut state = PaginatedListState {
+            selected,
+            cursor_move: Some(cursor_move),
+            moved: false,
+        };
+        state.apply_cursor_move(per_page, items, unselectable, wrap);
+        state.selected
+    }
+
+    #[test]
+    fn previous_past_leading_header_wraps_to_last() {
+        assert_eq!(move_cursor(1, CursorMove::Previous, 10, 5, &[0], true), 4);
+    }
+
+    #[test]
+    fn previous_past_leading_header_stops_without_wrap() {
+        assert_eq!(move_cursor(1, CursorMove::Previous, 10, 5, &[0], false), 1);
+    }
+
+    #[test]
+    fn next_from_last_wraps_past_leading_header() {
+        assert_eq!(move_cursor(4, CursorMove::Next, 10, 5, &[0], true), 1);
+    }
+
+    #[test]
+    fn skips_header_in_the_middle() {
+        assert_eq!(move_cursor(1, CursorMove::Next, 10, 5, &[0, 2], true), 3);
+        assert_eq!(
+            move_cursor(3, CursorMove::Previous, 10, 5, &[0, 2], true),
+            1
+        );
+    }
+
+    /// The text of each row of the list rendered into a `width` by `height` area.
+    fn rendered(
+        list: PaginatedList,
+        state: &mut PaginatedListState,
+        width: u16,
+        height: u16,
+    ) -> Vec<String> {
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = Buffer::empty(area);
+        list.render(area, &mut buf, state);
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    fn items(count: usize) -> Vec<Line<'static>> {
+        (0..count).map(|n| Line::raw(format!("item {n}"))).collect()
+    }
+
+    #[test]
+    fn renders_one_item_into_a_single_row() {
+        let mut state = PaginatedListState::default();
+        let lines = rendered(PaginatedList::new(items(5), true), &mut state, 20, 1);
+        assert_eq!(lines, ["> item 0"]);
+        // Each page is then a single item.
+        state.select_next_page();
+        let lines = rendered(PaginatedList::new(items(5), true), &mut state, 20, 1);
+        assert_eq!(lines, ["> item 1"]);
+    }
+
+    #[test]
+    fn renders_into_tiny_areas_without_panicking() {
+        for (width, height) in [(0, 0), (20, 0), (0, 1), (1, 1), (20, 1), (20, 2)] {
+            for count in [0, 1, 5] {
+                let mut state = PaginatedListState::default();
+                state.select_last();
+                rendered(
+                    PaginatedList::new(items(count), true),
+                    &mut state,
+                    width,
+                    height,
+                );
+                // Items with subtitles are taller than the area.
+                rendered(
+                    PaginatedList::new(items(count), true)
+                        .subtitles((0..count).map(|_| Some(Line::raw("subtitle")))),
+                    &mut state,
+                    width,
+                    height,
+                );
+                // The borders take up all the space.
+                rendered(
+                    PaginatedList::new(items(count), true).block(Block::bordered()),
+                    &mut state,
+                    width,
+                    height,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn moving_in_an_empty_list_stays_at_the_start() {
+        let mut state = PaginatedListState {
+            selected: 3,
+            ..Default::default()
+        };
+        state.select_next();
+        state.apply_cursor_move(10, 0, &[], true);
+        assert_eq!(state.selected, 0);
+        assert!(state.cursor_move.is_none());
+
+        // The list can still be moved in once it has items.
+        state.select_next();
+        let lines = rendered(PaginatedList::new(items(0), true), &mut state, 20, 5);
+        assert!(lines.iter().all(String::is_empty), "{lines:?}");
+        state.select_next();
+        rendered(PaginatedList::new(items(3), true), &mut state, 20, 5);
+        assert_eq!(state.selected, 1);
+    }
+
+    #[test]
+    fn pages_keep_the_position_on_the_page() {
+        // Seven items shown three to a page.
+        assert_eq!(move_cursor(1, CursorMove::NextPage, 3, 7, &[], true), 4);
+        assert_eq!(move_cursor(4, CursorMove::PreviousPage, 3, 7, &[], true), 1);
+        assert_eq!(move_cursor(3, CursorMove::NextPage, 3, 7, &[], true), 6);
+    }
+
+    #[test]
+    fn partially_full_last_page_clamps_to_its_last_item() {
+        // Seven items shown three to a page, so the last page has just one.
+        assert_eq!(move_cursor(5, CursorMove::NextPage, 3, 7, &[], true), 6);
+        assert_eq!(move_cursor(2, CursorMove::PreviousPage, 3, 7, &[], true), 6);
+        assert_eq!(move_cursor(6, CursorMove::NextPage, 3, 7, &[], true), 0);
+        assert_eq!(move_cursor(0, CursorMove::Page(2), 3, 7, &[], true), 6);
+        assert_eq!(move_cursor(0, CursorMove::Page(9), 3, 7, &[], true), 6);
+        assert_eq!(move_cursor(6, CursorMove::PreviousPage, 3, 7, &[], true), 3);
+    }
+
+    #[test]
+    fn renders_the_page_the_cursor_is_on() {
+        // Three items fit above the two rows of pagination.
+        let mut state = PaginatedListState {
+            selected: 5,
+            cursor_move: Some(CursorMove::NextPage),
+            moved: false,
+        };
+        let lines = rendered(PaginatedList::new(items(7), true), &mut state, 20, 5);
+        assert_eq!(lines[..3], ["> item 6", "", ""]);
+        assert!(lines[4].ends_with("7/7"), "{lines:?}");
+
+        state.select_previous_page();
+        let lines = rendered(PaginatedList::new(items(7), true), &mut state, 20, 5);
+        assert_eq!(lines[..3], ["> item 3", "item 4", "item 5"]);
+    }
+
+    #[test]
+    fn wraps_around_both_ends() {
+        assert_eq!(move_cursor(0, CursorMove::Previous, 10, 5, &[], true), 4);
+        assert_eq!(move_cursor(4, CursorMove::Next, 10, 5, &[], true), 0);
+    }
+
+    #[test]
+    fn clamps_at_both_ends_without_wrap() {
+        assert_eq!(move_cursor(0, CursorMove::Previous, 10, 5, &[], false), 0);
+        assert_eq!(move_cursor(4, CursorMove::Next, 10, 5, &[], false), 4);
+        assert_eq!(move_cursor(2, CursorMove::Next, 10, 5, &[], false), 3);
+    }
+}
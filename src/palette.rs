@@ -0,0 +1,261 @@
+//! An overlay for calling any BRP method, such as those added by the app's own `RemotePlugin`.
+
+use crate::{
+    inspector::{Inspector, InspectorState},
+    popup::centered,
+    text_input::TextInput,
+    theme::Theme,
+    Focus,
+};
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Style, Stylize},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Padding, StatefulWidget, Widget},
+};
+use serde_json::Value;
+use std::collections::VecDeque;
+
+const MAX_WIDTH: u16 = 70;
+const MAX_HEIGHT: u16 = 24;
+/// The number of calls kept in the history before the oldest are dropped.
+const HISTORY_CAPACITY: usize = 20;
+
+/// A method called from the palette, kept to call again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+    pub method: String,
+    /// The params as typed, empty for none.
+    pub params: String,
+}
+
+/// The calls made from the palette, most recent first.
+#[derive(Debug, Default)]
+pub struct CallHistory {
+    calls: VecDeque<Call>,
+}
+
+impl CallHistory {
+    /// Add a call, moving it to the front if it was already made.
+    pub fn push(&mut self, call: Call) {
+        self.calls.retain(|c| *c != call);
+        if self.calls.len() == HISTORY_CAPACITY {
+            self.calls.pop_back();
+        }
+        self.calls.push_front(call);
+    }
+}
+
+#[derive(Debug)]
+pub struct MethodPalette {
+    pub method: TextInput,
+    pub params: TextInput,
+    /// Whether keys go to the params rather than the method.
+    pub editing_params: bool,
+    /// The call from the history filled in, if any.
+    history_index: Option<usize>,
+    /// Why the last attempt to call wasn't sent, such as params that aren't JSON.
+    pub error: Option<String>,
+    /// The panel to focus once the palette is closed.
+    pub return_focus: Focus,
+}
+
+impl MethodPalette {
+    pub fn new(return_focus: Focus) -> Self {
+        Self {
+            method: TextInput::default(),
+            params: TextInput::default(),
+            editing_params: false,
+            history_index: None,
+            error: None,
+            return_focus,
+        }
+    }
+
+    /// The input keys go to.
+    pub fn input(&mut self) -> &mut TextInput {
+        match self.editing_params {
+            true => &mut self.params,
+            false => &mut self.method,
+        }
+    }
+
+    /// Fill in the next older call in the history, or the next newer one if not `older`.
+    pub fn browse_history(&mut self, history: &CallHistory, older: bool) {
+        let index = match (self.history_index, older) {
+            (None, true) => 0,
+            (None, false) => return,
+            (Some(n), true) => (n + 1).min(history.calls.len().saturating_sub(1)),
+            (Some(0), false) => {
+                self.history_index = None;
+                self.method.clear();
+                self.params.clear();
+                return;
+            }
+            (Some(n), false) => n - 1,
+        };
+        let Some(call) = history.calls.get(index) else {
+            return;
+        };
+        self.history_index = Some(index);
+        self.method = TextInput::with_value(call.method.as_str());
+        self.params = TextInput::with_value(call.params.as_str());
+    }
+
+    /// The call to make, or why it can't be made.
+    pub fn call(&self) -> Result<(Call, Option<Value>), String> {
+        let method = self.method.value().trim();
+        if method.is_empty() {
+            return Err(String::from("Type the name of a method to call"));
+        }
+        let params = self.params.value().trim();
+        let value = match params {
+            "" => None,
+            params => {
+                Some(serde_json::from_str(params).map_err(|err| format!("Invalid params: {err}"))?)
+            }
+        };
+        let call = Call {
+            method: method.to_string(),
+            params: params.to_string(),
+        };
+        Ok((call, value))
+    }
+}
+
+/// Renders a [`MethodPalette`] with the recent calls below it.
+pub struct MethodPaletteWidget<'a> {
+    history: &'a CallHistory,
+    theme: Theme,
+}
+
+impl<'a> MethodPaletteWidget<'a> {
+    pub fn new(history: &'a CallHistory) -> Self {
+        Self {
+            history,
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+}
+
+impl StatefulWidget for MethodPaletteWidget<'_> {
+    type State = MethodPalette;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let height = (self.history.calls.len() as u16 + 7).min(MAX_HEIGHT);
+        let popup_area = centered(
+            area,
+            area.width.saturating_sub(4).min(MAX_WIDTH),
+            height.min(area.height.saturating_sub(2)),
+        );
+        let block = Block::default()
+            .title(Line::from(" call method ").bold())
+            .borders(Borders::ALL)
+            .border_set(self.theme.border_set(border::THICK))
+            .border_style(Style::default().fg(self.theme.accent))
+            .padding(Padding::horizontal(1));
+        let inner = block.inner(popup_area);
+        Clear.render(popup_area, buf);
+        block.render(popup_area, buf);
+
+        let mut lines = vec![
+            input_line("method ", &state.method, !state.editing_params, &self.theme),
+            input_line("params ", &state.params, state.editing_params, &self.theme),
+        ];
+        if let Some(error) = &state.error {
+            lines.push(Line::from(Span::raw(error.as_str()).fg(self.theme.error)));
+        }
+        if !self.history.calls.is_empty() {
+            lines.push(Line::default());
+            lines.push(Line::styled("recent", self.theme.dim()));
+            for (n, call) in self.history.calls.iter().enumerate() {
+                let mut line = Line::from(vec![
+                    Span::raw(call.method.as_str()),
+                    Span::raw(" "),
+                    Span::styled(call.params.as_str(), self.theme.dim()),
+                ]);
+                if state.history_index == Some(n) {
+                    line = line.fg(self.theme.selection).bold();
+                }
+                lines.push(line);
+            }
+        }
+        for (y, line) in lines.iter().take(inner.height as usize).enumerate() {
+            buf.set_line(inner.x, inner.y + y as u16, line, inner.width);
+        }
+    }
+}
+
+/// A labelled input, with a cursor if it is being typed in.
+fn input_line<'a>(label: &'a str, input: &'a TextInput, editing: bool, theme: &Theme) -> Line<'a> {
+    let mut line = Line::from(vec![
+        Span::styled(label, theme.dim()),
+        Span::raw(input.value()),
+    ]);
+    if editing {
+        line.push_span(Span::raw(theme.cursor()).fg(theme.accent));
+    }
+    line
+}
+
+/// The result of a call shown over the UI until dismissed.
+#[derive(Debug)]
+pub struct MethodResponse {
+    pub method: String,
+    pub value: Value,
+    pub inspector: InspectorState,
+    /// The panel to focus once the response is dismissed.
+    pub return_focus: Focus,
+}
+
+/// Renders a [`MethodResponse`] in an inspector.
+pub struct MethodResponseWidget {
+    theme: Theme,
+    wrap: bool,
+}
+
+impl MethodResponseWidget {
+    pub fn new(theme: Theme) -> Self {
+        Self { theme, wrap: false }
+    }
+
+    /// See [`Inspector::wrap`].
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+}
+
+impl StatefulWidget for MethodResponseWidget {
+    type State = MethodResponse;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let popup_area = centered(
+            area,
+            area.width.saturating_sub(4).min(MAX_WIDTH),
+            area.height.saturating_sub(2).min(MAX_HEIGHT),
+        );
+        let block = Block::default()
+            .title(Line::from(format!(" {} ", state.method)).bold())
+            .title_bottom(Line::from(vec![
+                Span::styled(" esc", self.theme.dim().bold()),
+                Span::styled(" dismiss ", self.theme.dim()),
+            ]))
+            .borders(Borders::ALL)
+            .border_set(self.theme.border_set(border::THICK))
+            .border_style(Style::default().fg(self.theme.accent))
+            .padding(Padding::horizontal(1));
+        Clear.render(popup_area, buf);
+        Inspector::new(&state.value, true)
+            .theme(self.theme)
+            .wrap(self.wrap)
+            .block(block)
+            .render(popup_area, buf, &mut state.inspector);
+    }
+}
@@ -0,0 +1,119 @@
+//! A glance at the components of the entity highlighted in the entities list, without focusing
+//! the components panel.
+
+use crate::{
+    components,
+    inspector::{BoolStyle, Inspector, InspectorState},
+    theme::Theme,
+};
+use bevy_ecs::entity::Entity;
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Style, Stylize},
+    symbols::border,
+    text::Line,
+    widgets::{Block, Borders, Clear, Padding, StatefulWidget, Widget},
+};
+use serde_json::{Map, Value};
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+/// How long the components of a peeked entity are kept, so moving back to it shows them straight
+/// away rather than waiting for them to be fetched again.
+const CACHE_DURATION: Duration = Duration::from_secs(5);
+
+/// The components of the recently peeked entities.
+#[derive(Debug, Default)]
+pub struct PeekCache {
+    entries: HashMap<Entity, (Instant, Vec<(String, Value)>)>,
+}
+
+impl PeekCache {
+    pub fn insert(&mut self, entity: Entity, components: &[(String, Value)]) {
+        let now = Instant::now();
+        self.entries
+            .retain(|_, (at, _)| now.duration_since(*at) < CACHE_DURATION);
+        self.entries.insert(entity, (now, components.to_vec()));
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&[(String, Value)]> {
+        self.entries
+            .get(&entity)
+            .filter(|(at, _)| at.elapsed() < CACHE_DURATION)
+            .map(|(_, components)| components.as_slice())
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Renders the components of an entity collapsed to a line each, or that they are loading when
+/// there are none yet.
+pub struct PeekWidget<'a> {
+    title: Line<'a>,
+    components: Option<&'a [(String, Value)]>,
+    bool_style: BoolStyle,
+    theme: Theme,
+}
+
+impl<'a> PeekWidget<'a> {
+    pub fn new(title: Line<'a>, components: Option<&'a [(String, Value)]>) -> Self {
+        Self {
+            title,
+            components,
+            bool_style: BoolStyle::default(),
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn bool_style(mut self, bool_style: BoolStyle) -> Self {
+        self.bool_style = bool_style;
+        self
+    }
+}
+
+impl Widget for PeekWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default()
+            .title(Line::from(" peek ").bold())
+            .title(self.title)
+            .borders(Borders::ALL)
+            .border_set(self.theme.border_set(border::THICK))
+            .border_style(Style::default().fg(self.theme.accent))
+            .padding(Padding::horizontal(1));
+        let inner = block.inner(area);
+        Clear.render(area, buf);
+        block.render(area, buf);
+
+        let Some(components) = self.components else {
+            let text = self
+                .theme
+                .symbol("Loading components…", "Loading components...");
+            Line::from(text).bold().render(inner, buf);
+            return;
+        };
+        // Each component is a collapsed field of one object, named like the components panel.
+        let names = components::display_names(components, false);
+        let value = Value::Object(
+            names
+                .iter()
+                .cloned()
+                .zip(components.iter().map(|(_, value)| value.clone()))
+                .collect::<Map<_, _>>(),
+        );
+        let collapsed: HashSet<_> = names.iter().map(|name| format!(".{name}")).collect();
+        Inspector::new(&value, false)
+            .theme(self.theme)
+            .bool_style(self.bool_style)
+            .collapsed(&collapsed)
+            .render(inner, buf, &mut InspectorState::default());
+    }
+}
@@ -0,0 +1,151 @@
+//! An overlay for picking a component to insert into an entity.
+
+use crate::{
+    paginated_list::{PaginatedList, PaginatedListState},
+    popup::centered,
+    registry::RegistryStatus,
+    search::Search,
+    theme::Theme,
+    Focus,
+};
+use bevy_ecs::entity::Entity;
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Style, Stylize},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Padding, Paragraph, StatefulWidget, Widget, Wrap},
+};
+
+const MAX_WIDTH: u16 = 70;
+const MAX_HEIGHT: u16 = 24;
+
+#[derive(Debug)]
+pub struct ComponentPicker {
+    /// The entity the picked component will be inserted into.
+    pub entity: Entity,
+    /// The panel to focus once the picker is closed.
+    pub return_focus: Focus,
+    pub search: Search,
+    pub list: PaginatedListState,
+}
+
+impl ComponentPicker {
+    pub fn new(entity: Entity, return_focus: Focus) -> Self {
+        Self {
+            entity,
+            return_focus,
+            search: Search::default(),
+            list: PaginatedListState::default(),
+        }
+    }
+}
+
+/// Renders a [`ComponentPicker`] listing the components in the registry, or a text field for the
+/// type path when the registry is unavailable.
+pub struct ComponentPickerWidget<'a> {
+    registry: &'a RegistryStatus,
+    wrap: bool,
+    theme: Theme,
+}
+
+impl<'a> ComponentPickerWidget<'a> {
+    pub fn new(registry: &'a RegistryStatus) -> Self {
+        Self {
+            registry,
+            wrap: true,
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// See [`PaginatedList::wrap`].
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+}
+
+impl StatefulWidget for ComponentPickerWidget<'_> {
+    type State = ComponentPicker;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let popup_area = centered(
+            area,
+            area.width.saturating_sub(4).min(MAX_WIDTH),
+            area.height.saturating_sub(2).min(MAX_HEIGHT),
+        );
+        let block = Block::default()
+            .title(Line::from(format!(" add component to {} ", state.entity)).bold())
+            .borders(Borders::ALL)
+            .border_set(self.theme.border_set(border::THICK))
+            .border_style(Style::default().fg(self.theme.accent))
+            .padding(Padding::horizontal(1));
+        let inner = block.inner(popup_area);
+        Clear.render(popup_area, buf);
+        block.render(popup_area, buf);
+        if inner.height < 3 {
+            return;
+        }
+
+        let search_area = Rect { height: 1, ..inner };
+        let list_area = Rect {
+            y: inner.y + 2,
+            height: inner.height - 2,
+            ..inner
+        };
+        Line::from(vec![
+            Span::styled("/ ", self.theme.dim()),
+            Span::raw(state.search.query()),
+            Span::raw(self.theme.cursor()).fg(self.theme.accent),
+        ])
+        .render(search_area, buf);
+
+        // The list is always rendered, even when empty, so pending cursor moves are applied.
+        let (lines, message) = match self.registry {
+            RegistryStatus::Available(registry) => {
+                let matches = state.search.matches(&registry.components);
+                let lines: Vec<_> = matches
+                    .iter()
+                    .map(|n| {
+                        let component = &registry.components[*n];
+                        Line::from(vec![
+                            Span::raw(component.short_path.as_str()).bold(),
+                            Span::raw(" "),
+                            Span::styled(component.type_path.as_str(), self.theme.dim()),
+                        ])
+                    })
+                    .collect();
+                let message = lines.is_empty().then_some("No components match");
+                (lines, message)
+            }
+            RegistryStatus::Unknown | RegistryStatus::Loading | RegistryStatus::Failed(_) => {
+                let message = self
+                    .theme
+                    .symbol("Loading the registry…", "Loading the registry...");
+                (Vec::new(), Some(message))
+            }
+            RegistryStatus::Unavailable => (
+                Vec::new(),
+                Some(
+                    "The app doesn't provide its registry, type the full type path of the \
+                     component and press enter to insert it with an empty value",
+                ),
+            ),
+        };
+        PaginatedList::new(lines, true)
+            .theme(self.theme)
+            .wrap(self.wrap)
+            .render(list_area, buf, &mut state.list);
+        if let Some(message) = message {
+            Paragraph::new(message)
+                .style(self.theme.dim())
+                .wrap(Wrap { trim: true })
+                .render(list_area, buf);
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use crate::{
+    brp::{EntityMeta, RemoteError},
+    theme::Theme,
+};
+use bevy_ecs::entity::Entity;
+use disqualified::ShortName;
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Style, Stylize},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Padding, Paragraph, Widget, Wrap},
+};
+use serde_json::Value;
+
+const MAX_WIDTH: u16 = 70;
+
+/// A modal describing a failed operation, rendered over the rest of the UI until dismissed.
+#[derive(Debug, Clone)]
+pub struct ErrorPopup {
+    title: String,
+    message: String,
+    code: Option<i16>,
+    data: Option<Value>,
+}
+
+impl ErrorPopup {
+    /// Create a popup from the given error, keeping the code and data of BRP error payloads.
+    pub fn new(title: impl Into<String>, err: &anyhow::Error) -> Self {
+        match err.downcast_ref::<RemoteError>() {
+            Some(RemoteError(err)) => Self {
+                title: title.into(),
+                message: err.message.clone(),
+                code: Some(err.code),
+                data: err.data.clone(),
+            },
+            None => Self {
+                title: title.into(),
+                message: format!("{err:#}"),
+                code: None,
+                data: None,
+            },
+        }
+    }
+
+    /// The widget showing this popup in the given theme.
+    pub fn widget(&self, theme: Theme) -> ErrorPopupWidget<'_> {
+        ErrorPopupWidget { popup: self, theme }
+    }
+
+    fn lines(&self, theme: &Theme) -> Vec<Line<'_>> {
+        let mut lines = vec![Line::raw(self.message.as_str())];
+        if let Some(code) = self.code {
+            lines.push(Line::default());
+            lines.push(Line::from(vec![
+                Span::raw("code: ").bold(),
+                Span::raw(code.to_string()),
+            ]));
+        }
+        if let Some(data) = &self.data {
+            lines.push(Line::from(Span::raw("data:").bold()));
+            let pretty = serde_json::to_string_pretty(data).unwrap_or_else(|_| data.to_string());
+            lines.extend(pretty.lines().map(|l| Line::raw(l.to_string())));
+        }
+        lines.push(Line::default());
+        lines.push(Line::from(vec![
+            Span::styled("esc", theme.dim().bold()),
+            Span::styled(" dismiss", theme.dim()),
+        ]));
+        lines
+    }
+}
+
+pub struct ErrorPopupWidget<'a> {
+    popup: &'a ErrorPopup,
+    theme: Theme,
+}
+
+impl Widget for ErrorPopupWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let lines = self.popup.lines(&self.theme);
+        let width = area.width.saturating_sub(4).min(MAX_WIDTH);
+        // Account for the lines that will wrap, the borders and the padding.
+        let inner_width = width.saturating_sub(4).max(1) as usize;
+        let height = lines
+            .iter()
+            .map(|line| line.width().max(1).div_ceil(inner_width) as u16)
+            .sum::<u16>()
+            .saturating_add(2)
+            .min(area.height);
+        let popup_area = centered(area, width, height);
+
+        let block = Block::default()
+            .title(Line::from(format!(" {} ", self.popup.title)).bold())
+            .borders(Borders::ALL)
+            .border_set(self.theme.border_set(border::THICK))
+            .border_style(Style::default().fg(self.theme.error))
+            .padding(Padding::horizontal(1));
+
+        Clear.render(popup_area, buf);
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .render(popup_area, buf);
+    }
+}
+
+/// A rect of the given size in the center of `area`.
+pub fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// A modal summarizing an entity, rendered over the rest of the UI until dismissed.
+#[derive(Debug, Clone)]
+pub struct EntityPopup {
+    id: Entity,
+    name: Option<String>,
+    parent: Option<Entity>,
+    children: Vec<Entity>,
+    /// The short names of the entity's components.
+    components: Vec<String>,
+}
+
+impl EntityPopup {
+    /// Summarize `entity` with the components last fetched for it, finding its children among
+    /// `entities`.
+    pub fn new(
+        entity: &EntityMeta,
+        entities: &[EntityMeta],
+        components: &[(String, Value)],
+    ) -> Self {
+        let mut components: Vec<_> = components
+            .iter()
+            .map(|(type_path, _)| ShortName(type_path).to_string())
+            .collect();
+        components.sort();
+        Self {
+            id: entity.id,
+            name: entity.name.clone(),
+            parent: entity.parent,
+            children: entities
+                .iter()
+                .filter(|e| e.parent == Some(entity.id))
+                .map(|e| e.id)
+                .collect(),
+            components,
+        }
+    }
+
+    /// The widget showing this popup in the given theme.
+    pub fn widget(&self, theme: Theme) -> EntityPopupWidget<'_> {
+        EntityPopupWidget { popup: self, theme }
+    }
+
+    fn lines(&self, theme: &Theme) -> Vec<Line<'_>> {
+        let field = |name: &'static str, value: String| {
+            Line::from(vec![Span::raw(name).bold(), Span::raw(value)])
+        };
+        let mut lines = vec![
+            field(
+                "id: ",
+                format!(
+                    "{} (index {}, generation {})",
+                    self.id,
+                    self.id.index(),
+                    self.id.generation()
+                ),
+            ),
+            field(
+                "name: ",
+                self.name.clone().unwrap_or_else(|| String::from("none")),
+            ),
+        ];
+        if let Some(parent) = self.parent {
+            lines.push(field("parent: ", parent.to_string()));
+        }
+        if !self.children.is_empty() {
+            let children: Vec<_> = self.children.iter().map(Entity::to_string).collect();
+            lines.push(field("children: ", children.join(", ")));
+        }
+        lines.push(field("components: ", self.components.len().to_string()));
+        lines.extend(
+            self.components
+                .iter()
+                .map(|name| Line::from(vec![Span::raw("  "), Span::raw(name.as_str())])),
+        );
+        lines.push(Line::default());
+        lines.push(Line::from(vec![
+            Span::styled("esc", theme.dim().bold()),
+            Span::styled(" dismiss", theme.dim()),
+        ]));
+        lines
+    }
+}
+
+pub struct EntityPopupWidget<'a> {
+    popup: &'a EntityPopup,
+    theme: Theme,
+}
+
+impl Widget for EntityPopupWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let lines = self.popup.lines(&self.theme);
+        let width = area.width.saturating_sub(4).min(MAX_WIDTH);
+        let inner_width = width.saturating_sub(4).max(1) as usize;
+        let height = lines
+            .iter()
+            .map(|line| line.width().max(1).div_ceil(inner_width) as u16)
+            .sum::<u16>()
+            .saturating_add(2)
+            .min(area.height);
+        let popup_area = centered(area, width, height);
+
+        let block = Block::default()
+            .title(Line::from(format!(" entity {} ", self.popup.id)).bold())
+            .borders(Borders::ALL)
+            .border_set(self.theme.border_set(border::THICK))
+            .border_style(Style::default().fg(self.theme.accent))
+            .padding(Padding::horizontal(1));
+
+        Clear.render(popup_area, buf);
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .render(popup_area, buf);
+    }
+}
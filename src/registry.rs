@@ -0,0 +1,524 @@
+//! The types registered in the connected app, as described by the `bevy/registry/schema` method.
+
+use crate::{
+    inspector::{path_segments, PathSegment},
+    search::{fuzzy_score, Searchable},
+};
+use disqualified::ShortName;
+use serde_json::{Map, Value};
+use std::{collections::HashMap, time::Instant};
+
+/// How deep to follow nested types when building a default value, in case a type refers to
+/// itself.
+const MAX_DEFAULT_DEPTH: usize = 16;
+const ENTITY_TYPE_PATH: &str = "bevy_ecs::entity::Entity";
+
+/// Whether the registry of the connected app has been fetched. The registry doesn't change while
+/// the app is running so it is only fetched once.
+#[derive(Debug, Default)]
+pub enum RegistryStatus {
+    #[default]
+    Unknown,
+    Loading,
+    Available(Registry),
+    /// The app doesn't support the registry method, such as apps using Bevy 0.15.
+    Unavailable,
+    /// Fetching the registry failed at the given time for another reason, such as timing out.
+    /// It is fetched again once the entities update after a short delay.
+    Failed(Instant),
+}
+
+/// The JSON schemas of the registered types, keyed by type path.
+#[derive(Debug, Clone)]
+pub struct Registry {
+    /// The registered components sorted by name.
+    pub components: Vec<RegisteredComponent>,
+    schemas: HashMap<String, Value>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RegisteredComponent {
+    pub type_path: String,
+    pub short_path: String,
+}
+
+impl Searchable for RegisteredComponent {
+    fn score(&self, query: &str) -> Option<i64> {
+        fuzzy_score(&self.short_path, query).max(fuzzy_score(&self.type_path, query))
+    }
+}
+
+impl Registry {
+    pub fn new(schemas: HashMap<String, Value>) -> Self {
+        let mut components: Vec<_> = schemas
+            .iter()
+            .filter(|(_, schema)| reflects(schema, "Component"))
+            .map(|(type_path, schema)| RegisteredComponent {
+                type_path: type_path.clone(),
+                short_path: schema
+                    .get("shortPath")
+                    .and_then(Value::as_str)
+                    .map(String::from)
+                    .unwrap_or_else(|| ShortName(type_path).to_string()),
+            })
+            .collect();
+        components
+            .sort_by(|a, b| (&a.short_path, &a.type_path).cmp(&(&b.short_path, &b.type_path)));
+        Self {
+            components,
+            schemas,
+        }
+    }
+
+    /// A value for the given type built from its schema, such as zero for numbers and the first
+    /// variant for enums. Types without a schema default to an empty object.
+    pub fn default_value(&self, type_path: &str) -> Value {
+        match self.schemas.get(type_path) {
+            Some(schema) => self.default_for_schema(schema, 0),
+            None => Value::Object(Map::new()),
+        }
+    }
+
+    /// The doc comment of the given type, if the app was built with documentation included.
+    pub fn description(&self, type_path: &str) -> Option<&str> {
+        self.schemas.get(type_path)?.get("description")?.as_str()
+    }
+
+    /// The short name of the type of the field at `path` within the given type, where `path` is
+    /// in the format the inspector uses such as `.translation.x` or `.points[2]`.
+    pub fn field_type(&self, type_path: &str, path: &str) -> Option<&str> {
+        let schema = self.field_schema(type_path, path)?;
+        schema
+            .get("shortPath")
+            .or_else(|| schema.get("typePath"))?
+            .as_str()
+    }
+
+    /// Whether the field at `path` within the given type holds an entity, either directly or
+    /// wrapped in a tuple struct like `Parent`.
+    pub fn is_entity(&self, type_path: &str, path: &str) -> bool {
+        let Some(mut schema) = self.field_schema(type_path, path) else {
+            return false;
+        };
+        // Tuple structs with a single field are serialized as just that field.
+        for _ in 0..MAX_DEFAULT_DEPTH {
+            match schema
+                .get("prefixItems")
+                .and_then(Value::as_array)
+                .map(Vec::as_slice)
+            {
+                Some([inner]) => schema = self.resolve(inner),
+                _ => break,
+            }
+        }
+        schema.get("typePath").and_then(Value::as_str) == Some(ENTITY_TYPE_PATH)
+    }
+
+    /// The kind of the type of the field at `path` within the given type, such as `Struct`,
+    /// `Enum` or `TupleStruct`.
+    pub fn field_kind(&self, type_path: &str, path: &str) -> Option<&str> {
+        self.field_schema(type_path, path)?.get("kind")?.as_str()
+    }
+
+    /// The names of the variants of the enum at `path` within the given type.
+    pub fn variants(&self, type_path: &str, path: &str) -> Option<Vec<&str>> {
+        let variants = self
+            .field_schema(type_path, path)?
+            .get("oneOf")?
+            .as_array()?;
+        variants
+            .iter()
+            .map(|variant| {
+                variant
+                    .as_str()
+                    .or_else(|| variant.get("shortPath")?.as_str())
+            })
+            .collect()
+    }
+
+    /// The value of the named variant of the enum at `path` within the given type, with any data
+    /// it holds set to defaults.
+    pub fn variant_value(&self, type_path: &str, path: &str, name: &str) -> Option<Value> {
+        let schema = self.field_schema(type_path, path)?;
+        let variants = schema.get("oneOf")?.as_array()?;
+        if variants
+            .iter()
+            .any(|variant| variant.as_str() == Some(name))
+        {
+            return Some(Value::String(name.to_string()));
+        }
+        let mut map = Map::new();
+        map.insert(
+            name.to_string(),
+            self.default_for_schema(variant(schema, name)?, 0),
+        );
+        Some(Value::Object(map))
+    }
+
+    /// Check that `value` fits the schema of the field at `path` within the given type. Fields
+    /// the registry doesn't describe are assumed to be valid.
+    pub fn validate(&self, type_path: &str, path: &str, value: &Value) -> Result<(), String> {
+        match self.field_schema(type_path, path) {
+            Some(schema) => self.validate_schema(schema, value, 0),
+            None => Ok(()),
+        }
+    }
+
+    /// The path to the field at `path` within the given type in Bevy's reflection path syntax.
+    /// The paths mostly match, but single field tuple structs are serialized as just their field
+    /// and some structs, such as `Vec3`, are serialized as arrays.
+    pub fn reflect_path(&self, type_path: &str, path: &str) -> Option<String> {
+        self.walk(type_path, path)?.1
+    }
+
+    fn field_schema(&self, type_path: &str, path: &str) -> Option<&Value> {
+        Some(self.walk(type_path, path)?.0)
+    }
+
+    /// Follow `path` from the given type to the schema of the field it leads to, along with the
+    /// reflection path to it if it has one.
+    fn walk(&self, type_path: &str, path: &str) -> Option<(&Value, Option<String>)> {
+        let mut schema = self.resolve(self.schemas.get(type_path)?);
+        let mut reflect_path = String::new();
+        // The data of an enum variant can't be set on its own, only the fields within it.
+        let mut ends_at_variant = false;
+        for segment in path_segments(path) {
+            // Tuple structs with a single field are serialized as just that field.
+            if let Some([inner]) = schema
+                .get("prefixItems")
+                .and_then(Value::as_array)
+                .map(Vec::as_slice)
+            {
+                schema = self.resolve(inner);
+                reflect_path.push_str(".0");
+            }
+            ends_at_variant = false;
+            let field = match segment {
+                PathSegment::Field(name) => {
+                    match schema.get("properties").and_then(|p| p.get(name)) {
+                        Some(field) => {
+                            reflect_path.push_str(&format!(".{name}"));
+                            field
+                        }
+                        // The fields of the current variant are accessed as fields of the enum.
+                        None => {
+                            ends_at_variant = true;
+                            variant(schema, name)?
+                        }
+                    }
+                }
+                PathSegment::Index(n) => {
+                    if let Some(item) = schema.get("prefixItems").and_then(|items| items.get(n)) {
+                        reflect_path.push_str(&format!(".{n}"));
+                        item
+                    } else if let Some(items) = schema.get("items") {
+                        reflect_path.push_str(&format!("[{n}]"));
+                        items
+                    } else {
+                        // Structs serialized as arrays list their fields in order.
+                        let name = schema.get("required")?.get(n)?.as_str()?;
+                        reflect_path.push_str(&format!(".{name}"));
+                        schema.get("properties")?.get(name)?
+                    }
+                }
+            };
+            schema = self.resolve(field);
+        }
+        Some((schema, (!ends_at_variant).then_some(reflect_path)))
+    }
+
+    fn validate_schema(&self, schema: &Value, value: &Value, depth: usize) -> Result<(), String> {
+        if depth > MAX_DEFAULT_DEPTH {
+            return Ok(());
+        }
+        let schema = self.resolve(schema);
+        let name = schema
+            .get("shortPath")
+            .and_then(Value::as_str)
+            .unwrap_or("value");
+
+        if let Some(variants) = schema.get("oneOf").and_then(Value::as_array) {
+            return match value {
+                Value::String(s) if variants.iter().any(|v| v.as_str() == Some(s)) => Ok(()),
+                Value::Object(map) if map.len() == 1 => {
+                    let (key, payload) = map.iter().next().unwrap();
+                    match variant(schema, key) {
+                        Some(variant) => self.validate_schema(variant, payload, depth + 1),
+                        None => Err(format!("{key} isn't a variant of {name}")),
+                    }
+                }
+                _ => Err(format!("expected a variant of {name}")),
+            };
+        }
+
+        if let Some(items) = schema.get("prefixItems").and_then(Value::as_array) {
+            return match (items.as_slice(), value) {
+                ([inner], value) => self.validate_schema(inner, value, depth + 1),
+                (items, Value::Array(values)) if items.len() == values.len() => items
+                    .iter()
+                    .zip(values)
+                    .try_for_each(|(item, value)| self.validate_schema(item, value, depth + 1)),
+                (items, _) => Err(format!("expected {} items for {name}", items.len())),
+            };
+        }
+
+        let valid = match (schema.get("type").and_then(Value::as_str), value) {
+            (Some("float" | "number"), Value::Number(_)) => true,
+            (Some("uint"), Value::Number(n)) => n.is_u64(),
+            (Some("int" | "integer"), Value::Number(n)) => n.is_i64() || n.is_u64(),
+            (Some("boolean"), Value::Bool(_)) => true,
+            (Some("string"), Value::String(_)) => true,
+            (Some("null"), Value::Null) => true,
+            (Some("array" | "set"), Value::Array(values)) => {
+                if let Some(items) = schema.get("items") {
+                    for value in values {
+                        self.validate_schema(items, value, depth + 1)?;
+                    }
+                }
+                true
+            }
+            (Some("object"), Value::Object(map)) => {
+                if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+                    for (key, value) in map {
+                        let field = properties
+                            .get(key)
+                            .ok_or_else(|| format!("{name} has no field {key}"))?;
+                        self.validate_schema(field, value, depth + 1)?;
+                    }
+                }
+                true
+            }
+            // Types the schema doesn't describe can't be checked.
+            (None, _) => true,
+            _ => false,
+        };
+        match valid {
+            true => Ok(()),
+            false => Err(format!("{value} isn't a valid {name}")),
+        }
+    }
+
+    /// Follow references and field wrappers to the schema of the type itself.
+    fn resolve<'a>(&'a self, mut schema: &'a Value) -> &'a Value {
+        for _ in 0..MAX_DEFAULT_DEPTH {
+            if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+                match self.schemas.get(reference.trim_start_matches("#/$defs/")) {
+                    Some(referenced) => schema = referenced,
+                    None => break,
+                }
+            } else if let Some(field_type @ Value::Object(_)) = schema.get("type") {
+                schema = field_type;
+            } else {
+                break;
+            }
+        }
+        schema
+    }
+
+    fn default_for_schema(&self, schema: &Value, depth: usize) -> Value {
+        if depth > MAX_DEFAULT_DEPTH {
+            return Value::Null;
+        }
+
+        // References to other types look like `{ "$ref": "#/$defs/f32" }`.
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let type_path = reference.trim_start_matches("#/$defs/");
+            return match self.schemas.get(type_path) {
+                Some(schema) => self.default_for_schema(schema, depth + 1),
+                None => Value::Null,
+            };
+        }
+
+        // Enums list their variants, unit variants are plain strings.
+        if let Some(variant) = schema
+            .get("oneOf")
+            .and_then(Value::as_array)
+            .and_then(|variants| variants.first())
+        {
+            if variant.is_string() {
+                return variant.clone();
+            }
+            let name = variant
+                .get("shortPath")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let mut map = Map::new();
+            map.insert(
+                name.to_string(),
+                self.default_for_schema(variant, depth + 1),
+            );
+            return Value::Object(map);
+        }
+
+        // Fields are described as `{ "type": { "$ref": ... } }`.
+        let ty = match schema.get("type") {
+            Some(field_type @ Value::Object(_)) => {
+                return self.default_for_schema(field_type, depth + 1)
+            }
+            Some(Value::String(ty)) => ty.as_str(),
+            _ => return Value::Null,
+        };
+
+        // Tuples and tuple structs list their fields in order, ones with a single field are
+        // serialized as just that field.
+        if let Some(items) = schema.get("prefixItems").and_then(Value::as_array) {
+            let mut fields: Vec<_> = items
+                .iter()
+                .map(|item| self.default_for_schema(item, depth + 1))
+                .collect();
+            return match fields.len() {
+                1 => fields.remove(0),
+                _ => Value::Array(fields),
+            };
+        }
+
+        match ty {
+            "object" => Value::Object(
+                schema
+                    .get("properties")
+                    .and_then(Value::as_object)
+                    .into_iter()
+                    .flatten()
+                    .map(|(name, field)| (name.clone(), self.default_for_schema(field, depth + 1)))
+                    .collect(),
+            ),
+            "array" | "set" => Value::Array(Vec::new()),
+            "string" => Value::String(String::new()),
+            "boolean" => Value::Bool(false),
+            "float" | "uint" | "int" | "number" | "integer" => Value::from(0),
+            _ => Value::Null,
+        }
+    }
+}
+
+/// The schema of the enum variant with the given name.
+fn variant<'a>(schema: &'a Value, name: &str) -> Option<&'a Value> {
+    schema
+        .get("oneOf")?
+        .as_array()?
+        .iter()
+        .find(|variant| variant.get("shortPath").and_then(Value::as_str) == Some(name))
+}
+
+/// If the schema lists the given trait among the reflected traits of the type.
+fn reflects(schema: &Value, reflect_trait: &str) -> bool {
+    schema
+        .get("reflectTypes")
+        .and_then(Value::as_array)
+        .is_some_and(|traits| traits.iter().any(|t| t.as_str() == Some(reflect_trait)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A registry with a `Player` component holding a few kinds of fields.
+    fn registry() -> Registry {
+        let schemas = json!({
+            "game::Player": {
+                "typePath": "game::Player",
+                "shortPath": "Player",
+                "kind": "Struct",
+                "type": "object",
+                "reflectTypes": ["Component"],
+                "properties": {
+                    "lives": { "type": { "$ref": "#/$defs/u32" } },
+                    "speed": { "type": { "$ref": "#/$defs/f32" } },
+                    "team": { "type": { "$ref": "#/$defs/game::Team" } },
+                    "spawn": { "type": { "$ref": "#/$defs/(f32, f32)" } },
+                },
+            },
+            "game::Team": {
+                "typePath": "game::Team",
+                "shortPath": "Team",
+                "kind": "Enum",
+                "type": "string",
+                "oneOf": [
+                    "Red",
+                    {
+                        "shortPath": "Custom",
+                        "kind": "Tuple",
+                        "type": "array",
+                        "prefixItems": [{ "type": { "$ref": "#/$defs/String" } }],
+                    },
+                ],
+            },
+            "(f32, f32)": {
+                "typePath": "(f32, f32)",
+                "shortPath": "(f32, f32)",
+                "kind": "Tuple",
+                "type": "array",
+                "prefixItems": [
+                    { "type": { "$ref": "#/$defs/f32" } },
+                    { "type": { "$ref": "#/$defs/f32" } },
+                ],
+            },
+            "u32": { "typePath": "u32", "shortPath": "u32", "kind": "Value", "type": "uint" },
+            "f32": { "typePath": "f32", "shortPath": "f32", "kind": "Value", "type": "float" },
+            "String": {
+                "typePath": "String",
+                "shortPath": "String",
+                "kind": "Value",
+                "type": "string",
+            },
+        });
+        Registry::new(serde_json::from_value(schemas).unwrap())
+    }
+
+    #[test]
+    fn validates_fields_against_their_types() {
+        let registry = registry();
+        let valid = |path: &str, value: Value| registry.validate("game::Player", path, &value);
+
+        assert_eq!(valid(".lives", json!(3)), Ok(()));
+        assert_eq!(
+            valid(".lives", json!(-1)),
+            Err("-1 isn't a valid u32".to_string())
+        );
+        assert_eq!(valid(".speed", json!(1.5)), Ok(()));
+        assert!(valid(".speed", json!("fast")).is_err());
+        assert_eq!(valid(".spawn", json!([0.0, 2.5])), Ok(()));
+        assert_eq!(
+            valid(".spawn", json!([0.0])),
+            Err("expected 2 items for (f32, f32)".to_string())
+        );
+    }
+
+    #[test]
+    fn validates_enum_variants() {
+        let registry = registry();
+        let valid = |value: Value| registry.validate("game::Player", ".team", &value);
+
+        assert_eq!(valid(json!("Red")), Ok(()));
+        assert_eq!(valid(json!({ "Custom": "Purple" })), Ok(()));
+        assert_eq!(
+            valid(json!({ "Blue": null })),
+            Err("Blue isn't a variant of Team".to_string())
+        );
+        assert!(valid(json!({ "Custom": 7 })).is_err());
+        assert!(valid(json!("Blue")).is_err());
+    }
+
+    #[test]
+    fn validates_whole_components() {
+        let registry = registry();
+        let player = json!({ "lives": 3, "speed": 2.0, "team": "Red", "spawn": [0, 0] });
+        assert_eq!(registry.validate("game::Player", "", &player), Ok(()));
+        assert_eq!(
+            registry.validate("game::Player", "", &json!({ "mana": 10 })),
+            Err("Player has no field mana".to_string())
+        );
+    }
+
+    #[test]
+    fn undescribed_fields_are_assumed_valid() {
+        let registry = registry();
+        let anything = json!({ "any": ["thing"] });
+        assert_eq!(registry.validate("game::Enemy", "", &anything), Ok(()));
+        assert_eq!(
+            registry.validate("game::Player", ".mana", &anything),
+            Ok(())
+        );
+    }
+}
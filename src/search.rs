@@ -0,0 +1,154 @@
+//! Fuzzy searching of lists such as the entities list.
+
+use crate::{brp::EntityMeta, text_input::TextInput};
+
+/// Something that can be found by a [`Search`].
+pub trait Searchable {
+    /// How well this matches the query, or [`None`] if it doesn't match at all.
+    fn score(&self, query: &str) -> Option<i64>;
+}
+
+impl Searchable for EntityMeta {
+    fn score(&self, query: &str) -> Option<i64> {
+        fuzzy_score(&self.name(), query).max(fuzzy_score(&self.id.to_string(), query))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Search {
+    pub input: TextInput,
+    cache: Option<SearchCache>,
+}
+
+/// The result of the last search, reused while neither the query nor the items change.
+#[derive(Debug)]
+struct SearchCache {
+    query: String,
+    /// Indices into the items, best matches first.
+    matches: Vec<usize>,
+}
+
+impl Search {
+    pub fn query(&self) -> &str {
+        self.input.value()
+    }
+
+    /// Forget the cached result, this must be called whenever the items change.
+    pub fn invalidate(&mut self) {
+        self.cache = None;
+    }
+
+    /// The indices of the items matching the query, best matches first. Every item matches an
+    /// empty query and is kept in its original order.
+    ///
+    /// The result is cached until the query or items change, so it is only worked out again
+    /// after typing rather than on every frame drawn. When the query has only grown since the
+    /// last search just the previous matches are rescored, since a longer query can only match a
+    /// subset of them.
+    pub fn matches<T: Searchable>(&mut self, items: &[T]) -> &[usize] {
+        let query = self.input.value();
+        let matches = match self.cache.take() {
+            Some(cache) if cache.query == query => cache.matches,
+            _ if query.is_empty() => (0..items.len()).collect(),
+            cache => {
+                let candidates = match cache {
+                    Some(cache) if query.starts_with(&cache.query) => cache.matches,
+                    _ => (0..items.len()).collect(),
+                };
+                let mut scored: Vec<_> = candidates
+                    .into_iter()
+                    .filter_map(|n| Some((n, items[n].score(query)?)))
+                    .collect();
+                // The sort is stable so equal scores keep the original order.
+                scored.sort_by_key(|(_, score)| -score);
+                scored.into_iter().map(|(n, _)| n).collect()
+            }
+        };
+        let cache = self.cache.insert(SearchCache {
+            query: query.to_string(),
+            matches,
+        });
+        &cache.matches
+    }
+}
+
+/// Score how well `needle` matches `haystack` as a case insensitive subsequence, or [`None`] if
+/// it doesn't. Consecutive characters and matches at the start of words score higher.
+pub fn fuzzy_score(haystack: &str, needle: &str) -> Option<i64> {
+    let mut score = 0;
+    let mut haystack_chars = haystack.chars().flat_map(char::to_lowercase).enumerate();
+    let mut previous_match = None;
+    let mut previous_char = None;
+
+    for needle_char in needle.chars().flat_map(char::to_lowercase) {
+        loop {
+            let (n, ch) = haystack_chars.next()?;
+            let before = previous_char.replace(ch);
+            if ch != needle_char {
+                continue;
+            }
+            score += 1;
+            if previous_match.is_some_and(|p| p + 1 == n) {
+                score += 5;
+            }
+            if before.is_none_or(|c: char| !c.is_alphanumeric()) {
+                score += 3;
+            }
+            previous_match = Some(n);
+            break;
+        }
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl Searchable for &str {
+        fn score(&self, query: &str) -> Option<i64> {
+            fuzzy_score(self, query)
+        }
+    }
+
+    fn search(query: &str) -> Search {
+        Search {
+            input: TextInput::with_value(query.to_string()),
+            cache: None,
+        }
+    }
+
+    #[test]
+    fn empty_query_matches_everything_in_order() {
+        let items = ["b", "a", "c"];
+        assert_eq!(search("").matches(&items), [0, 1, 2]);
+    }
+
+    #[test]
+    fn best_matches_come_first() {
+        let items = ["transform", "global_transform", "visibility"];
+        assert_eq!(search("tra").matches(&items), [0, 1]);
+    }
+
+    #[test]
+    fn growing_query_narrows_previous_matches() {
+        let items = ["camera", "camera_3d", "window"];
+        let mut search = search("cam");
+        assert_eq!(search.matches(&items), [0, 1]);
+        search.input = TextInput::with_value(String::from("cam3"));
+        assert_eq!(search.matches(&items), [1]);
+        search.input = TextInput::with_value(String::from("w"));
+        assert_eq!(search.matches(&items), [2]);
+    }
+
+    #[test]
+    fn cached_matches_are_kept_until_invalidated() {
+        let mut search = search("");
+        assert_eq!(search.matches(&["a", "b"]), [0, 1]);
+        // Without invalidating, the cached result for the old items is reused.
+        assert_eq!(search.matches(&["a", "b", "c"]), [0, 1]);
+        search.invalidate();
+        assert_eq!(search.matches(&["a", "b", "c"]), [0, 1, 2]);
+    }
+}
@@ -0,0 +1,41 @@
+//! What was being viewed when brptui last exited, restored on the next run.
+
+use crate::{config::config_dir, transport::Socket};
+use bevy_ecs::entity::Entity;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Session {
+    /// The address of the app last connected to.
+    pub socket: Option<Socket>,
+    /// The entity last selected in the entities list.
+    pub selected_entity: Option<Entity>,
+}
+
+impl Session {
+    /// Load the last session, or an empty one if there isn't one or it can't be read.
+    pub fn load() -> Self {
+        path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the session, failures are ignored since it is only a convenience.
+    pub fn save(&self) {
+        let Some(path) = path() else {
+            return;
+        };
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Ok(text) = serde_json::to_string_pretty(self) {
+            let _ = fs::write(path, text);
+        }
+    }
+}
+
+fn path() -> Option<PathBuf> {
+    Some(config_dir()?.join("session.json"))
+}
@@ -0,0 +1,150 @@
+//! Counts of the BRP requests made recently, shown in the debug overlay.
+
+use crate::theme::Theme;
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Style, Stylize},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Padding, Paragraph, Widget},
+};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+/// How far back requests are counted, making the counts per second.
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// The requests made within the last [`WINDOW`] by any thread.
+static RECENT: Mutex<VecDeque<Request>> = Mutex::new(VecDeque::new());
+
+#[derive(Debug)]
+struct Request {
+    at: Instant,
+    method: String,
+    sent: usize,
+    received: usize,
+}
+
+/// Record a request to the given method and the number of bytes sent and received.
+pub fn record(method: &str, sent: usize, received: usize) {
+    let mut recent = RECENT.lock().unwrap_or_else(PoisonError::into_inner);
+    let now = Instant::now();
+    prune(&mut recent, now);
+    recent.push_back(Request {
+        at: now,
+        method: method.to_string(),
+        sent,
+        received,
+    });
+}
+
+fn prune(recent: &mut VecDeque<Request>, now: Instant) {
+    while recent
+        .front()
+        .is_some_and(|request| now.duration_since(request.at) > WINDOW)
+    {
+        recent.pop_front();
+    }
+}
+
+/// The number of requests made to each method and the bytes transferred in the last second.
+#[derive(Debug, Default)]
+pub struct RequestRates {
+    methods: BTreeMap<String, usize>,
+    sent: usize,
+    received: usize,
+    theme: Theme,
+}
+
+impl RequestRates {
+    pub fn now() -> Self {
+        let mut recent = RECENT.lock().unwrap_or_else(PoisonError::into_inner);
+        prune(&mut recent, Instant::now());
+        let mut rates = Self::default();
+        for request in recent.iter() {
+            *rates.methods.entry(request.method.clone()).or_default() += 1;
+            rates.sent += request.sent;
+            rates.received += request.received;
+        }
+        rates
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    fn lines(&self) -> Vec<Line<'_>> {
+        let method_width = self.methods.keys().map(String::len).max().unwrap_or(0);
+        let mut lines: Vec<_> = self
+            .methods
+            .iter()
+            .map(|(method, count)| {
+                Line::from(vec![
+                    Span::raw(format!("{method:method_width$}  ")),
+                    Span::raw(count.to_string()).bold(),
+                ])
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push(Line::styled("No requests", self.theme.dim()));
+        }
+        lines.push(Line::default());
+        lines.push(Line::from(vec![
+            Span::styled("sent ", self.theme.dim()),
+            Span::raw(format_bytes(self.sent)),
+        ]));
+        lines.push(Line::from(vec![
+            Span::styled("received ", self.theme.dim()),
+            Span::raw(format_bytes(self.received)),
+        ]));
+        lines
+    }
+}
+
+/// Rendered in the top right corner of the given area.
+impl Widget for &RequestRates {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let lines = self.lines();
+        // Account for the borders and the padding.
+        let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4;
+        let height = lines.len() as u16 + 2;
+        let overlay_area = Rect {
+            x: area.right().saturating_sub(width).max(area.x),
+            y: area.y,
+            width: width.min(area.width),
+            height: height.min(area.height),
+        };
+
+        let block = Block::default()
+            .title(Line::from(" requests/s ").bold())
+            .borders(Borders::ALL)
+            .border_set(self.theme.border_set(border::THICK))
+            .border_style(Style::default().fg(self.theme.accent))
+            .padding(Padding::horizontal(1));
+
+        Clear.render(overlay_area, buf);
+        Paragraph::new(lines).block(block).render(overlay_area, buf);
+    }
+}
+
+/// The given number of bytes in the largest unit it is at least one of.
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 3] = ["KB", "MB", "GB"];
+    if bytes < 1000 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1000.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if value < 1000.0 {
+            break;
+        }
+        value /= 1000.0;
+        unit = next;
+    }
+    format!("{value:.1} {unit}")
+}
@@ -0,0 +1,59 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// A single line of text being typed by the user.
+#[derive(Debug, Default, Clone)]
+pub struct TextInput {
+    value: String,
+}
+
+/// The outcome of giving a key to a [`TextInput`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputResult {
+    /// The text was changed.
+    Changed,
+    /// The user pressed enter.
+    Submitted,
+    /// The user pressed escape.
+    Cancelled,
+    /// The key isn't used for text input and should be handled elsewhere.
+    Ignored,
+}
+
+impl TextInput {
+    /// An input with the given text already typed.
+    pub fn with_value(value: impl Into<String>) -> Self {
+        Self {
+            value: value.into(),
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn clear(&mut self) {
+        self.value.clear();
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> InputResult {
+        if key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return InputResult::Ignored;
+        }
+        match key.code {
+            KeyCode::Char(ch) => {
+                self.value.push(ch);
+                InputResult::Changed
+            }
+            KeyCode::Backspace => {
+                self.value.pop();
+                InputResult::Changed
+            }
+            KeyCode::Enter => InputResult::Submitted,
+            KeyCode::Esc => InputResult::Cancelled,
+            _ => InputResult::Ignored,
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! The colors brptui is drawn with.
+
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Style, Stylize},
+    symbols::border,
+};
+use serde::Deserialize;
+
+/// The colors used across the UI, read from the `theme` of the config. Colors are given as names
+/// like `"red"`, hex like `"#25a065"` or indices into the terminal's palette like `"10"`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Theme {
+    /// Focused borders, the title and other highlights.
+    pub accent: Color,
+    /// The selected item of a focused list or inspector.
+    pub selection: Color,
+    /// Errors and failed actions.
+    pub error: Color,
+    /// Values that changed, such as in the diff against a snapshot.
+    pub changed: Color,
+    /// Hints, annotations and other secondary text. [`Color::Reset`] dims the text instead.
+    pub dim: Color,
+    /// Behind everything. [`Color::Reset`] keeps the terminal's own.
+    pub background: Color,
+    /// Draw with just ASCII, for terminals and fonts without the other symbols. Set with `--ascii`
+    /// or `ascii` in the config rather than in the theme.
+    #[serde(skip)]
+    pub ascii: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        let green = Color::Rgb(37, 160, 101);
+        Self {
+            accent: green,
+            selection: green,
+            error: Color::Red,
+            changed: Color::Yellow,
+            dim: Color::Reset,
+            background: Color::Reset,
+            ascii: false,
+        }
+    }
+}
+
+impl Theme {
+    /// The style of secondary text.
+    pub fn dim(&self) -> Style {
+        match self.dim {
+            Color::Reset => Style::default().dim(),
+            color => Style::default().fg(color),
+        }
+    }
+
+    /// `unicode`, or `ascii` when drawing with just ASCII.
+    pub fn symbol<'a>(&self, unicode: &'a str, ascii: &'a str) -> &'a str {
+        match self.ascii {
+            true => ascii,
+            false => unicode,
+        }
+    }
+
+    /// The cursor at the end of text being typed.
+    pub fn cursor(&self) -> &'static str {
+        self.symbol("▏", "|")
+    }
+
+    /// The mark at the end of text that is cut short.
+    pub fn ellipsis(&self) -> &'static str {
+        self.symbol("…", "...")
+    }
+
+    /// The given borders, or ones drawn with `+`, `-` and `|` when drawing with just ASCII.
+    pub fn border_set(&self, set: border::Set) -> border::Set {
+        match self.ascii {
+            true => ASCII_BORDER,
+            false => set,
+        }
+    }
+
+    /// The label of a key with arrows swapped for ASCII when drawing with just ASCII, each taking
+    /// a single column like the arrow it replaces.
+    pub fn keys(&self, keys: &str) -> String {
+        if !self.ascii {
+            return keys.to_string();
+        }
+        keys.chars()
+            .map(|ch| match ch {
+                '←' => '<',
+                '→' => '>',
+                '↑' => '^',
+                '↓' => 'v',
+                ch => ch,
+            })
+            .collect()
+    }
+}
+
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+/// Reverse the colors of the text drawn in the given row, up to its last character, to draw the
+/// eye to a selection that just moved.
+pub fn flash(buf: &mut Buffer, row: Rect) {
+    let end = (row.left()..row.right())
+        .rev()
+        .find(|&x| buf[(x, row.y)].symbol() != " ");
+    if let Some(end) = end {
+        let text = Rect {
+            width: end + 1 - row.x,
+            height: 1,
+            ..row
+        };
+        buf.set_style(text, Style::new().reversed());
+    }
+}
@@ -0,0 +1,434 @@
+//! How requests reach the app, over TCP or a Unix domain socket.
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    fmt,
+    io::Read,
+    net::{SocketAddr, TcpStream},
+    str::FromStr,
+    time::Duration,
+};
+#[cfg(unix)]
+use std::{
+    io::{ErrorKind, Write},
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+};
+
+/// The prefix of addresses that are paths to Unix domain sockets.
+const UNIX_PREFIX: &str = "unix:";
+/// How long to wait for a connection when checking whether anything is listening.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+/// How long reading or writing over a Unix domain socket can stall before the request fails.
+#[cfg(unix)]
+const UNIX_IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Sends the body of a JSON-RPC request to the app, returning the body of the response.
+pub trait Transport {
+    fn post(&self, body: &[u8]) -> anyhow::Result<Vec<u8>>;
+}
+
+/// Where the app can be reached, given as an address like `127.0.0.1:15702` or a path to a Unix
+/// domain socket like `unix:/tmp/app.sock`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Socket {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl Socket {
+    /// A `curl` command posting the given request body to the socket, quoted for POSIX shells.
+    pub fn curl_command(&self, body: &str) -> String {
+        let target = match self {
+            Socket::Tcp(addr) => shell_quote(&format!("http://{addr}")),
+            #[cfg(unix)]
+            Socket::Unix(path) => format!(
+                "--unix-socket {} http://localhost",
+                shell_quote(&path.to_string_lossy())
+            ),
+        };
+        format!(
+            "curl -X POST {target} -H 'Content-Type: application/json' -d {}",
+            shell_quote(body)
+        )
+    }
+
+    /// Whether anything accepts a connection on the socket, regardless of what it is.
+    pub fn is_listening(&self) -> bool {
+        match self {
+            Socket::Tcp(addr) => TcpStream::connect_timeout(addr, CONNECT_TIMEOUT).is_ok(),
+            #[cfg(unix)]
+            Socket::Unix(path) => UnixStream::connect(path).is_ok(),
+        }
+    }
+}
+
+impl Transport for Socket {
+    fn post(&self, body: &[u8]) -> anyhow::Result<Vec<u8>> {
+        match self {
+            Socket::Tcp(addr) => addr.post(body),
+            #[cfg(unix)]
+            Socket::Unix(path) => path.as_path().post(body),
+        }
+    }
+}
+
+impl Transport for SocketAddr {
+    fn post(&self, body: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let response = ureq::post(&format!("http://{self}"))
+            .set("Content-Type", "application/json")
+            .send_bytes(body)?;
+        let mut received = Vec::new();
+        response.into_reader().read_to_end(&mut received)?;
+        Ok(received)
+    }
+}
+
+/// The same HTTP request as over TCP, written by hand since ureq only speaks TCP.
+#[cfg(unix)]
+impl Transport for Path {
+    fn post(&self, body: &[u8]) -> anyhow::Result<Vec<u8>> {
+        post_unix(self, body, UNIX_IO_TIMEOUT)
+    }
+}
+
+/// Post `body` over the Unix domain socket at `path`, failing if reading or writing stalls for
+/// longer than `timeout`.
+///
+/// The connection is asked to close after the response, but the response is read only until it
+/// is complete in case the other end keeps it open anyway.
+#[cfg(unix)]
+fn post_unix(path: &Path, body: &[u8], timeout: Duration) -> anyhow::Result<Vec<u8>> {
+    let mut stream = UnixStream::connect(path)
+        .with_context(|| format!("Failed to connect to {}", path.display()))?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    write!(
+        stream,
+        "POST / HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(body)?;
+
+    let mut response = Vec::new();
+    let mut buf = vec![0; 64 * 1024];
+    while !is_complete(&response) {
+        match stream.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => response.extend_from_slice(&buf[..n]),
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                bail!("Timed out waiting for a response from {}", path.display())
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+    response_body(response)
+}
+
+/// Wrap `text` in single quotes, which keep everything within them literal apart from single
+/// quotes themselves, so those are closed, escaped and reopened.
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// How the end of the body of an HTTP response is found.
+#[cfg(unix)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Framing {
+    Chunked,
+    Length(usize),
+    /// The body is everything until the connection closes.
+    UntilClose,
+}
+
+/// The head of an HTTP response, everything before the blank line, and the body after it. `None`
+/// until the blank line has arrived.
+#[cfg(unix)]
+fn split_response(response: &[u8]) -> Option<(&[u8], &[u8])> {
+    let header_end = response.windows(4).position(|w| w == b"\r\n\r\n")?;
+    Some((&response[..header_end], &response[header_end + 4..]))
+}
+
+/// The status code of an HTTP response and how its body is framed, read from its head.
+#[cfg(unix)]
+fn parse_head(head: &[u8]) -> anyhow::Result<(u16, Framing)> {
+    let head = String::from_utf8_lossy(head);
+    let mut lines = head.lines();
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse::<u16>().ok())
+        .context("Malformed HTTP status line")?;
+    let mut chunked = false;
+    let mut content_length = None;
+    for (name, value) in lines.filter_map(|line| line.split_once(':')) {
+        if name.eq_ignore_ascii_case("transfer-encoding") {
+            chunked = value.trim() == "chunked";
+        } else if name.eq_ignore_ascii_case("content-length") {
+            content_length = Some(
+                value
+                    .trim()
+                    .parse::<usize>()
+                    .context("Malformed Content-Length")?,
+            );
+        }
+    }
+    let framing = match (chunked, content_length) {
+        (true, _) => Framing::Chunked,
+        (false, Some(len)) => Framing::Length(len),
+        (false, None) => Framing::UntilClose,
+    };
+    Ok((status, framing))
+}
+
+/// Whether all of an HTTP response has arrived, so reading can stop without waiting for the
+/// connection to close. Malformed responses are complete as there is no use waiting for more.
+#[cfg(unix)]
+fn is_complete(response: &[u8]) -> bool {
+    let Some((head, body)) = split_response(response) else {
+        return false;
+    };
+    match parse_head(head) {
+        // Checking the end first avoids decoding every chunk again after each read.
+        Ok((_, Framing::Chunked)) => body.ends_with(b"0\r\n\r\n") && decode_chunked(body).is_ok(),
+        Ok((_, Framing::Length(len))) => body.len() >= len,
+        Ok((_, Framing::UntilClose)) => false,
+        Err(_) => true,
+    }
+}
+
+/// The body of an HTTP response, or an error if the status isn't a success.
+#[cfg(unix)]
+fn response_body(response: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+    let (head, body) = split_response(&response).context("Incomplete HTTP response")?;
+    let (status, framing) = parse_head(head)?;
+    if status >= 400 {
+        bail!("Status code {status}");
+    }
+    match framing {
+        Framing::Chunked => decode_chunked(body),
+        Framing::Length(len) => Ok(body
+            .get(..len)
+            .context("Incomplete HTTP response body")?
+            .to_vec()),
+        Framing::UntilClose => Ok(body.to_vec()),
+    }
+}
+
+/// Join the chunks of a body sent with `Transfer-Encoding: chunked`.
+#[cfg(unix)]
+fn decode_chunked(mut body: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut decoded = Vec::new();
+    loop {
+        let line_end = body
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .context("Incomplete chunk")?;
+        let size = std::str::from_utf8(&body[..line_end])?;
+        // Chunk extensions after a `;` aren't used by anything brptui talks to.
+        let size = size.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size, 16).context("Malformed chunk size")?;
+        if size == 0 {
+            return Ok(decoded);
+        }
+        let chunk = body
+            .get(line_end + 2..line_end + 2 + size)
+            .context("Incomplete chunk")?;
+        decoded.extend_from_slice(chunk);
+        body = body.get(line_end + 4 + size..).unwrap_or_default();
+    }
+}
+
+impl fmt::Display for Socket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Socket::Tcp(addr) => addr.fmt(f),
+            #[cfg(unix)]
+            Socket::Unix(path) => write!(f, "{UNIX_PREFIX}{}", path.display()),
+        }
+    }
+}
+
+impl FromStr for Socket {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix(UNIX_PREFIX) {
+            #[cfg(unix)]
+            Some(path) => Ok(Socket::Unix(PathBuf::from(path))),
+            #[cfg(not(unix))]
+            Some(_) => bail!("Unix domain sockets aren't supported on this platform"),
+            None => Ok(Socket::Tcp(s.parse().context("Invalid socket address")?)),
+        }
+    }
+}
+
+/// Stored as the same text it is given as on the command line.
+impl Serialize for Socket {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Socket {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+
+    #[test]
+    fn reads_body_of_content_length() {
+        let response = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello".to_vec();
+        assert_eq!(response_body(response).unwrap(), b"hello");
+        // Anything after the length isn't part of the body.
+        let response = b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nhello".to_vec();
+        assert_eq!(response_body(response).unwrap(), b"he");
+    }
+
+    #[test]
+    fn reads_body_until_the_end_without_a_length() {
+        let response = b"HTTP/1.1 200 OK\r\nConnection: close\r\n\r\nhello".to_vec();
+        assert_eq!(response_body(response).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn joins_chunked_body() {
+        let response = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+            4\r\nwiki\r\n6;name=value\r\npedia \r\nC\r\nin \r\nchunks.\r\n0\r\n\r\n"
+            .to_vec();
+        assert_eq!(
+            response_body(response).unwrap(),
+            b"wikipedia in \r\nchunks."
+        );
+    }
+
+    #[test]
+    fn truncated_responses_are_errors() {
+        for response in [
+            &b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n"[..],
+            b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nhello",
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhel",
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n",
+        ] {
+            assert!(
+                response_body(response.to_vec()).is_err(),
+                "{}",
+                String::from_utf8_lossy(response)
+            );
+        }
+    }
+
+    #[test]
+    fn error_statuses_are_errors() {
+        let response = b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_vec();
+        assert_eq!(
+            response_body(response).unwrap_err().to_string(),
+            "Status code 404"
+        );
+        let response = b"garbage\r\n\r\n".to_vec();
+        assert!(response_body(response).is_err());
+    }
+
+    #[test]
+    fn responses_are_complete_once_their_body_arrives() {
+        assert!(!is_complete(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n"));
+        assert!(!is_complete(
+            b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhel"
+        ));
+        assert!(is_complete(
+            b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello"
+        ));
+
+        let chunked = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n";
+        let complete = |body: &[u8]| is_complete(&[&chunked[..], body].concat());
+        assert!(!complete(b"5\r\nhello\r\n"));
+        // Data that happens to end like the last chunk isn't the end.
+        assert!(!complete(b"7\r\nhello0\r\n\r\n"));
+        assert!(complete(b"5\r\nhello\r\n0\r\n\r\n"));
+
+        assert!(!is_complete(b"HTTP/1.1 200 OK\r\n\r\nhello"));
+    }
+
+    /// Serve a single request on a new Unix domain socket with `respond`, returning the path of
+    /// the socket and the thread serving it, which gives back the request it read.
+    fn serve(
+        name: &str,
+        respond: impl FnOnce(&mut UnixStream) + Send + 'static,
+    ) -> (PathBuf, std::thread::JoinHandle<String>) {
+        let path = std::env::temp_dir().join(format!("brptui-{name}-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"{}") {
+                let n = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            respond(&mut stream);
+            String::from_utf8(request).unwrap()
+        });
+        (path, server)
+    }
+
+    #[test]
+    fn posts_over_a_unix_socket() {
+        let (path, server) = serve("post", |stream| {
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n[]")
+                .unwrap();
+        });
+        let response = path.as_path().post(b"{}").unwrap();
+        let request = server.join().unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(response, b"[]");
+        assert!(request.starts_with("POST / HTTP/1.1\r\n"), "{request}");
+        assert!(request.contains("Content-Length: 2\r\n"), "{request}");
+    }
+
+    #[test]
+    fn stops_reading_once_the_response_is_complete() {
+        let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+        let (path, server) = serve("open", move |stream| {
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n")
+                .unwrap();
+            stream.write_all(b"2\r\n[]\r\n0\r\n\r\n").unwrap();
+            // The connection is kept open until the response has been read.
+            let _ = done_rx.recv();
+        });
+        let response = post_unix(&path, b"{}", Duration::from_secs(5));
+        done_tx.send(()).unwrap();
+        server.join().unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(response.unwrap(), b"[]");
+    }
+
+    #[test]
+    fn stalled_responses_time_out() {
+        let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+        let (path, server) = serve("stall", move |stream| {
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\n[")
+                .unwrap();
+            let _ = done_rx.recv();
+        });
+        let response = post_unix(&path, b"{}", Duration::from_millis(100));
+        done_tx.send(()).unwrap();
+        server.join().unwrap();
+        let _ = std::fs::remove_file(&path);
+        let err = response.unwrap_err().to_string();
+        assert!(err.starts_with("Timed out"), "{err}");
+    }
+}
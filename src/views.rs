@@ -0,0 +1,50 @@
+//! Named setups of the search, filters, component ordering, pins and focus, saved to switch
+//! between.
+
+use crate::{components::ComponentListOptions, config::config_dir, text_input::TextInput, Focus};
+use bevy_ecs::entity::Entity;
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::PathBuf};
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct View {
+    pub name: String,
+    /// The query of the entities search.
+    pub search: String,
+    /// The filter of the fields shown in the inspector.
+    pub filter: String,
+    pub components: ComponentListOptions,
+    pub pins: Vec<Entity>,
+    /// The index of the focused panel, from the entities panel on the left.
+    pub panel: usize,
+}
+
+/// The name of the view being typed before saving it.
+#[derive(Debug)]
+pub struct ViewNameInput {
+    pub input: TextInput,
+    /// The panel to focus once the view is saved or the input is cancelled.
+    pub return_focus: Focus,
+}
+
+/// Load the saved views, or none if there aren't any or they can't be read.
+pub fn load() -> Vec<View> {
+    path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// Save the views, replacing those saved before.
+pub fn save(views: &[View]) -> io::Result<()> {
+    let path = path().ok_or_else(|| io::Error::other("no config directory"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(views)?)
+}
+
+fn path() -> Option<PathBuf> {
+    Some(config_dir()?.join("views.json"))
+}
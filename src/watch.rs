@@ -0,0 +1,166 @@
+//! Values followed live in the watch pane, from any field of any component of any entity.
+
+use crate::{
+    brp::{self, EntityLabel, EntityMeta, QUERY_COOLDOWN},
+    inspector,
+    theme::Theme,
+    transport::Socket,
+    Message, ThreadQuitToken,
+};
+use bevy_ecs::entity::Entity;
+use bevy_remote::builtin_methods::{BrpGetParams, BrpGetResponse};
+use disqualified::ShortName;
+use ratatui::{
+    prelude::{BlockExt, Buffer, Rect},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Widget},
+};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+/// How often the watched values are fetched, slower than the entities since there may be many
+/// entities to fetch them from.
+const WATCH_COOLDOWN: Duration = QUERY_COOLDOWN.saturating_mul(5);
+
+/// A field being watched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Watch {
+    pub entity: Entity,
+    pub component: String,
+    /// The path of the field as used by the inspector, empty for the whole component.
+    pub path: String,
+}
+
+/// The latest values of the watched components by entity and type path. Components missing from
+/// it are no longer on their entity, or the entity is gone.
+pub type WatchValues = HashMap<(Entity, String), Value>;
+
+/// Fetch the components of the `watches` every [`WATCH_COOLDOWN`] until quit with the `quit`
+/// token, sending them in [`Message::UpdateWatches`].
+pub fn handle_watch_querying(
+    tx: mpsc::Sender<Message>,
+    socket: &Socket,
+    watches: Vec<Watch>,
+    quit: ThreadQuitToken,
+) {
+    let mut by_entity: HashMap<Entity, Vec<String>> = HashMap::new();
+    for watch in watches {
+        let components = by_entity.entry(watch.entity).or_default();
+        if !components.contains(&watch.component) {
+            components.push(watch.component);
+        }
+    }
+
+    let mut last_time = Instant::now();
+    loop {
+        if quit.should_quit() {
+            return;
+        }
+
+        let mut values = WatchValues::new();
+        for (&entity, components) in &by_entity {
+            let params = BrpGetParams {
+                entity,
+                components: components.clone(),
+                strict: false,
+            };
+            // Despawned entities fail the request, their watches are left without values.
+            let Ok(BrpGetResponse::Lenient { components, .. } | BrpGetResponse::Strict(components)) =
+                brp::get_request(socket, params)
+            else {
+                continue;
+            };
+            values.extend(
+                components
+                    .into_iter()
+                    .map(|(type_path, value)| ((entity, type_path), value)),
+            );
+        }
+        if quit.should_quit() || tx.send(Message::UpdateWatches(values)).is_err() {
+            return;
+        }
+
+        std::thread::sleep(brp::request_delay(WATCH_COOLDOWN, last_time));
+        last_time = Instant::now();
+    }
+}
+
+/// Widget listing watched fields with their latest values, dimming those that are gone.
+pub struct WatchPane<'a> {
+    watches: &'a [Watch],
+    values: &'a WatchValues,
+    /// The entities to label watches with, by id if they aren't among them.
+    entities: &'a [EntityMeta],
+    label: EntityLabel,
+    block: Option<Block<'a>>,
+    theme: Theme,
+}
+
+impl<'a> WatchPane<'a> {
+    pub fn new(watches: &'a [Watch], values: &'a WatchValues) -> Self {
+        Self {
+            watches,
+            values,
+            entities: &[],
+            label: EntityLabel::default(),
+            block: None,
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn entities(mut self, entities: &'a [EntityMeta], label: EntityLabel) -> Self {
+        self.entities = entities;
+        self.label = label;
+        self
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+}
+
+impl Widget for WatchPane<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if let Some(block) = &self.block {
+            block.render(area, buf);
+        }
+        let area = self.block.inner_if_some(area);
+
+        for (y, watch) in self.watches.iter().take(area.height as usize).enumerate() {
+            let label = self
+                .entities
+                .iter()
+                .find(|meta| meta.id == watch.entity)
+                .map_or_else(|| watch.entity.to_string(), |meta| meta.label(self.label));
+            let value = self
+                .values
+                .get(&(watch.entity, watch.component.clone()))
+                .and_then(|value| inspector::value_at_path(value, &watch.path));
+            let mut line = Line::from(vec![
+                Span::raw(label).bold(),
+                Span::raw(" "),
+                Span::raw(format!("{}{}", ShortName(&watch.component), watch.path)),
+                Span::raw("  "),
+            ]);
+            match value {
+                Some(value) => line.push_span(Span::raw(value.to_string()).fg(self.theme.accent)),
+                None => {
+                    line.push_span(Span::raw("gone"));
+                    line = line.patch_style(self.theme.dim());
+                }
+            }
+            buf.set_line(area.x, area.y + y as u16, &line, area.width);
+        }
+    }
+}